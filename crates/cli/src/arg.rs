@@ -8,6 +8,22 @@ pub struct Options {
     #[clap(short, long)]
     pub config: Option<String>,
 
+    /// Credential store file, overrides `store_file` of the config
+    #[clap(short, long)]
+    pub store: Option<String>,
+
+    /// Encrypted credential vault, overrides `vault_file` of the config and `--store`
+    ///
+    /// The passphrase is read from `YXY_VAULT_PASSPHRASE` or prompted.
+    #[clap(long)]
+    pub vault: Option<String>,
+
+    /// Bound room to query electricity, by room name or code, overrides `room` of the config
+    ///
+    /// The last binding by default.
+    #[clap(long)]
+    pub room: Option<String>,
+
     /// Query
     #[clap(subcommand)]
     pub command: Option<Commands>,
@@ -32,6 +48,113 @@ pub enum Commands {
         /// Argument
         arg: String,
     },
+
+    /// Electricity of many rooms, listed in a CSV or YAML file
+    Rooms {
+        /// Rooms file, a `.csv` with a header or a YAML list,
+        /// of `area_id`, `building_code`, `floor_code` & `room_code`
+        file: String,
+
+        /// UID to query by
+        uid: String,
+
+        /// Sort rows by
+        #[clap(long, value_enum, default_value = "room")]
+        sort: RoomSort,
+
+        /// Sort in descending order
+        #[clap(short, long)]
+        reverse: bool,
+
+        /// Maximum queries in flight
+        #[clap(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// Milliseconds between the starts of two queries
+        #[clap(long, default_value_t = 500)]
+        interval: u64,
+    },
+
+    /// Pick a room of the directory and print it as an item of rooms files
+    Room {
+        /// UID to query by
+        uid: String,
+
+        /// Path of names like `Building 10/1F/101`, chosen level by level if not set
+        path: Option<String>,
+    },
+
+    /// Bind, rebind or unbind electricity rooms
+    Bind {
+        #[clap(subcommand)]
+        command: BindCommand,
+    },
+
+    /// Encrypted credential vault
+    Vault {
+        #[clap(subcommand)]
+        command: VaultCommand,
+    },
+}
+
+/// The binding to change or remove is chosen by `--room`, the last binding by default
+#[derive(Subcommand, Debug)]
+pub enum BindCommand {
+    /// List bound rooms
+    List {
+        /// UID to query by
+        uid: String,
+    },
+
+    /// Bind a room of the directory
+    Add {
+        /// UID to query by
+        uid: String,
+
+        /// Path of names like `Building 10/1F/101`, chosen level by level if not set
+        path: Option<String>,
+    },
+
+    /// Change the room of a binding
+    Change {
+        /// UID to query by
+        uid: String,
+
+        /// Path of names of the new room, chosen level by level if not set
+        path: Option<String>,
+    },
+
+    /// Remove a binding
+    Remove {
+        /// UID to query by
+        uid: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum VaultCommand {
+    /// Create an empty vault
+    Create {
+        /// Vault file
+        path: String,
+    },
+
+    /// Unlock the vault and list its accounts
+    Unlock {
+        /// Vault file
+        path: String,
+    },
+
+    /// Import credentials of plaintext configs (`conf.yaml`, `test.yaml`) & credential store files (`*.json`),
+    /// the vault is created if not exists
+    Migrate {
+        /// Vault file
+        path: String,
+
+        /// Plaintext files
+        #[clap(required = true)]
+        sources: Vec<String>,
+    },
 }
 
 #[derive(ValueEnum, Clone, Debug)]
@@ -44,3 +167,15 @@ pub enum Query {
     #[clap(name = "uid")]
     Uid,
 }
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum RoomSort {
+    /// Room name
+    Room,
+
+    /// Surplus
+    Soc,
+
+    /// Room status
+    Status,
+}
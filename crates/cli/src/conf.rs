@@ -1,10 +1,18 @@
 use serde::Deserialize;
 use std::error::Error;
+use yxy::KilowattHours;
 
 /// Configuration file
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub uid: String,
+    /// Name or code of the bound room to query, the last binding if not set
+    pub room: Option<String>,
+    /// Encrypted credential vault, see `yxy::store::VaultStore`
+    pub vault_file: Option<String>,
+    /// Credential store file, see `yxy::store::FileStore`
+    pub store_file: Option<String>,
+    /// Raw session cache, ignored if a credential store is set
     pub cookie_file: Option<String>,
     pub server_chan: Option<ServerChan>,
 }
@@ -13,7 +21,8 @@ pub struct Config {
 pub struct ServerChan {
     pub key: String,
     pub title: String,
-    pub warning_threshold: f32,
+    /// Surplus in kW·h, below which the warning is pushed
+    pub warning_threshold: KilowattHours,
     pub warning_title: String,
     pub log_level: u8,
 }
@@ -2,12 +2,18 @@
 //!
 //! CLI for YXY
 
+use std::sync::Arc;
+
 use clap::Parser;
+use yxy::store::{CredentialStore, FileStore};
 use yxy::*;
 
 mod arg;
 mod conf;
+mod picker;
+mod rooms;
 mod utils;
+mod vault;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<yxy::error::Error>> {
@@ -17,13 +23,67 @@ async fn main() -> Result<(), Box<yxy::error::Error>> {
         match v {
             arg::Commands::Query { query: q, arg: a } => match q {
                 arg::Query::Uid => {
-                    query_uid(&a, opts.verbose).await?;
+                    let store = open_store(opts.vault, opts.store)?;
+                    query_uid(&a, store.as_deref(), opts.verbose).await?;
                 }
                 arg::Query::Electricity => {
-                    let (result, _session) = query_ele(&a, None, opts.verbose).await?;
+                    let app = match open_store(opts.vault, opts.store)? {
+                        Some(store) => app_session_from_store(store, &a, opts.verbose)?,
+                        None => app_session(&a, None, opts.verbose)?,
+                    };
+                    let result = query_ele(&app, opts.room.as_deref(), opts.verbose).await?;
                     print_ele(&result);
                 }
             },
+            arg::Commands::Rooms {
+                file,
+                uid,
+                sort,
+                reverse,
+                concurrency,
+                interval,
+            } => {
+                let rooms = rooms::read(&file)?;
+                let app = match open_store(opts.vault, opts.store)? {
+                    Some(store) => app_session_from_store(store, &uid, opts.verbose)?,
+                    None => app_session(&uid, None, opts.verbose)?,
+                };
+                let options = yxy::bind::bulk::BulkOptions {
+                    concurrency,
+                    interval: std::time::Duration::from_millis(interval),
+                };
+                rooms::query(&app, rooms, &options, sort, reverse).await;
+            }
+            arg::Commands::Room { uid, path } => {
+                let app = match open_store(opts.vault, opts.store)? {
+                    Some(store) => app_session_from_store(store, &uid, opts.verbose)?,
+                    None => app_session(&uid, None, opts.verbose)?,
+                };
+                let room = picker::pick(&app, path.as_deref()).await?;
+                match serde_yaml::to_string(&[room]) {
+                    Ok(v) => print!("{}", v),
+                    Err(e) => return Err(Box::new(error::Error::Runtime(e.to_string()))),
+                }
+            }
+            arg::Commands::Bind { command } => {
+                bind(command, opts.vault, opts.store, opts.room, opts.verbose).await?;
+            }
+            arg::Commands::Vault { command } => match command {
+                arg::VaultCommand::Create { path } => {
+                    vault::create(&path)?;
+                    println!("Vault created: {}", path);
+                }
+                arg::VaultCommand::Unlock { path } => {
+                    vault::list(&vault::unlock(&path)?)?;
+                }
+                arg::VaultCommand::Migrate { path, sources } => {
+                    let vault = match std::path::Path::new(&path).exists() {
+                        true => vault::unlock(&path)?,
+                        false => vault::create(&path)?,
+                    };
+                    vault::migrate(&vault, &sources)?;
+                }
+            },
         }
     } else {
         let conf_path = match &opts.config {
@@ -41,34 +101,49 @@ async fn main() -> Result<(), Box<yxy::error::Error>> {
             }
         };
 
-        // Read the session cache
-        let session = match &conf.cookie_file {
-            None => None,
-            Some(cookie_file) => match tokio::fs::read_to_string(cookie_file).await {
-                Ok(v) => {
-                    if opts.verbose {
-                        println!("Using cached session id: {}", v);
+        let room = opts.room.or(conf.room);
+        let room = room.as_deref();
+        let store = open_store(
+            opts.vault.or(conf.vault_file),
+            opts.store.or(conf.store_file),
+        )?;
+        let result = match (store, &conf.cookie_file) {
+            (Some(store), _) => {
+                let app = app_session_from_store(store, &conf.uid, opts.verbose)?;
+                query_ele(&app, room, opts.verbose).await?
+            }
+            (None, Some(cookie_file)) => {
+                // Read the session cache
+                let session = match tokio::fs::read_to_string(cookie_file).await {
+                    Ok(v) => {
+                        if opts.verbose {
+                            println!("Using cached session id: {}", v);
+                        }
+                        Some(v)
                     }
-                    Some(v)
-                }
-                Err(e) => {
-                    eprintln!("Session cache file reading error: {}", e);
-                    None
-                }
-            },
-        };
+                    Err(e) => {
+                        eprintln!("Session cache file reading error: {}", e);
+                        None
+                    }
+                };
 
-        // Default query electricity
-        let (result, session) = query_ele(&conf.uid, session, opts.verbose).await?;
+                let app = app_session(&conf.uid, session, opts.verbose)?;
+                let result = query_ele(&app, room, opts.verbose).await?;
+
+                // Cache the session
+                if let Err(e) = yxy::utils::file_write(cookie_file, &app.session().unwrap()) {
+                    eprintln!("Fail to cache the session id: {}", e);
+                } else if opts.verbose {
+                    println!("Session cached.")
+                }
 
-        // Cache the session
-        if let Some(cookie_file) = &conf.cookie_file {
-            if let Err(e) = yxy::utils::file_write(cookie_file, &session.unwrap()) {
-                eprintln!("Fail to cache the session id: {}", e);
-            } else if opts.verbose {
-                println!("Session cached.")
+                result
             }
-        }
+            (None, None) => {
+                let app = app_session(&conf.uid, None, opts.verbose)?;
+                query_ele(&app, room, opts.verbose).await?
+            }
+        };
 
         // Notification
         if opts.notify {
@@ -159,7 +234,13 @@ pub fn fmt_ele_md(info: &yxy::SurplusInfo) -> String {
 }
 
 /// Query UID procedure
-async fn query_uid(phone_num: &str, verbose: bool) -> Result<(), yxy::error::Error> {
+///
+/// Credentials of the login are saved to `store` if any, or printed.
+async fn query_uid(
+    phone_num: &str,
+    store: Option<&dyn CredentialStore>,
+    verbose: bool,
+) -> Result<(), yxy::error::Error> {
     let handler = yxy::bind::campus::login::LoginHandler::new()?;
 
     println!("Querying security token...");
@@ -211,6 +292,14 @@ async fn query_uid(phone_num: &str, verbose: bool) -> Result<(), yxy::error::Err
     }
     println!("Login successfully. Here is your uid & other information:");
 
+    let token = match store {
+        Some(store) => {
+            store.update(&result.id, &mut |c| c.set_login(&result))?;
+            "(saved to the credential store)".to_string()
+        }
+        None => result.token.clone(),
+    };
+
     // stdout infos
     println!(
         "
@@ -226,7 +315,7 @@ async fn query_uid(phone_num: &str, verbose: bool) -> Result<(), yxy::error::Err
 
     ",
         result.id,
-        result.token,
+        token,
         result.device_id,
         result.job_no.unwrap_or_default(),
         result.user_idcard.unwrap_or_default(),
@@ -237,91 +326,159 @@ async fn query_uid(phone_num: &str, verbose: bool) -> Result<(), yxy::error::Err
     Ok(())
 }
 
-/// Procedure of query electricity
-async fn query_ele(
+/// Session of the UID, re-authorized if expired
+fn app_session(
     uid: &str,
-    mut session: Option<String>,
+    session: Option<String>,
     verbose: bool,
-) -> Result<(SurplusInfo, Option<String>), error::Error> {
-    let mut tried = false;
-    loop {
-        if session.is_none() {
-            let (ses, _) = app_auth(uid, verbose).await?;
-            session.replace(ses);
-        }
-        match app_query_ele(session.as_ref().unwrap(), verbose).await {
-            Err(e) => {
-                // Handle errors
-                match e {
-                    error::Error::Auth(_) => {
-                        if tried {
-                            return Err(error::Error::Auth(
-                                "Maximum auth retry number reached.".into(),
-                            ));
-                        }
-                        session.take();
-                        if verbose {
-                            eprintln!("Auth may expired, trying to reauthorize.")
-                        }
-                    }
-                    _ => return Err(e),
-                }
-                tried = true;
-            }
-            Ok(v) => {
-                return Ok((v, session));
-            }
-        }
-    }
+) -> Result<AppSession, error::Error> {
+    let app = AppSession::new(uid, session)?;
+
+    Ok(log_session(app, verbose))
 }
 
-/// Authorization sub-procedure
-async fn app_auth(id: &str, verbose: bool) -> Result<(String, UserInfo), error::Error> {
-    let client = bind::build_non_redirect_client()?;
+/// Credential store of the options, the vault takes precedence
+fn open_store(
+    vault: Option<String>,
+    store: Option<String>,
+) -> Result<Option<Arc<dyn CredentialStore>>, error::Error> {
+    Ok(match (vault, store) {
+        (Some(path), _) => Some(Arc::new(vault::unlock(&path)?)),
+        (None, Some(path)) => Some(Arc::new(FileStore::new(path))),
+        (None, None) => None,
+    })
+}
 
-    if verbose {
-        println!("Trying to get oauth code...");
-        let oauth_code = bind::app::auth::get_oauth_code(&client, id).await?;
-        println!("OAuth Code: {}", oauth_code);
+/// Session of the UID in the credential store
+fn app_session_from_store(
+    store: Arc<dyn CredentialStore>,
+    uid: &str,
+    verbose: bool,
+) -> Result<AppSession, error::Error> {
+    let app = AppSession::from_store(store, uid, HandlerConfig::default())?;
+    if verbose && app.session().is_some() {
+        println!("Using stored session.");
+    }
 
-        println!("Trying to auth...");
-        let (ses, user) = bind::app::auth::authorize(&client, &oauth_code).await?;
-        println!("Authorized, the session id is: {}", ses);
+    Ok(log_session(app, verbose))
+}
 
-        Ok((ses, user))
+fn log_session(app: AppSession, verbose: bool) -> AppSession {
+    if verbose {
+        app.on_change(|session| println!("Authorized, the session id is: {}", session))
     } else {
-        let oauth_code = bind::app::auth::get_oauth_code(&client, id).await?;
-
-        let (ses, user) = bind::app::auth::authorize(&client, &oauth_code).await?;
-
-        Ok((ses, user))
+        app
     }
 }
 
-/// Application sub-procedure
-async fn app_query_ele(session: &str, verbose: bool) -> Result<SurplusInfo, error::Error> {
-    // Init authorized handler
-    let handler = bind::app::AppHandler::build(session)?;
-
-    // Query Bind Info
+/// Procedure of query electricity
+///
+/// Of the bound room named `room` if any, or else the last binding.
+async fn query_ele(
+    app: &AppSession,
+    room: Option<&str>,
+    verbose: bool,
+) -> Result<SurplusInfo, error::Error> {
     if verbose {
         println!("Querying bind info...");
-        let bind_info = handler.binding_info().await?;
+    }
+    let bind_info = match room {
+        Some(room) => select_binding(app.bindings().await?, room)?,
+        None => app.binding_info().await?,
+    };
+    if verbose {
         println!("Bind info: {:?}", bind_info);
-
-        // Query Electricity Info
         println!("Query electricity info...");
+    }
 
-        let electricity_info = handler.surplus(&yxy::RoomInfo::from(bind_info)).await?;
+    let electricity_info = app.surplus(&yxy::RoomInfo::from(bind_info)).await?;
+    if verbose {
         println!("Electricity info: {:?}", electricity_info);
+    }
 
-        Ok(electricity_info)
-    } else {
-        let bind_info = handler.binding_info().await?;
+    Ok(electricity_info)
+}
 
-        // Query Electricity Info
-        let electricity_info = handler.surplus(&yxy::RoomInfo::from(bind_info)).await?;
+/// Procedure of binding changes
+async fn bind(
+    command: arg::BindCommand,
+    vault: Option<String>,
+    store: Option<String>,
+    room: Option<String>,
+    verbose: bool,
+) -> Result<(), error::Error> {
+    let uid = match &command {
+        arg::BindCommand::List { uid }
+        | arg::BindCommand::Add { uid, .. }
+        | arg::BindCommand::Change { uid, .. }
+        | arg::BindCommand::Remove { uid } => uid,
+    };
+    let app = match open_store(vault, store)? {
+        Some(store) => app_session_from_store(store, uid, verbose)?,
+        None => app_session(uid, None, verbose)?,
+    };
+    // The binding to change or remove
+    let binding = || async {
+        match room.as_deref() {
+            Some(room) => select_binding(app.bindings().await?, room),
+            None => app.binding_info().await,
+        }
+    };
+    let name = |b: &BindInfo| format!("{} {} {}", b.building_name, b.floor_name, b.room_name);
+
+    match command {
+        arg::BindCommand::List { .. } => {
+            for b in app.bindings().await? {
+                let lock = if b.is_changeable() { "" } else { " (locked)" };
+                println!("{}{}", name(&b), lock);
+            }
+        }
+        arg::BindCommand::Add { path, .. } => {
+            let room = picker::pick(&app, path.as_deref()).await?;
+            app.bind_room(&room).await?;
+            println!("Bound");
+        }
+        arg::BindCommand::Change { path, .. } => {
+            let binding = binding().await?;
+            let room = picker::pick(&app, path.as_deref()).await?;
+            app.rebind_room(&binding, &room).await?;
+            println!("Changed binding of {}", name(&binding));
+        }
+        arg::BindCommand::Remove { .. } => {
+            let binding = binding().await?;
+            app.unbind_room(&binding).await?;
+            println!("Removed binding of {}", name(&binding));
+        }
+    }
 
-        Ok(electricity_info)
+    Ok(())
+}
+
+/// The only binding matching `room`, see [`BindInfo::matches_room`]
+fn select_binding(bindings: Vec<BindInfo>, room: &str) -> Result<BindInfo, error::Error> {
+    if bindings.is_empty() {
+        return Err(error::Error::NoBind);
+    }
+    let names = || {
+        bindings
+            .iter()
+            .map(|b| format!("{} {} {}", b.building_name, b.floor_name, b.room_name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut matched = bindings.iter().filter(|b| b.matches_room(room));
+    match (matched.next(), matched.next()) {
+        (Some(bind), None) => Ok(bind.clone()),
+        (Some(_), Some(_)) => Err(error::Error::BadInput(format!(
+            "Room {:?} matches several bindings: {}",
+            room,
+            names()
+        ))),
+        (None, _) => Err(error::Error::BadInput(format!(
+            "No bound room {:?}, bound rooms: {}",
+            room,
+            names()
+        ))),
     }
 }
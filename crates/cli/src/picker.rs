@@ -0,0 +1,119 @@
+//! Interactive room picker of the room directory
+//!
+//! Prompts are written to stderr, so the picked room can be redirected.
+
+use std::io::Write;
+
+use yxy::bind::app::directory::{select, DirectoryNode, RoomEntry};
+use yxy::error::Error;
+use yxy::{AppSession, RoomInfo};
+
+/// Pick a room by `path` if any, prompting among several matches, or else level by level
+pub async fn pick(app: &AppSession, path: Option<&str>) -> Result<RoomInfo, Error> {
+    let entry = match path {
+        Some(path) => {
+            let found = app.find_rooms(path).await?;
+            if found.is_empty() {
+                return Err(Error::BadInput(format!("No room found: {:?}", path)));
+            }
+            choose("room", &found, |e| e.to_string())?.clone()
+        }
+        None => browse(app).await?,
+    };
+
+    eprintln!("Room: {}", entry);
+    Ok(entry.room)
+}
+
+/// Choose the area, building, floor and room in turn
+async fn browse(app: &AppSession) -> Result<RoomEntry, Error> {
+    let areas = app.areas().await?;
+    let area = choose_node("area", &areas)?;
+    let buildings = app.buildings(&area.id).await?;
+    let building = choose_node("building", &buildings)?;
+    let floors = app.floors(&area.id, &building.code).await?;
+    let floor = choose_node("floor", &floors)?;
+    let rooms = app.rooms(&area.id, &building.code, &floor.code).await?;
+    let room = choose_node("room", &rooms)?;
+
+    Ok(RoomEntry {
+        room: RoomInfo {
+            area_id: area.id.clone(),
+            building_code: building.code.clone(),
+            floor_code: floor.code.clone(),
+            room_code: room.code.clone(),
+        },
+        area_name: area.name.clone(),
+        building_name: building.name.clone(),
+        floor_name: floor.name.clone(),
+        room_name: room.name.clone(),
+    })
+}
+
+/// Choose a node, the input is a number of the list or a name to narrow it
+fn choose_node<'a, N: DirectoryNode>(level: &str, nodes: &'a [N]) -> Result<&'a N, Error> {
+    let mut candidates: Vec<&N> = nodes.iter().collect();
+    loop {
+        let input = match prompt(level, &candidates, |n| n.name().to_string())? {
+            Choice::Picked(node) => return Ok(node),
+            Choice::Filter(input) => input,
+        };
+
+        let matched: Vec<&N> = select(nodes, Some(&input));
+        if matched.is_empty() {
+            eprintln!("No {} matches {:?}", level, input);
+        } else {
+            candidates = matched;
+        }
+    }
+}
+
+/// Choose an item by its number
+fn choose<'a, T>(
+    level: &str,
+    items: &'a [T],
+    label: impl Fn(&T) -> String,
+) -> Result<&'a T, Error> {
+    let candidates: Vec<&T> = items.iter().collect();
+    loop {
+        if let Choice::Picked(item) = prompt(level, &candidates, &label)? {
+            return Ok(item);
+        }
+    }
+}
+
+enum Choice<'a, T> {
+    Picked(&'a T),
+    Filter(String),
+}
+
+/// List `candidates` and read the choice, the only candidate is picked without asking
+fn prompt<'a, T>(
+    level: &str,
+    candidates: &[&'a T],
+    label: impl Fn(&T) -> String,
+) -> Result<Choice<'a, T>, Error> {
+    match candidates {
+        [] => return Err(Error::BadInput(format!("No {} to choose", level))),
+        [only] => return Ok(Choice::Picked(only)),
+        _ => {}
+    }
+
+    eprintln!("Choose the {}:", level);
+    for (i, item) in candidates.iter().enumerate() {
+        eprintln!("{:>4}. {}", i + 1, label(item));
+    }
+    eprint!("Number or name: ");
+    std::io::stderr().flush()?;
+
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input)? == 0 {
+        return Err(Error::BadInput("No room chosen".to_string()));
+    }
+    let input = input.trim();
+
+    Ok(match input.parse::<usize>() {
+        Ok(n) if (1..=candidates.len()).contains(&n) => Choice::Picked(candidates[n - 1]),
+        _ => Choice::Filter(input.to_string()),
+    })
+}
@@ -0,0 +1,137 @@
+//! Electricity of many rooms
+
+use std::path::Path;
+
+use yxy::bind::bulk::BulkOptions;
+use yxy::error::Error;
+use yxy::{AppSession, RoomInfo, SurplusInfo};
+
+use crate::arg::RoomSort;
+
+/// Read rooms of a CSV file with a header, or of a YAML list
+///
+/// Columns or keys are the fields of [`RoomInfo`], like `area_id` or `areaId`.
+pub fn read(path: &str) -> Result<Vec<RoomInfo>, Error> {
+    let is_csv = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let bad_file = |e: &dyn std::fmt::Display| Error::BadInput(format!("{}: {}", path, e));
+
+    let file = std::fs::File::open(path)?;
+    if is_csv {
+        csv::Reader::from_reader(file)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(|e| bad_file(&e))
+    } else {
+        serde_yaml::from_reader(file).map_err(|e| bad_file(&e))
+    }
+}
+
+/// Row of the table
+struct Row {
+    room: RoomInfo,
+    result: Result<SurplusInfo, Error>,
+}
+
+impl Row {
+    fn name(&self) -> String {
+        match self.result {
+            Ok(ref info) if !info.display_room_name.is_empty() => info.display_room_name.clone(),
+            _ => format!(
+                "{}-{}-{}",
+                self.room.building_code, self.room.floor_code, self.room.room_code
+            ),
+        }
+    }
+
+    fn status(&self) -> &str {
+        match self.result {
+            Ok(ref info) => info
+                .surplus_list
+                .first()
+                .map_or("", |s| s.room_status.as_str()),
+            Err(_) => "",
+        }
+    }
+}
+
+/// Query all rooms and print a table, sorted by `sort`
+///
+/// Failed rooms are listed with the error, after the others.
+pub async fn query(
+    app: &AppSession,
+    rooms: Vec<RoomInfo>,
+    options: &BulkOptions,
+    sort: RoomSort,
+    reverse: bool,
+) {
+    let results = app.surplus_many(&rooms, options).await;
+    let mut rows: Vec<_> = rooms
+        .into_iter()
+        .zip(results)
+        .map(|(room, result)| Row { room, result })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        let ordering = match sort {
+            RoomSort::Room => a.name().cmp(&b.name()),
+            RoomSort::Soc => soc(a).cmp(&soc(b)),
+            RoomSort::Status => a.status().cmp(b.status()),
+        };
+        match reverse {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    });
+    rows.sort_by_key(|row| row.result.is_err());
+
+    print_table(&rows);
+}
+
+fn soc(row: &Row) -> Option<yxy::KilowattHours> {
+    row.result.as_ref().ok().map(|info| info.soc)
+}
+
+fn print_table(rows: &[Row]) {
+    let cells: Vec<[String; 3]> = rows
+        .iter()
+        .map(|row| match row.result {
+            Ok(ref info) => [row.name(), info.soc.to_string(), row.status().to_string()],
+            Err(ref e) => [row.name(), "-".to_string(), format!("Error: {}", e)],
+        })
+        .collect();
+
+    let header = ["Room", "Surplus (kW·h)", "Status"];
+    let mut widths = header.map(|h| h.chars().count());
+    for (row, cells) in rows.iter().zip(&cells) {
+        // Errors overflow the last column
+        let columns = if row.result.is_ok() { 3 } else { 2 };
+        for (width, cell) in widths.iter_mut().zip(cells).take(columns) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    println!(
+        "{:<w0$}  {:>w1$}  {}",
+        header[0],
+        header[1],
+        header[2],
+        w0 = widths[0],
+        w1 = widths[1]
+    );
+    println!("{}", "-".repeat(widths.iter().sum::<usize>() + 4));
+    for [name, soc, status] in &cells {
+        println!(
+            "{:<w0$}  {:>w1$}  {}",
+            name,
+            soc,
+            status,
+            w0 = widths[0],
+            w1 = widths[1]
+        );
+    }
+
+    let failed = rows.iter().filter(|row| row.result.is_err()).count();
+    println!("\n{} room(s), {} failed", rows.len(), failed);
+}
@@ -12,7 +12,7 @@ pub async fn push_message(
 ) -> Result<(String, String), yxy::error::Error> {
     let client = Client::new();
     let resp = client
-        .post(&format!("{}{}.send", SERVER_CHAN, key))
+        .post(format!("{}{}.send", SERVER_CHAN, key))
         .form(&vec![("title", title), ("desp", desp)])
         .send()
         .await?;
@@ -0,0 +1,161 @@
+//! Encrypted credential vault commands
+
+use std::path::Path;
+
+use serde::Deserialize;
+use yxy::error::Error;
+use yxy::store::{CredentialStore, FileStore, Token, VaultStore};
+
+/// Passphrase of the vault, prompted if not set
+const PASSPHRASE_ENV: &str = "YXY_VAULT_PASSPHRASE";
+
+/// Unlock the vault at `path`
+pub fn unlock(path: &str) -> Result<VaultStore, Error> {
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(v) => v,
+        Err(_) => rpassword::prompt_password("Vault passphrase: ")?,
+    };
+
+    let vault = VaultStore::open(path);
+    vault.unlock(&passphrase)?;
+
+    Ok(vault)
+}
+
+/// Create an empty vault at `path`
+pub fn create(path: &str) -> Result<VaultStore, Error> {
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(v) => v,
+        Err(_) => {
+            let passphrase = rpassword::prompt_password("New vault passphrase: ")?;
+            if passphrase != rpassword::prompt_password("Repeat the passphrase: ")? {
+                return Err(Error::BadInput("Passphrases do not match".to_string()));
+            }
+            passphrase
+        }
+    };
+    if passphrase.is_empty() {
+        return Err(Error::BadInput("Empty passphrase".to_string()));
+    }
+
+    VaultStore::create(path, &passphrase)
+}
+
+/// Print the accounts of the vault, without secrets
+pub fn list(vault: &VaultStore) -> Result<(), Error> {
+    let accounts = vault.accounts()?;
+    println!(
+        "{} account(s) in {}",
+        accounts.len(),
+        vault.path().display()
+    );
+
+    for uid in accounts {
+        let Some(c) = vault.load(&uid)? else {
+            continue;
+        };
+        println!(
+            "
+UID: {}
+    Device ID: {}
+    School code: {}
+    App session: {}
+    Campus token: {}
+    Updated at: {}",
+            uid,
+            c.device_id.as_deref().unwrap_or("-"),
+            c.school_code.as_deref().unwrap_or("-"),
+            issued(&c.app_session),
+            issued(&c.campus_token),
+            c.updated_at,
+        );
+    }
+
+    Ok(())
+}
+
+fn issued(token: &Option<Token>) -> String {
+    match token {
+        Some(token) => format!("issued at {}", token.issued_at),
+        None => "-".to_string(),
+    }
+}
+
+/// Plaintext config with credentials, e.g. `conf.yaml` of the CLI or `test.yaml` of tests
+#[derive(Debug, Deserialize)]
+struct PlainConfig {
+    uid: String,
+    /// Raw session cache
+    cookie_file: Option<String>,
+    session_token: Option<String>,
+    device_id: Option<String>,
+    campus_token: Option<String>,
+    #[serde(alias = "school_id")]
+    school_code: Option<String>,
+}
+
+/// Import the credentials of plaintext configs & credential store files (`*.json`)
+pub fn migrate(vault: &VaultStore, sources: &[String]) -> Result<(), Error> {
+    for source in sources {
+        let count = if source.ends_with(".json") {
+            migrate_store(vault, source)?
+        } else {
+            migrate_config(vault, source)?;
+            1
+        };
+        println!("Migrated {} account(s) from {}", count, source);
+    }
+
+    println!("The plaintext files are kept, remove the secrets in them once the vault works.");
+    Ok(())
+}
+
+fn migrate_store(vault: &VaultStore, path: &str) -> Result<usize, Error> {
+    if !Path::new(path).exists() {
+        return Err(Error::BadInput(format!("{} not found", path)));
+    }
+
+    let store = FileStore::new(path);
+    let accounts = store.accounts()?;
+    for uid in &accounts {
+        if let Some(credentials) = store.load(uid)? {
+            vault.save(&credentials)?;
+        }
+    }
+
+    Ok(accounts.len())
+}
+
+fn migrate_config(vault: &VaultStore, path: &str) -> Result<(), Error> {
+    let config: PlainConfig = serde_yaml::from_reader(std::fs::File::open(path)?)
+        .map_err(|e| Error::BadInput(format!("{}: {}", path, e)))?;
+
+    let session = match config.cookie_file {
+        Some(ref cookie_file) => match std::fs::read_to_string(cookie_file) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                eprintln!("Session cache file reading error: {}", e);
+                config.session_token.clone()
+            }
+        },
+        None => config.session_token.clone(),
+    };
+
+    // Keep the stored values which are not in the config
+    vault.update(&config.uid, &mut |c| {
+        if let Some(ref session) = session {
+            c.app_session = Some(Token::new(session));
+        }
+        if let Some(ref token) = config.campus_token {
+            c.campus_token = Some(Token::new(token));
+        }
+        if config.device_id.is_some() {
+            c.device_id.clone_from(&config.device_id);
+        }
+        if config.school_code.is_some() {
+            c.school_code.clone_from(&config.school_code);
+        }
+    })?;
+
+    Ok(())
+}
@@ -1,39 +1,10 @@
 //! Application authorize APIs
 
-use reqwest::{
-    cookie::{Cookie, Jar},
-    Client,
-};
-use serde::Deserialize;
-use std::{sync::Arc, time::Duration};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
 
 use super::*;
-
-/// Extract code value from redirection URL query string
-///
-/// # Example match URL
-///
-/// ```text
-/// https://application.xiaofubao.com/?code=b3cb4e67111b453488d826ba4397d921&errCode=0&status=null
-/// ```
-///
-/// If matched, return the `code` value
-fn extract_code(url: &str) -> Option<String> {
-    match reqwest::Url::parse(url) {
-        Ok(url) => {
-            let query = url.query_pairs();
-
-            for (key, value) in query {
-                if key == "ymCode" {
-                    return Some(value.to_string());
-                }
-            }
-
-            None
-        }
-        Err(_) => None,
-    }
-}
+use protocol::auth::{Authorize, GetOAuthCode, GetUserInfo};
 
 /// Request and extract code from redirect `Location`
 ///
@@ -64,150 +35,45 @@ fn extract_code(url: &str) -> Option<String> {
 /// ```
 ///
 /// **Must use a none redirect policy client**
-pub async fn get_oauth_code(client: &Client, id: &str) -> Result<String> {
-    let query = [
-        ("bindSkip", "1"),
-        ("authType", "2"),
-        ("appid", super::APP_ID),
-        (
-            "callbackUrl",
-            &format!("{}/", crate::url::application::BASE_URL),
-        ),
-        ("unionid", id),
-    ];
-
-    let response = client
-        .get(crate::url::auth::OAUTH_URL)
-        .query(&query)
-        .send()
-        .await?;
-
-    if !response.status().is_redirection() {
-        return Err(Error::Auth("OAuth failed.".to_string()));
-    }
-
-    let header_location = match response.headers().get("Location") {
-        Some(header) => header,
-        None => return Err(Error::EmptyResp),
-    };
-    println!("Location: {:?}", header_location);
-
-    match extract_code(header_location.to_str().unwrap()) {
-        Some(t) => Ok(t),
-        None => Err(Error::EmptyResp),
-    }
+pub async fn get_oauth_code(client: &Client, config: &HandlerConfig, id: &str) -> Result<String> {
+    send(client, config, &GetOAuthCode { uid: id }).await
 }
 
 /// Authorize the handler and fetch user infos
-pub async fn authorize(client: &Client, code: &str) -> Result<(String, UserInfo)> {
-    // Form data
-    let params = [("code", code)];
-
-    let mut response = client
-        .post(crate::url::application::GET_USER_FOR_AUTHORIZE)
-        .form(&params)
-        .send()
-        .await?;
-
-    crate::bind::check_response(&mut response).await?;
-
-    let cookies: Vec<Cookie> = response.cookies().collect();
-
-    // get session
-    match cookies.iter().find(|x| x.name() == super::SESSION_KEY) {
-        Some(v) => {
-            let session = v.value().to_string();
-            let resp = response.bytes().await?;
-
-            let resp: AuthResponse = match serde_json::from_slice(resp.as_ref()) {
-                Ok(v) => v,
-                Err(e) => return Err(Error::Deserialize(e, resp)),
-            };
-            if !resp.success {
-                return Err(Error::Auth(resp.message));
-            }
-            match resp.data {
-                Some(v) => Ok((session, v)),
-                None => Err(Error::Auth(resp.message)),
-            }
-        }
-        None => {
-            let resp_ser: AuthResponse = response.json().await?;
-            Err(Error::Auth(resp_ser.message))
-        }
-    }
+pub async fn authorize(
+    client: &Client,
+    config: &HandlerConfig,
+    code: &str,
+) -> Result<(String, UserInfo)> {
+    send(client, config, &Authorize { code }).await
 }
 
 impl super::AppHandler {
     /// Create new app handler with authorize
     pub async fn build_by_uid(uid: &str) -> Result<Self> {
-        // Store session in cookie jar
-        let jar = Jar::default();
-
-        let client = Client::builder()
-            .connect_timeout(Duration::new(5, 0))
-            .user_agent(crate::bind::USER_AGENT)
-            .redirect(reqwest::redirect::Policy::none())
-            .cookie_provider(Arc::new(jar))
-            .build()?;
-
-        let code = get_oauth_code(&client, uid).await?;
-
-        // Form data
-        let params = [("code", code.as_str())];
-
-        let mut response = client
-            .post(crate::url::application::GET_USER_FOR_AUTHORIZE)
-            .form(&params)
-            .send()
-            .await?;
-
-        crate::bind::check_response(&mut response).await?;
-
-        let resp = response.bytes().await?;
-
-        let resp: AuthResponse = match serde_json::from_slice(resp.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, resp)),
-        };
-        if !resp.success {
-            return Err(Error::Auth(resp.message));
-        }
+        Self::build_by_uid_with_config(uid, HandlerConfig::default()).await
+    }
 
-        Ok(Self { client })
+    /// Create new app handler with authorize and specific [`HandlerConfig`]
+    pub async fn build_by_uid_with_config(uid: &str, config: HandlerConfig) -> Result<Self> {
+        let client = init_app_client(&config)?;
+        let code = get_oauth_code(&client, &config, uid).await?;
+        let (session, _) = authorize(&client, &config, &code).await?;
+
+        Ok(Self {
+            client,
+            config,
+            session,
+        })
     }
 
     /// Get user info
     pub async fn user_info(&self) -> Result<UserInfo> {
-        // Form data
-        let params = [("userId", rand::random::<u8>())];
-
-        let mut response = self
-            .client
-            .post(crate::url::application::GET_USER_FOR_AUTHORIZE)
-            .form(&params)
-            .send()
-            .await?;
-        crate::bind::check_response(&mut response).await?;
-
-        let resp: AuthResponse = response.json().await?;
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
+        let call = GetUserInfo {
+            session: &self.session,
+        };
 
-            return Err(Error::Runtime(format!(
-                "Get user info failed: {}",
-                resp.message
-            )));
-        }
-        match resp.data {
-            Some(v) => Ok(v),
-            None => Err(Error::Runtime(format!(
-                "Get user info failed: {}",
-                resp.message
-            ))),
-        }
+        send(&self.client, &self.config, &call).await
     }
 }
 
@@ -215,33 +81,33 @@ impl super::AppHandler {
 // ====== Models ======
 // ====================
 
-/// Authorize API response definition
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AuthResponse {
-    status_code: i32,
-    message: String,
-    success: bool,
-    data: Option<UserInfo>,
-}
-
 /// User info provided by platform
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UserInfo {
     /// UID: User ID
     pub id: String,
+    #[serde(alias = "mobilePhone")]
     pub mobile_phone: String,
     pub sex: i8,
+    #[serde(alias = "testAccount")]
     pub test_account: i8,
     pub platform: String,
+    #[serde(alias = "thirdOpenid")]
     pub third_openid: String,
+    #[serde(alias = "schoolCode")]
     pub school_code: Option<String>,
+    #[serde(alias = "schoolName")]
     pub school_name: Option<String>,
+    #[serde(alias = "userName")]
     pub user_name: Option<String>,
+    #[serde(alias = "userType")]
     pub user_type: Option<String>,
+    #[serde(alias = "jobNo")]
     pub job_no: Option<String>,
+    #[serde(alias = "userIdcard")]
     pub user_idcard: Option<String>,
+    #[serde(alias = "userClass")]
     pub user_class: Option<String>,
+    #[serde(alias = "bindCardStatus")]
     pub bind_card_status: Option<i8>,
 }
@@ -0,0 +1,424 @@
+//! Room directory
+//!
+//! Areas, buildings, floors and rooms of the school, to build a [`RoomInfo`] without a binding.
+//! Each level is listed by the codes of the upper ones, or a room is searched by its names:
+//!
+//! ```no_run
+//! # async fn run(app: yxy::AppSession) -> Result<(), yxy::error::Error> {
+//! let room = app.resolve_room("Building 10/1F/101").await?;
+//! let info = app.surplus(&room).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::bind::bulk::{self, BulkOptions};
+use crate::error::Error;
+use electricity::RoomInfo;
+use protocol::directory::*;
+
+/// Node of the room directory
+pub trait DirectoryNode {
+    fn code(&self) -> &str;
+    fn name(&self) -> &str;
+}
+
+/// Area, like a campus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Area {
+    #[serde(alias = "areaId")]
+    pub id: String,
+    #[serde(alias = "areaName")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Building {
+    #[serde(alias = "buildingCode")]
+    pub code: String,
+    #[serde(alias = "buildingName")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Floor {
+    #[serde(alias = "floorCode")]
+    pub code: String,
+    #[serde(alias = "floorName")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Room {
+    #[serde(alias = "roomCode")]
+    pub code: String,
+    #[serde(alias = "roomName")]
+    pub name: String,
+}
+
+impl DirectoryNode for Area {
+    fn code(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+macro_rules! directory_node {
+    ($($node:ty),*) => {
+        $(impl DirectoryNode for $node {
+            fn code(&self) -> &str {
+                &self.code
+            }
+
+            fn name(&self) -> &str {
+                &self.name
+            }
+        })*
+    };
+}
+
+directory_node!(Building, Floor, Room);
+
+/// Room found in the directory, with the names of each level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomEntry {
+    pub room: RoomInfo,
+    pub area_name: String,
+    pub building_name: String,
+    pub floor_name: String,
+    pub room_name: String,
+}
+
+impl fmt::Display for RoomEntry {
+    /// Path of names, like `Main/Building 10/1F/101`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}",
+            self.area_name, self.building_name, self.floor_name, self.room_name
+        )
+    }
+}
+
+/// Path of a room by the names or codes of its levels, separated by `/`
+///
+/// - `building/room`
+/// - `building/floor/room`
+/// - `area/building/floor/room`
+///
+/// A name matches a node of the same name or code, ignoring ASCII case and spaces,
+/// or else every node containing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomQuery {
+    pub area: Option<String>,
+    pub building: String,
+    pub floor: Option<String>,
+    pub room: String,
+}
+
+impl FromStr for RoomQuery {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<_> = s.split('/').map(str::trim).collect();
+        if parts.iter().any(|p| p.is_empty()) {
+            return Err(Error::BadInput(format!("Invalid room path: {:?}", s)));
+        }
+
+        let owned = |i: usize| parts[i].to_string();
+        match parts.len() {
+            2 => Ok(Self {
+                area: None,
+                building: owned(0),
+                floor: None,
+                room: owned(1),
+            }),
+            3 => Ok(Self {
+                area: None,
+                building: owned(0),
+                floor: Some(owned(1)),
+                room: owned(2),
+            }),
+            4 => Ok(Self {
+                area: Some(owned(0)),
+                building: owned(1),
+                floor: Some(owned(2)),
+                room: owned(3),
+            }),
+            _ => Err(Error::BadInput(format!(
+                "Room path needs a building and a room: {:?}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Name for comparison, ignoring ASCII case and whitespace
+pub(crate) fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Nodes matching `pattern`, all of them if `None`
+pub fn select<'a, N: DirectoryNode>(nodes: &'a [N], pattern: Option<&str>) -> Vec<&'a N> {
+    let Some(pattern) = pattern else {
+        return nodes.iter().collect();
+    };
+    let pattern = normalize(pattern);
+
+    let exact: Vec<_> = nodes
+        .iter()
+        .filter(|n| normalize(n.name()) == pattern || normalize(n.code()) == pattern)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    nodes
+        .iter()
+        .filter(|n| normalize(n.name()).contains(&pattern))
+        .collect()
+}
+
+/// Directory queries by a session
+pub(crate) struct Directory<'a> {
+    pub client: &'a Client,
+    pub config: &'a HandlerConfig,
+    pub session: &'a str,
+}
+
+impl Directory<'_> {
+    pub async fn areas(&self) -> Result<Vec<Area>> {
+        let call = QueryAreas {
+            session: self.session,
+        };
+
+        send(self.client, self.config, &call).await
+    }
+
+    pub async fn buildings(&self, area_id: &str) -> Result<Vec<Building>> {
+        let call = QueryBuildings {
+            session: self.session,
+            area_id,
+        };
+
+        send(self.client, self.config, &call).await
+    }
+
+    pub async fn floors(&self, area_id: &str, building_code: &str) -> Result<Vec<Floor>> {
+        let call = QueryFloors {
+            session: self.session,
+            area_id,
+            building_code,
+        };
+
+        send(self.client, self.config, &call).await
+    }
+
+    pub async fn rooms(
+        &self,
+        area_id: &str,
+        building_code: &str,
+        floor_code: &str,
+    ) -> Result<Vec<Room>> {
+        let call = QueryRooms {
+            session: self.session,
+            area_id,
+            building_code,
+            floor_code,
+        };
+
+        send(self.client, self.config, &call).await
+    }
+
+    /// Walk the levels matching `query`, the nodes of a level queried concurrently
+    pub async fn find_rooms(&self, query: &RoomQuery) -> Result<Vec<RoomEntry>> {
+        // Owned nodes, as the futures would not be `Send` otherwise
+        let areas = self.areas().await?;
+        let areas = select(&areas, query.area.as_deref()).into_iter().cloned();
+        let buildings = walk(areas, |area| async move {
+            let buildings = self.buildings(&area.id).await?;
+            Ok(select(&buildings, Some(&query.building))
+                .into_iter()
+                .map(|building| (area.clone(), building.clone()))
+                .collect())
+        })
+        .await?;
+
+        let floors = walk(buildings, |(area, building)| async move {
+            let floors = self.floors(&area.id, &building.code).await?;
+            Ok(select(&floors, query.floor.as_deref())
+                .into_iter()
+                .map(|floor| (area.clone(), building.clone(), floor.clone()))
+                .collect())
+        })
+        .await?;
+
+        walk(floors, |(area, building, floor)| async move {
+            let rooms = self.rooms(&area.id, &building.code, &floor.code).await?;
+            Ok(select(&rooms, Some(&query.room))
+                .into_iter()
+                .map(|room| RoomEntry {
+                    room: RoomInfo {
+                        area_id: area.id.clone(),
+                        building_code: building.code.clone(),
+                        floor_code: floor.code.clone(),
+                        room_code: room.code.clone(),
+                    },
+                    area_name: area.name.clone(),
+                    building_name: building.name.clone(),
+                    floor_name: floor.name.clone(),
+                    room_name: room.name.clone(),
+                })
+                .collect())
+        })
+        .await
+    }
+}
+
+/// Queries of a level in [`Directory::find_rooms`], paced by the limiter of the config
+const WALK: BulkOptions = BulkOptions {
+    concurrency: 4,
+    interval: Duration::ZERO,
+};
+
+/// Nodes below each of `items` in order, failed by the first error
+async fn walk<I, T, F, Fut>(items: I, f: F) -> Result<Vec<T>>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let levels = bulk::run(items, &WALK, f)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(levels.into_iter().flatten().collect())
+}
+
+/// The only room of `found`, [`Error::BadInput`] if none or several
+pub(crate) fn only_room(found: Vec<RoomEntry>, path: &str) -> Result<RoomInfo> {
+    match <[RoomEntry; 1]>::try_from(found) {
+        Ok([entry]) => Ok(entry.room),
+        Err(found) if found.is_empty() => {
+            Err(Error::BadInput(format!("No room found: {:?}", path)))
+        }
+        Err(found) => {
+            let names: Vec<_> = found.iter().map(ToString::to_string).collect();
+            Err(Error::BadInput(format!(
+                "Room {:?} matches {} rooms: {}",
+                path,
+                found.len(),
+                names.join(", ")
+            )))
+        }
+    }
+}
+
+impl AppHandler {
+    fn directory(&self) -> Directory<'_> {
+        Directory {
+            client: &self.client,
+            config: &self.config,
+            session: &self.session,
+        }
+    }
+
+    /// Query areas of the room directory
+    pub async fn areas(&self) -> Result<Vec<Area>> {
+        self.directory().areas().await
+    }
+
+    /// Query buildings of the area
+    pub async fn buildings(&self, area_id: &str) -> Result<Vec<Building>> {
+        self.directory().buildings(area_id).await
+    }
+
+    /// Query floors of the building
+    pub async fn floors(&self, area_id: &str, building_code: &str) -> Result<Vec<Floor>> {
+        self.directory().floors(area_id, building_code).await
+    }
+
+    /// Query rooms of the floor
+    pub async fn rooms(
+        &self,
+        area_id: &str,
+        building_code: &str,
+        floor_code: &str,
+    ) -> Result<Vec<Room>> {
+        self.directory()
+            .rooms(area_id, building_code, floor_code)
+            .await
+    }
+
+    /// Search rooms by a path of names, see [`RoomQuery`]
+    ///
+    /// Each matching area, building and floor is listed, so a specific path
+    /// takes fewer requests.
+    pub async fn find_rooms(&self, path: &str) -> Result<Vec<RoomEntry>> {
+        self.directory().find_rooms(&path.parse()?).await
+    }
+
+    /// The only room of the path, see [`Self::find_rooms`]
+    ///
+    /// [`Error::BadInput`] if no room or several rooms match.
+    pub async fn resolve_room(&self, path: &str) -> Result<RoomInfo> {
+        only_room(self.find_rooms(path).await?, path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn room(code: &str, name: &str) -> Room {
+        Room {
+            code: code.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_query_parse() {
+        let query: RoomQuery = "Building 10 / 101".parse().unwrap();
+        assert_eq!(query.building, "Building 10");
+        assert_eq!((query.floor, query.room.as_str()), (None, "101"));
+
+        let query: RoomQuery = "Main/Building 10/1F/101".parse().unwrap();
+        assert_eq!(query.area.as_deref(), Some("Main"));
+        assert_eq!(query.floor.as_deref(), Some("1F"));
+
+        assert!("101".parse::<RoomQuery>().is_err());
+        assert!("Building 10//101".parse::<RoomQuery>().is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        let rooms = [
+            room("10101", "101"),
+            room("10102", "1010"),
+            room("10201", "201"),
+        ];
+
+        let names = |found: Vec<&Room>| found.iter().map(|r| r.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(select(&rooms, Some("101"))), ["101"]);
+        assert_eq!(names(select(&rooms, Some("10102"))), ["1010"]);
+        assert_eq!(names(select(&rooms, Some("01"))), ["101", "1010", "201"]);
+        assert_eq!(select(&rooms, None).len(), 3);
+        assert!(select(&rooms, Some("301")).is_empty());
+    }
+}
@@ -1,78 +1,70 @@
 //! Electricity APIs
 
 use super::*;
-use crate::url::application::electricity::*;
+use protocol::electricity::*;
 
+use chrono::NaiveDate;
+use futures_core::Stream;
 use serde::{Deserialize, Serialize};
 
-const BIND_TYPE: &str = "3";
-const SUB_TYPE: &str = "100304";
+use super::directory::normalize;
+use crate::bind::bulk::{self, BulkOptions};
+use crate::bind::page::{paginate, Page};
+use crate::error::Error;
+use crate::time::Timestamp;
+use crate::units::{KilowattHours, Money};
 
 impl AppHandler {
     /// Query Bind infos
     ///
-    /// Only return one binding info from list
+    /// Only return the last binding info of the list, see [`Self::bindings`] for all
     pub async fn binding_info(&self) -> Result<BindInfo> {
-        let form = [("bindType", BIND_TYPE)];
-
-        let mut resp = self.client.post(QUERY_BIND).form(&form).send().await?;
-        check_response(&mut resp).await?;
-
-        let resp: Response<BindInfo> = resp.json().await?;
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
-            return Err(Error::Runtime(format!(
-                "Fail to query bind: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default()
-            )));
-        }
+        let call = QueryBind {
+            session: &self.session,
+        };
 
-        // Take data
-        if let Some(mut bind_info) = resp.rows {
-            Ok(match bind_info.pop() {
-                Some(v) => v,
-                None => return Err(Error::NoBind),
-            })
-        } else {
-            Err(Error::NoBind)
-        }
+        send(&self.client, &self.config, &call).await
+    }
+
+    /// Query all bindings, like a dorm room and a lab
+    ///
+    /// Empty if no room is bound.
+    pub async fn bindings(&self) -> Result<Vec<BindInfo>> {
+        let call = QueryBindings {
+            session: &self.session,
+        };
+
+        send(&self.client, &self.config, &call).await
     }
 
     /// Query electricity info
     ///
     /// Like surplus, subsidy, amount, etc.
     pub async fn surplus(&self, info: &RoomInfo) -> Result<SurplusInfo> {
-        let mut resp = self.client.post(QUERY_SURPLUS).form(&info).send().await?;
-        check_response(&mut resp).await?;
-
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Response {
-            pub status_code: i32,
-            pub success: bool,
-            pub message: String,
-            pub data: Option<SurplusInfo>,
-        }
-        let resp: Response = resp.json().await?;
-
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
-            return Err(Error::Runtime(format!(
-                "Fail to query surplus: ({}); {}",
-                resp.status_code, resp.message
-            )));
-        }
+        let call = QuerySurplus {
+            session: &self.session,
+            room: info,
+        };
 
-        if let Some(v) = resp.data {
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        send(&self.client, &self.config, &call).await
+    }
+
+    /// Query electricity info of many rooms, see [`crate::bind::bulk`]
+    ///
+    /// Results are in the order of `rooms`.
+    pub async fn surplus_many(
+        &self,
+        rooms: &[RoomInfo],
+        options: &BulkOptions,
+    ) -> Vec<Result<SurplusInfo>> {
+        // Owned rooms, as the futures would not be `Send` otherwise
+        let rooms = rooms.iter().cloned();
+        bulk::run(
+            rooms,
+            options,
+            |room| async move { self.surplus(&room).await },
+        )
+        .await
     }
 
     /// Query electricity usage records
@@ -84,145 +76,70 @@ impl AppHandler {
         room_info: &RoomInfo,
         md_type: &str,
     ) -> Result<Vec<UsageRecord>> {
-        let form = [
-            ("mdtype", md_type),
-            ("areaId", &room_info.area_id),
-            ("buildingCode", &room_info.building_code),
-            ("floorCode", &room_info.floor_code),
-            ("roomCode", &room_info.room_code),
-        ];
-
-        let mut resp = self
-            .client
-            .post(QUERY_USAGE_RECORDS)
-            .form(&form)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: Response<UsageRecord> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
+        let call = QueryUsageRecords {
+            session: &self.session,
+            room: room_info,
+            md_type,
         };
 
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
-            return Err(Error::Runtime(format!(
-                "Fail to query usage: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default(),
-            )));
-        }
-
-        match resp.rows {
-            Some(rows) if !rows.is_empty() => Ok(rows),
-            _ => Err(Error::EmptyResp),
-        }
+        send(&self.client, &self.config, &call).await
     }
 
     /// Query my recharge records
+    ///
+    /// Records of the month containing `month` if any, or of all months.
+    /// Pages are counted from 1.
     pub async fn user_recharge_records(
         &self,
         page: u32,
-        time: Option<&str>,
-    ) -> Result<Vec<UserRechargeRecord>> {
-        let page = page.to_string();
-
-        let mut resp = if let Some(t) = time {
-            let form = [
-                ("currentPage", page.as_str()),
-                ("subType", SUB_TYPE),
-                ("createTime", t),
-            ];
-            self.client
-                .post(QUERY_USER_RECHARGE_RECORDS)
-                .form(&form)
-                .send()
-                .await?
-        } else {
-            let form = [("currentPage", page.as_str()), ("subType", SUB_TYPE)];
-            self.client
-                .post(QUERY_USER_RECHARGE_RECORDS)
-                .form(&form)
-                .send()
-                .await?
+        month: Option<NaiveDate>,
+    ) -> Result<Page<UserRechargeRecord>> {
+        let call = QueryUserRechargeRecords {
+            session: &self.session,
+            page,
+            month,
         };
 
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: Response<UserRechargeRecord> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
-        };
-
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
-            return Err(Error::Runtime(format!(
-                "Fail to query user recharge record: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default(),
-            )));
-        }
-
-        match resp.rows {
-            Some(rows) if !rows.is_empty() => Ok(rows),
-            _ => Err(Error::EmptyResp),
-        }
+        send(&self.client, &self.config, &call).await
     }
 
+    /// Query recharge records of the room
+    ///
+    /// Pages are counted from 1.
     pub async fn room_recharge_records(
         &self,
         page: u32,
         room_info: &RoomInfo,
-    ) -> Result<Vec<RechargeRecord>> {
-        let page = page.to_string();
-        let form = [
-            ("currentPage", page.as_str()),
-            ("subType", SUB_TYPE),
-            ("areaId", &room_info.area_id),
-            ("buildingCode", &room_info.building_code),
-            ("floorCode", &room_info.floor_code),
-            ("roomCode", &room_info.room_code),
-        ];
-
-        let mut resp = self
-            .client
-            .post(QUERY_ROOM_RECHARGE_RECORDS)
-            .form(&form)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: Response<RechargeRecord> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
+    ) -> Result<Page<RechargeRecord>> {
+        let call = QueryRoomRechargeRecords {
+            session: &self.session,
+            page,
+            room: room_info,
         };
 
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
-            return Err(Error::Runtime(format!(
-                "Fail to query room recharge record: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default(),
-            )));
-        }
+        send(&self.client, &self.config, &call).await
+    }
 
-        match resp.rows {
-            Some(rows) if !rows.is_empty() => Ok(rows),
-            _ => Err(Error::EmptyResp),
-        }
+    /// All pages of [`Self::user_recharge_records`], at most `max_pages` requests
+    pub fn user_recharge_records_stream(
+        &self,
+        month: Option<NaiveDate>,
+        max_pages: Option<u32>,
+    ) -> impl Stream<Item = Result<UserRechargeRecord>> + '_ {
+        paginate(1, max_pages, move |page| {
+            self.user_recharge_records(page, month)
+        })
+    }
+
+    /// All pages of [`Self::room_recharge_records`], at most `max_pages` requests
+    pub fn room_recharge_records_stream<'a>(
+        &'a self,
+        room_info: &'a RoomInfo,
+        max_pages: Option<u32>,
+    ) -> impl Stream<Item = Result<RechargeRecord>> + 'a {
+        paginate(1, max_pages, move |page| {
+            self.room_recharge_records(page, room_info)
+        })
     }
 
     /// Create recharge transaction
@@ -242,69 +159,54 @@ impl AppHandler {
         submit_token: &str,
         uid: &str,
     ) -> Result<String> {
-        #[derive(Serialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Request<'a, 'b> {
-            pub area_id: &'a str,
-            pub building_code: &'a str,
-            pub floor_code: &'a str,
-            pub room_code: &'a str,
-            pub money: u32,
-            pub cztype: &'b str,
-            pub mdname: &'b str,
-            pub ym_id: &'b str,
-            pub submit_token: &'b str,
-        }
+        let call = Recharge {
+            session: &self.session,
+            room: info,
+            amount,
+            cztype,
+            mdname,
+            submit_token,
+            uid,
+        };
 
-        let mut resp = self
-            .client
-            .post(RECHARGE)
-            .form(&Request {
-                area_id: &info.area_id,
-                building_code: &info.building_code,
-                floor_code: &info.floor_code,
-                room_code: &info.room_code,
-                money: amount,
-                cztype,
-                mdname,
-                submit_token,
-                ym_id: uid,
-            })
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Response {
-            pub status_code: i32,
-            pub success: bool,
-            pub message: String,
-            /// Redirect URL
-            pub data: Option<String>,
-        }
+        send(&self.client, &self.config, &call).await
+    }
 
-        let resp: Response = resp.json().await?;
+    /// Bind a room, in addition to the current bindings
+    pub async fn bind_room(&self, room: &RoomInfo) -> Result<()> {
+        let call = BindRoom {
+            session: &self.session,
+            room,
+        };
 
-        if !resp.success {
-            if resp.status_code == 204 {
-                return Err(Error::Auth("Unauthorized".to_string()));
-            }
-            return Err(Error::Runtime(format!(
-                "Fail to recharge electricity: ({}); {}",
-                resp.status_code, resp.message,
-            )));
-        }
+        send(&self.client, &self.config, &call).await
+    }
 
-        let callback_url = reqwest::Url::parse(&resp.data.unwrap()).unwrap();
+    /// Change the room of a binding
+    ///
+    /// [`Error::BindNotAllowed`] if the binding can not be changed, see [`BindInfo::is_changeable`].
+    pub async fn rebind_room(&self, binding: &BindInfo, room: &RoomInfo) -> Result<()> {
+        binding.check_change()?;
+        let call = UpdateBind {
+            session: &self.session,
+            id: &binding.id,
+            room,
+        };
 
-        for (key, value) in callback_url.query_pairs() {
-            if key == "tran_no" {
-                return Ok(value.to_string());
-            }
-        }
+        send(&self.client, &self.config, &call).await
+    }
 
-        Err(Error::EmptyResp)
+    /// Remove a binding
+    ///
+    /// [`Error::BindNotAllowed`] if the binding can not be changed, see [`BindInfo::is_changeable`].
+    pub async fn unbind_room(&self, binding: &BindInfo) -> Result<()> {
+        binding.check_change()?;
+        let call = DeleteBind {
+            session: &self.session,
+            id: &binding.id,
+        };
+
+        send(&self.client, &self.config, &call).await
     }
 }
 
@@ -313,36 +215,89 @@ impl AppHandler {
 // ====================
 
 /// Electricity biding information
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BindInfo {
     pub id: String,
+    #[serde(alias = "schoolCode")]
     pub school_code: String,
+    #[serde(alias = "schoolName")]
     pub school_name: String,
+    #[serde(alias = "jobNo")]
     pub job_no: String,
+    #[serde(alias = "userName")]
     pub user_name: String,
+    #[serde(alias = "bindTypeStr")]
     pub bind_type_str: String,
+    #[serde(alias = "areaId")]
     pub area_id: String,
+    #[serde(alias = "areaName")]
     pub area_name: String,
+    #[serde(alias = "buildingCode")]
     pub building_code: String,
+    #[serde(alias = "buildingName")]
     pub building_name: String,
+    #[serde(alias = "floorCode")]
     pub floor_code: String,
+    #[serde(alias = "floorName")]
     pub floor_name: String,
+    #[serde(alias = "roomCode")]
     pub room_code: String,
+    #[serde(alias = "roomName")]
     pub room_name: String,
-    pub create_time: String,
+    #[serde(alias = "createTime")]
+    pub create_time: Timestamp,
+    /// `0` if the binding can not be changed or removed
+    #[serde(alias = "isAllowChange")]
     pub is_allow_change: u8,
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RoomInfo {
+    #[serde(alias = "areaId")]
     pub area_id: String,
+    #[serde(alias = "buildingCode")]
     pub building_code: String,
+    #[serde(alias = "floorCode")]
     pub floor_code: String,
+    #[serde(alias = "roomCode")]
     pub room_code: String,
 }
 
+impl BindInfo {
+    /// Whether the binding can be changed or removed
+    pub fn is_changeable(&self) -> bool {
+        self.is_allow_change != 0
+    }
+
+    /// [`Error::BindNotAllowed`] if not [`Self::is_changeable`]
+    pub(crate) fn check_change(&self) -> Result<()> {
+        match self.is_changeable() {
+            true => Ok(()),
+            false => Err(Error::BindNotAllowed(format!(
+                "Binding of {} is not allowed to change",
+                self.room_name
+            ))),
+        }
+    }
+
+    /// Whether the room is `name`, by the room name or code, or the full name
+    /// like `"Building 10 1F 101"`
+    ///
+    /// Compared ignoring ASCII case and spaces.
+    pub fn matches_room(&self, name: &str) -> bool {
+        let name = normalize(name);
+        let is_name = |s: &str| normalize(s) == name;
+
+        !name.is_empty()
+            && (is_name(&self.room_name)
+                || is_name(&self.room_code)
+                || is_name(&format!(
+                    "{}{}{}",
+                    self.building_name, self.floor_name, self.room_name
+                )))
+    }
+}
+
 impl From<&BindInfo> for RoomInfo {
     /// Extract [`RoomInfo`] from [`BindInfo`]
     ///
@@ -369,48 +324,60 @@ impl From<BindInfo> for RoomInfo {
     }
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SurplusInfo {
+    #[serde(alias = "schoolCode")]
     pub school_code: String,
+    #[serde(alias = "areaId")]
     pub area_id: String,
+    #[serde(alias = "buildingCode")]
     pub building_code: String,
+    #[serde(alias = "floorCode")]
     pub floor_code: String,
+    #[serde(alias = "roomCode")]
     pub room_code: String,
     /// Room name
+    #[serde(alias = "displayRoomName")]
     pub display_room_name: String,
     /// Unknown usage
     pub remind: String,
     /// Total surplus
-    pub soc: f32,
+    pub soc: KilowattHours,
     /// Total surplus amount
-    pub total_soc_amount: f32,
+    #[serde(alias = "totalSocAmount")]
+    pub total_soc_amount: Money,
+    #[serde(alias = "isAllowChange")]
     pub is_allow_change: u8,
+    #[serde(alias = "showType")]
     pub show_type: u8,
+    #[serde(alias = "recordShow")]
     pub record_show: u8,
     pub style: u8,
     /// Surplus details, usually contains only one element
+    #[serde(alias = "surplusList")]
     pub surplus_list: Vec<SurplusDetail>,
     /// Top up type, usually contains only one element
+    #[serde(alias = "topUpTypeList")]
     pub top_up_type_list: Vec<EleTopUpType>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SurplusDetail {
-    pub surplus: f32,
-    pub amount: f32,
-    pub subsidy: f32,
-    pub subsidy_amount: f32,
-    pub total_surplus: f32,
+    pub surplus: KilowattHours,
+    pub amount: Money,
+    pub subsidy: KilowattHours,
+    #[serde(alias = "subsidyAmount")]
+    pub subsidy_amount: Money,
+    #[serde(alias = "totalSurplus")]
+    pub total_surplus: KilowattHours,
     pub mdtype: String,
     pub mdname: String,
+    #[serde(alias = "roomStatus")]
     pub room_status: String,
 }
 
 /// Type of electricity top up
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EleTopUpType {
     /// type name
     pub mdname: String,
@@ -418,61 +385,64 @@ pub struct EleTopUpType {
     pub cztype: String,
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Response<T> {
-    pub status_code: i32,
-    pub success: bool,
-    pub total: Option<u32>,
-    pub message: Option<String>,
-    pub rows: Option<Vec<T>>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UserRechargeRecord {
     pub id: String,
+    #[serde(alias = "orderNo")]
     pub order_no: String,
-    pub pay_money: f64,
-    pub total_money: f64,
+    #[serde(alias = "payMoney")]
+    pub pay_money: Money,
+    #[serde(alias = "totalMoney")]
+    pub total_money: Money,
+    #[serde(alias = "payType")]
     pub pay_type: String,
+    #[serde(alias = "payNo")]
     pub pay_no: String,
-    pub create_time: String,
+    #[serde(alias = "createTime")]
+    pub create_time: Timestamp,
+    #[serde(alias = "payStatusStr")]
     pub pay_status_str: String,
+    #[serde(alias = "subType")]
     pub sub_type: String,
+    #[serde(alias = "prodName")]
     pub prod_name: String,
-    pub pay_time: String,
+    #[serde(alias = "payTime")]
+    pub pay_time: Timestamp,
     pub remark: String,
     pub logo: String,
-    pub fee_money: f64,
+    #[serde(alias = "feeMoney")]
+    pub fee_money: Money,
     pub week: String,
+    #[serde(alias = "dayDate")]
     pub day_date: String,
     pub month: String,
-    #[serde(rename = "centerOrderStatisticsVO")]
+    #[serde(alias = "centerOrderStatisticsVO")]
     pub center_order_statistics_vo: CenterOrderStatisticsVo,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CenterOrderStatisticsVo {
     pub months: String,
-    pub total_tran_money: String,
-    pub total_real_money: String,
+    #[serde(alias = "totalTranMoney")]
+    pub total_tran_money: Money,
+    #[serde(alias = "totalRealMoney")]
+    pub total_real_money: Money,
+    #[serde(alias = "totalCount")]
     pub total_count: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UsageRecord {
     pub roomdm: String,
-    pub datetime: String,
-    pub used: String,
+    pub datetime: Timestamp,
+    pub used: KilowattHours,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RechargeRecord {
     pub roomdm: String,
-    pub datetime: String,
+    pub datetime: Timestamp,
     pub buyusingtpe: String,
-    pub money: String,
+    pub money: Money,
     pub issend: String,
 }
@@ -2,45 +2,51 @@
 //!
 //! You should authorize before using any application API.
 
-use std::{sync::Arc, time::Duration};
-
-use reqwest::cookie::Jar;
 use reqwest::Client;
 
-use super::Result;
-use crate::bind::check_response;
-use crate::error::Error;
+use super::{send, HandlerConfig, Result};
+use crate::protocol::app as protocol;
 
 pub mod auth;
 pub mod common;
+pub mod directory;
 pub mod electricity;
+pub mod order;
+pub mod session;
 
-const APP_ID: &str = "1810181825222034";
-const SESSION_KEY: &str = "shiroJID";
+pub use session::AppSession;
 
 /// Authorized session handle
 pub struct AppHandler {
     client: Client,
+    config: HandlerConfig,
+    session: String,
 }
 
 impl AppHandler {
     /// Using session string to create
     pub fn build(session: &str) -> Result<Self> {
+        Self::build_with_config(session, HandlerConfig::default())
+    }
+
+    /// Using session string to create with specific [`HandlerConfig`]
+    pub fn build_with_config(session: &str, config: HandlerConfig) -> Result<Self> {
         Ok(Self {
-            client: {
-                // Store session in cookie jar
-                let jar = Jar::default();
-                jar.add_cookie_str(
-                    &format!("{}={}", SESSION_KEY, session),
-                    &reqwest::Url::parse(crate::url::application::BASE_URL).unwrap(),
-                );
-
-                Client::builder()
-                    .connect_timeout(Duration::new(5, 0))
-                    .user_agent(super::USER_AGENT)
-                    .cookie_provider(Arc::new(jar))
-                    .build()?
-            },
+            client: init_app_client(&config)?,
+            config,
+            session: session.to_string(),
         })
     }
+
+    /// Current session string
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+}
+
+/// Init application client
+///
+/// Redirections are not followed, as OAuth code is carried by the redirect `Location`.
+fn init_app_client(config: &HandlerConfig) -> Result<Client> {
+    super::build_non_redirect_client(config)
 }
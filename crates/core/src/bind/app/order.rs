@@ -0,0 +1,377 @@
+//! Recharge order tracking
+//!
+//! [`AppHandler::recharge`] only returns a transaction No., paid later by the cashier page.
+//! A [`RechargeOrder`] keeps what is needed to follow it: its status is read from the
+//! user recharge records, and the credit from the room surplus.
+//!
+//! ```no_run
+//! use yxy::bind::app::order::TrackOptions;
+//!
+//! # async fn run(app: yxy::AppSession, room: yxy::RoomInfo) -> Result<(), yxy::error::Error> {
+//! let order = app
+//!     .recharge_order(&room, 10, "50426", "照明用电", "token", "uid")
+//!     .await?;
+//! println!("Pay by {}", yxy::bind::pay::to_cashier(&order.tran_no));
+//!
+//! let outcome = app.track_order(&order, &TrackOptions::default()).await?;
+//! if let Some(kwh) = outcome.credited {
+//!     println!("{:?}: {} credited", outcome.status, kwh);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::pin::pin;
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use futures_core::Stream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::*;
+use crate::time::{cst, Timestamp};
+use crate::units::{KilowattHours, Money};
+use electricity::{RoomInfo, SurplusInfo, UserRechargeRecord};
+
+/// Records are created by the platform before [`RechargeOrder::create_time`],
+/// at most this many minutes
+const RECORD_LEAD_MINUTES: i64 = 10;
+
+/// Electricity recharge order, placed by [`AppHandler::recharge_order`]
+///
+/// Serializable, to be tracked by another process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RechargeOrder {
+    pub room: RoomInfo,
+    pub amount: Money,
+    pub cztype: String,
+    /// Transaction No., the `order_no` of [`UserRechargeRecord`]
+    pub tran_no: String,
+    /// Surplus of the room before the order, to detect the credit
+    pub soc_before: KilowattHours,
+    pub create_time: Timestamp,
+}
+
+impl RechargeOrder {
+    /// Order created now
+    pub(crate) fn new(
+        room: &RoomInfo,
+        amount: u32,
+        cztype: &str,
+        tran_no: String,
+        soc_before: KilowattHours,
+    ) -> Self {
+        Self {
+            room: room.clone(),
+            amount: Money::from_fen(i64::from(amount) * 100),
+            cztype: cztype.to_string(),
+            tran_no,
+            soc_before,
+            create_time: Utc::now().with_timezone(&cst()).into(),
+        }
+    }
+}
+
+/// Status of a [`RechargeOrder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// Not paid yet, or not listed in the records yet
+    Pending,
+    Paid,
+    /// Failed, closed or cancelled
+    Failed,
+    Refunded,
+}
+
+impl OrderStatus {
+    /// Status by the `pay_status_str` of a record, like `"支付成功"`
+    ///
+    /// Refund states come first, only a completed refund is [`Self::Refunded`] while
+    /// a failed one, like `"退款失败"`, leaves the order [`Self::Paid`]. Unknown statuses
+    /// are [`Self::Pending`].
+    pub fn parse(pay_status: &str) -> Self {
+        let has = |words: &[&str]| words.iter().any(|w| pay_status.contains(w));
+
+        let refund = has(&["退款"]);
+        if refund && has(&["成功", "已退款"]) {
+            Self::Refunded
+        } else if refund {
+            // Refunding, or the refund failed, so the payment stands
+            Self::Paid
+        } else if has(&["失败", "关闭", "取消", "超时"]) {
+            Self::Failed
+        } else if has(&["成功", "已支付"]) {
+            Self::Paid
+        } else {
+            Self::Pending
+        }
+    }
+
+    /// Whether the status is final
+    pub fn is_terminal(self) -> bool {
+        self != Self::Pending
+    }
+}
+
+/// Polling of [`AppHandler::track_order`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackOptions {
+    /// Time between two polls
+    pub interval: Duration,
+    /// Maximum time of tracking, the outcome so far is returned after it
+    pub timeout: Duration,
+}
+
+impl Default for TrackOptions {
+    /// Polled every 5 seconds, for 10 minutes
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+/// Result of [`AppHandler::track_order`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderOutcome {
+    pub status: OrderStatus,
+    /// Record of the order, once listed
+    pub record: Option<UserRechargeRecord>,
+    /// Increase of the surplus since the order, once it shows up
+    pub credited: Option<KilowattHours>,
+    /// When the credit was first seen
+    pub credited_time: Option<Timestamp>,
+    /// Latest surplus of the room, queried after payment
+    pub surplus: Option<SurplusInfo>,
+    /// Tracking stopped by [`TrackOptions::timeout`] before the order settled
+    pub timed_out: bool,
+    /// Last transient failure of a poll, skipped to poll again
+    ///
+    /// Response bodies are omitted.
+    pub last_error: Option<String>,
+}
+
+impl OrderOutcome {
+    /// Whether nothing is left to wait for: failed, refunded, or paid and credited
+    pub fn is_settled(&self) -> bool {
+        match self.status {
+            OrderStatus::Pending => false,
+            OrderStatus::Paid => self.credited.is_some(),
+            OrderStatus::Failed | OrderStatus::Refunded => true,
+        }
+    }
+}
+
+/// Record of `order` in `records`, newest first
+///
+/// Stops at the records created before the order.
+async fn find_record<S>(order: &RechargeOrder, records: S) -> Result<Option<UserRechargeRecord>>
+where
+    S: Stream<Item = Result<UserRechargeRecord>>,
+{
+    let lead = TimeDelta::try_minutes(RECORD_LEAD_MINUTES);
+    let earliest = order
+        .create_time
+        .datetime()
+        .zip(lead)
+        .and_then(|(t, lead)| t.checked_sub_signed(lead));
+
+    let mut records = pin!(records);
+    while let Some(record) = records.next().await {
+        let record = record?;
+        if record.order_no == order.tran_no {
+            return Ok(Some(record));
+        }
+        if let (Some(earliest), Some(time)) = (earliest, record.create_time.datetime()) {
+            if time < earliest {
+                break;
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// `Ok(None)` for a transient failure, kept in [`OrderOutcome::last_error`]
+fn skip_transient<T>(result: Result<T>, outcome: &mut OrderOutcome) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.is_retryable() => {
+            crate::trace::poll_failed(&e);
+            outcome.last_error = Some(crate::trace::brief(&e));
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Poll `record` and, once paid, `surplus` until settled or timed out
+///
+/// Transient failures are skipped until the timeout, others fail the tracking.
+async fn track<R, RFut, S, SFut>(
+    order: &RechargeOrder,
+    options: &TrackOptions,
+    mut record: R,
+    mut surplus: S,
+) -> Result<OrderOutcome>
+where
+    R: FnMut() -> RFut,
+    RFut: Future<Output = Result<Option<UserRechargeRecord>>>,
+    S: FnMut() -> SFut,
+    SFut: Future<Output = Result<SurplusInfo>>,
+{
+    let deadline = Instant::now() + options.timeout;
+    let mut outcome = OrderOutcome {
+        status: OrderStatus::Pending,
+        record: None,
+        credited: None,
+        credited_time: None,
+        surplus: None,
+        timed_out: false,
+        last_error: None,
+    };
+
+    loop {
+        if !outcome.status.is_terminal() {
+            if let Some(record) = skip_transient(record().await, &mut outcome)? {
+                outcome.status = record.as_ref().map_or(OrderStatus::Pending, |r| {
+                    OrderStatus::parse(&r.pay_status_str)
+                });
+                outcome.record = record;
+            }
+        }
+
+        if outcome.status == OrderStatus::Paid {
+            if let Some(info) = skip_transient(surplus().await, &mut outcome)? {
+                if info.soc > order.soc_before {
+                    outcome.credited = Some(info.soc - order.soc_before);
+                    outcome.credited_time = Some(Utc::now().with_timezone(&cst()).into());
+                }
+                outcome.surplus = Some(info);
+            }
+        }
+
+        if outcome.is_settled() {
+            return Ok(outcome);
+        }
+        if Instant::now() + options.interval > deadline {
+            outcome.timed_out = true;
+            return Ok(outcome);
+        }
+        tokio::time::sleep(options.interval).await;
+    }
+}
+
+impl AppHandler {
+    /// Create a recharge transaction like [`Self::recharge`], as a trackable [`RechargeOrder`]
+    ///
+    /// The surplus of the room is queried first, as the baseline of the credit.
+    pub async fn recharge_order(
+        &self,
+        info: &RoomInfo,
+        amount: u32,
+        cztype: &str,
+        mdname: &str,
+        submit_token: &str,
+        uid: &str,
+    ) -> Result<RechargeOrder> {
+        let soc_before = self.surplus(info).await?.soc;
+        let tran_no = self
+            .recharge(info, amount, cztype, mdname, submit_token, uid)
+            .await?;
+
+        Ok(RechargeOrder::new(
+            info, amount, cztype, tran_no, soc_before,
+        ))
+    }
+
+    /// Record of the order in the user recharge records, `None` if not listed yet
+    ///
+    /// The pages of its month are walked until the records are older than the order.
+    pub async fn order_record(&self, order: &RechargeOrder) -> Result<Option<UserRechargeRecord>> {
+        let records = self.user_recharge_records_stream(order.create_time.date(), None);
+
+        find_record(order, records).await
+    }
+
+    /// Poll the order until it settles, see [`OrderOutcome::is_settled`], or times out
+    ///
+    /// A paid order keeps being polled until the surplus rises above
+    /// [`RechargeOrder::soc_before`], so usage meanwhile may delay the credit.
+    ///
+    /// Transient failures, see [`Error::is_retryable`](crate::error::Error::is_retryable), are skipped until the timeout,
+    /// the last one is in [`OrderOutcome::last_error`].
+    pub async fn track_order(
+        &self,
+        order: &RechargeOrder,
+        options: &TrackOptions,
+    ) -> Result<OrderOutcome> {
+        track(
+            order,
+            options,
+            || self.order_record(order),
+            || self.surplus(&order.room),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_status_parse() {
+        assert_eq!(OrderStatus::parse("支付成功"), OrderStatus::Paid);
+        assert_eq!(OrderStatus::parse("退款成功"), OrderStatus::Refunded);
+        assert_eq!(OrderStatus::parse("已退款"), OrderStatus::Refunded);
+        assert_eq!(OrderStatus::parse("退款失败"), OrderStatus::Paid);
+        assert_eq!(OrderStatus::parse("退款中"), OrderStatus::Paid);
+        assert_eq!(OrderStatus::parse("交易关闭"), OrderStatus::Failed);
+        assert_eq!(OrderStatus::parse("待支付"), OrderStatus::Pending);
+        assert!(!OrderStatus::parse("").is_terminal());
+    }
+
+    #[tokio::test]
+    async fn test_track_transient() {
+        let room = RoomInfo {
+            area_id: "1".into(),
+            building_code: "2".into(),
+            floor_code: "3".into(),
+            room_code: "4".into(),
+        };
+        let order = RechargeOrder::new(&room, 10, "50426", "1".into(), KilowattHours::ZERO);
+        assert_eq!(order.amount, Money::from_fen(1000));
+
+        let options = TrackOptions {
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(50),
+        };
+        let surplus = || async { Err::<SurplusInfo, _>(Error::EmptyResp) };
+
+        let unavailable = || async {
+            Err(Error::Http {
+                status: 503,
+                body: "secret".into(),
+            })
+        };
+        let outcome = track(&order, &options, unavailable, surplus).await.unwrap();
+        assert!(outcome.timed_out);
+        assert_eq!(outcome.status, OrderStatus::Pending);
+        assert_eq!(outcome.last_error.as_deref(), Some("Bad response: (503)"));
+
+        let result = track(
+            &order,
+            &options,
+            || async { Err(Error::EmptyResp) },
+            surplus,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::EmptyResp)));
+    }
+}
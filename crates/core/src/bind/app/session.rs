@@ -0,0 +1,510 @@
+//! Self-healing application session
+
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use chrono::NaiveDate;
+use futures_core::Stream;
+use reqwest::Client;
+
+use super::*;
+use crate::bind::bulk::{self, BulkOptions};
+use crate::bind::page::{paginate, Page};
+use crate::store::{CredentialStore, Token};
+use auth::{authorize, get_oauth_code, UserInfo};
+use directory::{only_room, Area, Building, Directory, Floor, Room, RoomEntry};
+use electricity::*;
+use order::{OrderOutcome, RechargeOrder, TrackOptions};
+use protocol::auth::GetUserInfo;
+use protocol::electricity::*;
+
+/// Called with the new session once it changes
+type ChangeHook = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Application session of a UID, re-authorized on expiry
+///
+/// Has the query methods of [`AppHandler`]. A call rejected by an expired session
+/// (see [`Error::is_auth_expired`](crate::error::Error::is_auth_expired)) re-runs [`get_oauth_code`] and [`authorize`] by the UID,
+/// then is retried once with the new session.
+///
+/// Clones share the session, and concurrent calls failed by the same expired session
+/// trigger only one re-authorization. Built [from a store](Self::from_store), the new
+/// session is written back to it.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), yxy::error::Error> {
+/// use yxy::AppSession;
+///
+/// let cached = std::fs::read_to_string("session.txt").ok();
+/// let app = AppSession::new("your_user_id", cached)?
+///     .on_change(|session| std::fs::write("session.txt", session).unwrap());
+///
+/// let room = yxy::RoomInfo::from(app.binding_info().await?);
+/// let info = app.surplus(&room).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AppSession {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    uid: String,
+    client: Client,
+    config: HandlerConfig,
+    session: RwLock<Option<String>>,
+    /// Held while re-authorizing
+    refreshing: tokio::sync::Mutex<()>,
+    on_change: RwLock<Option<ChangeHook>>,
+    store: Option<Arc<dyn CredentialStore>>,
+}
+
+impl AppSession {
+    /// Create by UID and a cached session if any
+    ///
+    /// Without a session, it is authorized on the first call.
+    pub fn new(uid: &str, session: Option<String>) -> Result<Self> {
+        Self::with_config(uid, session, HandlerConfig::default())
+    }
+
+    /// [`AppSession::new`] with specific [`HandlerConfig`]
+    pub fn with_config(uid: &str, session: Option<String>, config: HandlerConfig) -> Result<Self> {
+        Self::build(uid, session, config, None)
+    }
+
+    /// Create by UID with the session stored in `store`, which is updated by re-authorizations
+    pub fn from_store(
+        store: Arc<dyn CredentialStore>,
+        uid: &str,
+        config: HandlerConfig,
+    ) -> Result<Self> {
+        let session = store
+            .load(uid)?
+            .and_then(|c| c.app_session().map(str::to_string));
+
+        Self::build(uid, session, config, Some(store))
+    }
+
+    fn build(
+        uid: &str,
+        session: Option<String>,
+        config: HandlerConfig,
+        store: Option<Arc<dyn CredentialStore>>,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(Inner {
+                uid: uid.to_string(),
+                client: init_app_client(&config)?,
+                config,
+                session: RwLock::new(session),
+                refreshing: Default::default(),
+                on_change: RwLock::new(None),
+                store,
+            }),
+        })
+    }
+
+    /// Set a hook called with the new session after each re-authorization, e.g. to persist it
+    ///
+    /// Shared by all clones, replacing the hook set before.
+    pub fn on_change(self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        *self.inner.on_change.write().unwrap() = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn uid(&self) -> &str {
+        &self.inner.uid
+    }
+
+    /// Current session string, `None` if not authorized yet
+    pub fn session(&self) -> Option<String> {
+        self.inner.session.read().unwrap().clone()
+    }
+
+    /// Handler of the current session, authorized first if needed
+    ///
+    /// The handler does not re-authorize by itself.
+    pub async fn handler(&self) -> Result<AppHandler> {
+        let session = match self.session() {
+            Some(session) => session,
+            None => self.reauthorize(None).await?,
+        };
+
+        Ok(self.handler_of(session))
+    }
+
+    fn handler_of(&self, session: String) -> AppHandler {
+        AppHandler {
+            client: self.inner.client.clone(),
+            config: self.inner.config.clone(),
+            session,
+        }
+    }
+
+    /// Re-authorize now, returning the new session
+    pub async fn refresh(&self) -> Result<String> {
+        let current = self.session().unwrap_or_default();
+        self.reauthorize(Some(&current)).await
+    }
+
+    /// Replace `stale` session by a new one, unless it was already replaced
+    ///
+    /// `None` to authorize only if there is no session.
+    async fn reauthorize(&self, stale: Option<&str>) -> Result<String> {
+        let _refreshing = self.inner.refreshing.lock().await;
+
+        if let Some(current) = self.session() {
+            if stale != Some(current.as_str()) {
+                return Ok(current);
+            }
+        }
+
+        let Inner {
+            uid,
+            client,
+            config,
+            ..
+        } = &*self.inner;
+        let code = get_oauth_code(client, config, uid).await?;
+        let (session, _) = authorize(client, config, &code).await?;
+
+        *self.inner.session.write().unwrap() = Some(session.clone());
+        // Cloned out, so the hook may set another one
+        let hook = self.inner.on_change.read().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(&session);
+        }
+        if let Some(ref store) = self.inner.store {
+            let result = store.update(uid, &mut |c| c.app_session = Some(Token::new(&session)));
+            if let Err(e) = result {
+                crate::trace::store_failed(&e);
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Run `f` with the session, re-authorize and retry once if it has expired
+    async fn authorized<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let session = match self.session() {
+            Some(session) => session,
+            None => self.reauthorize(None).await?,
+        };
+
+        match f(session.clone()).await {
+            Err(e) if e.is_auth_expired() => {
+                let session = self.reauthorize(Some(&session)).await?;
+                f(session).await
+            }
+            result => result,
+        }
+    }
+
+    /// Send a [`Call`](crate::protocol::Call) by the session
+    async fn send<C: crate::protocol::Call>(&self, call: C) -> Result<C::Output> {
+        send(&self.inner.client, &self.inner.config, &call).await
+    }
+
+    fn directory<'a>(&'a self, session: &'a str) -> Directory<'a> {
+        Directory {
+            client: &self.inner.client,
+            config: &self.inner.config,
+            session,
+        }
+    }
+
+    /// Get user info
+    pub async fn user_info(&self) -> Result<UserInfo> {
+        self.authorized(|session| async move { self.send(GetUserInfo { session: &session }).await })
+            .await
+    }
+
+    /// See [`AppHandler::binding_info`]
+    pub async fn binding_info(&self) -> Result<BindInfo> {
+        self.authorized(|session| async move { self.send(QueryBind { session: &session }).await })
+            .await
+    }
+
+    /// See [`AppHandler::bindings`]
+    pub async fn bindings(&self) -> Result<Vec<BindInfo>> {
+        self.authorized(
+            |session| async move { self.send(QueryBindings { session: &session }).await },
+        )
+        .await
+    }
+
+    /// See [`AppHandler::surplus`]
+    pub async fn surplus(&self, info: &RoomInfo) -> Result<SurplusInfo> {
+        self.authorized(|session| async move {
+            self.send(QuerySurplus {
+                session: &session,
+                room: info,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::surplus_many`]
+    pub async fn surplus_many(
+        &self,
+        rooms: &[RoomInfo],
+        options: &BulkOptions,
+    ) -> Vec<Result<SurplusInfo>> {
+        // Owned rooms, as the futures would not be `Send` otherwise
+        let rooms = rooms.iter().cloned();
+        bulk::run(
+            rooms,
+            options,
+            |room| async move { self.surplus(&room).await },
+        )
+        .await
+    }
+
+    /// See [`AppHandler::usage_records`]
+    pub async fn usage_records(
+        &self,
+        room_info: &RoomInfo,
+        md_type: &str,
+    ) -> Result<Vec<UsageRecord>> {
+        self.authorized(|session| async move {
+            self.send(QueryUsageRecords {
+                session: &session,
+                room: room_info,
+                md_type,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::user_recharge_records`]
+    pub async fn user_recharge_records(
+        &self,
+        page: u32,
+        month: Option<NaiveDate>,
+    ) -> Result<Page<UserRechargeRecord>> {
+        self.authorized(|session| async move {
+            self.send(QueryUserRechargeRecords {
+                session: &session,
+                page,
+                month,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::room_recharge_records`]
+    pub async fn room_recharge_records(
+        &self,
+        page: u32,
+        room_info: &RoomInfo,
+    ) -> Result<Page<RechargeRecord>> {
+        self.authorized(|session| async move {
+            self.send(QueryRoomRechargeRecords {
+                session: &session,
+                page,
+                room: room_info,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::user_recharge_records_stream`]
+    pub fn user_recharge_records_stream(
+        &self,
+        month: Option<NaiveDate>,
+        max_pages: Option<u32>,
+    ) -> impl Stream<Item = Result<UserRechargeRecord>> + '_ {
+        paginate(1, max_pages, move |page| {
+            self.user_recharge_records(page, month)
+        })
+    }
+
+    /// See [`AppHandler::room_recharge_records_stream`]
+    pub fn room_recharge_records_stream<'a>(
+        &'a self,
+        room_info: &'a RoomInfo,
+        max_pages: Option<u32>,
+    ) -> impl Stream<Item = Result<RechargeRecord>> + 'a {
+        paginate(1, max_pages, move |page| {
+            self.room_recharge_records(page, room_info)
+        })
+    }
+
+    /// See [`AppHandler::recharge`]
+    ///
+    /// Retried only if rejected by the expired session, which is never charged.
+    pub async fn recharge(
+        &self,
+        info: &RoomInfo,
+        amount: u32,
+        cztype: &str,
+        mdname: &str,
+        submit_token: &str,
+        uid: &str,
+    ) -> Result<String> {
+        self.authorized(|session| async move {
+            self.send(Recharge {
+                session: &session,
+                room: info,
+                amount,
+                cztype,
+                mdname,
+                submit_token,
+                uid,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::bind_room`]
+    pub async fn bind_room(&self, room: &RoomInfo) -> Result<()> {
+        self.authorized(|session| async move {
+            self.send(BindRoom {
+                session: &session,
+                room,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::rebind_room`]
+    pub async fn rebind_room(&self, binding: &BindInfo, room: &RoomInfo) -> Result<()> {
+        binding.check_change()?;
+        self.authorized(|session| async move {
+            self.send(UpdateBind {
+                session: &session,
+                id: &binding.id,
+                room,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::unbind_room`]
+    pub async fn unbind_room(&self, binding: &BindInfo) -> Result<()> {
+        binding.check_change()?;
+        self.authorized(|session| async move {
+            self.send(DeleteBind {
+                session: &session,
+                id: &binding.id,
+            })
+            .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::areas`]
+    pub async fn areas(&self) -> Result<Vec<Area>> {
+        self.authorized(|session| async move { self.directory(&session).areas().await })
+            .await
+    }
+
+    /// See [`AppHandler::buildings`]
+    pub async fn buildings(&self, area_id: &str) -> Result<Vec<Building>> {
+        self.authorized(|session| async move { self.directory(&session).buildings(area_id).await })
+            .await
+    }
+
+    /// See [`AppHandler::floors`]
+    pub async fn floors(&self, area_id: &str, building_code: &str) -> Result<Vec<Floor>> {
+        self.authorized(|session| async move {
+            self.directory(&session)
+                .floors(area_id, building_code)
+                .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::rooms`]
+    pub async fn rooms(
+        &self,
+        area_id: &str,
+        building_code: &str,
+        floor_code: &str,
+    ) -> Result<Vec<Room>> {
+        self.authorized(|session| async move {
+            self.directory(&session)
+                .rooms(area_id, building_code, floor_code)
+                .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::find_rooms`]
+    pub async fn find_rooms(&self, path: &str) -> Result<Vec<RoomEntry>> {
+        let query = path.parse()?;
+        let query = &query;
+        self.authorized(|session| async move { self.directory(&session).find_rooms(query).await })
+            .await
+    }
+
+    /// See [`AppHandler::resolve_room`]
+    pub async fn resolve_room(&self, path: &str) -> Result<RoomInfo> {
+        only_room(self.find_rooms(path).await?, path)
+    }
+
+    /// See [`AppHandler::recharge_order`]
+    pub async fn recharge_order(
+        &self,
+        info: &RoomInfo,
+        amount: u32,
+        cztype: &str,
+        mdname: &str,
+        submit_token: &str,
+        uid: &str,
+    ) -> Result<RechargeOrder> {
+        self.authorized(|session| async move {
+            self.handler_of(session)
+                .recharge_order(info, amount, cztype, mdname, submit_token, uid)
+                .await
+        })
+        .await
+    }
+
+    /// See [`AppHandler::order_record`]
+    pub async fn order_record(&self, order: &RechargeOrder) -> Result<Option<UserRechargeRecord>> {
+        self.authorized(|session| async move { self.handler_of(session).order_record(order).await })
+            .await
+    }
+
+    /// See [`AppHandler::track_order`]
+    ///
+    /// Re-authorized tracking starts over, before the deadline of the first one.
+    pub async fn track_order(
+        &self,
+        order: &RechargeOrder,
+        options: &TrackOptions,
+    ) -> Result<OrderOutcome> {
+        let deadline = Instant::now() + options.timeout;
+        self.authorized(|session| async move {
+            let options = TrackOptions {
+                timeout: deadline.saturating_duration_since(Instant::now()),
+                ..*options
+            };
+            self.handler_of(session).track_order(order, &options).await
+        })
+        .await
+    }
+}
+
+impl std::fmt::Debug for AppSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppSession")
+            .field("uid", &self.inner.uid)
+            .field("config", &self.inner.config)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,121 @@
+//! Bulk queries
+//!
+//! Queries of many items run concurrently, at most [`BulkOptions::concurrency`] at once,
+//! started at least [`BulkOptions::interval`] apart. Every item has its own result,
+//! so a failure does not abort the others.
+//!
+//! ```no_run
+//! use yxy::bind::bulk::BulkOptions;
+//!
+//! # async fn run(app: yxy::AppSession, rooms: Vec<yxy::RoomInfo>) {
+//! let results = app.surplus_many(&rooms, &BulkOptions::default()).await;
+//! for (room, result) in rooms.iter().zip(results) {
+//!     match result {
+//!         Ok(info) => println!("{}: {}", room.room_code, info.soc),
+//!         Err(e) => eprintln!("{}: {}", room.room_code, e),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures_util::stream::{self, StreamExt};
+use tokio::time::MissedTickBehavior;
+
+use super::Result;
+
+/// Concurrency and pacing of bulk queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkOptions {
+    /// Maximum queries in flight, at least 1
+    pub concurrency: usize,
+    /// Minimum time between the starts of two queries, no pacing if zero
+    pub interval: Duration,
+}
+
+impl Default for BulkOptions {
+    /// 4 queries in flight, started 500 ms apart
+    ///
+    /// The pace matches the default query quota of [`super::limit::RateLimits`].
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Run `f` on every item by `options`, results in the order of `items`
+pub(crate) async fn run<I, T, F, Fut>(items: I, options: &BulkOptions, f: F) -> Vec<Result<T>>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let ticker = (!options.interval.is_zero()).then(|| {
+        let mut ticker = tokio::time::interval(options.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker
+    });
+
+    let paced = stream::unfold(
+        (items.into_iter(), ticker),
+        |(mut items, mut ticker)| async move {
+            let item = items.next()?;
+            if let Some(ref mut ticker) = ticker {
+                ticker.tick().await;
+            }
+            Some((item, (items, ticker)))
+        },
+    );
+
+    paced
+        .map(f)
+        .buffered(options.concurrency.max(1))
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn test_run() {
+        let options = BulkOptions {
+            concurrency: 2,
+            interval: Duration::from_millis(20),
+        };
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let start = std::time::Instant::now();
+
+        let results = run(0..5, &options, |i| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+
+                match i {
+                    2 => Err(Error::EmptyResp),
+                    _ => Ok(i),
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert!(start.elapsed() >= Duration::from_millis(80));
+        assert_eq!(results.len(), 5);
+        assert!(matches!(results[2], Err(Error::EmptyResp)));
+        let values: Vec<_> = results.into_iter().filter_map(|r| r.ok()).collect();
+        assert_eq!(values, [0, 1, 3, 4]);
+    }
+}
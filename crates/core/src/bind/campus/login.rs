@@ -5,15 +5,17 @@ use aes::Aes128;
 use base64::engine::general_purpose;
 use base64::Engine;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::*;
-use crate::url::campus::login::*;
+use crate::time::Timestamp;
 use crate::utils::{md5, pkcs7_padding};
+use protocol::login::*;
 
 /// Handle of login procedure
 pub struct LoginHandler {
     client: Client,
+    config: HandlerConfig,
     device_id: String,
 }
 
@@ -22,85 +24,44 @@ impl LoginHandler {
     pub fn new() -> Result<Self> {
         let device_id = uuid::Uuid::new_v4().to_string().to_uppercase();
 
-        Ok(Self {
-            client: init_app_sim_client(&device_id)?,
-            device_id,
-        })
+        Self::build(device_id)
     }
 
     /// Init handler by specific device id
     pub fn build(device_id: String) -> Result<Self> {
+        Self::build_with_config(device_id, HandlerConfig::default())
+    }
+
+    /// Init handler by specific device id and [`HandlerConfig`]
+    pub fn build_with_config(device_id: String, config: HandlerConfig) -> Result<Self> {
         Ok(Self {
-            client: init_app_sim_client(&device_id)?,
+            client: init_app_sim_client(&config, &device_id)?,
+            config,
             device_id,
         })
     }
 
-    fn req_body(&self) -> Vec<(&str, &str)> {
-        vec![
-            ("appVersion", APP_VER),
-            ("deviceId", &self.device_id),
-            ("platform", PLATFORM),
-            ("testAccount", "1"),
-        ]
-    }
-
     /// Return security token & level
     pub async fn security_token(&self) -> Result<SecurityTokenInfo> {
-        let body = self.req_body();
-
-        let mut resp = self
-            .client
-            .post(GET_SECURITY_TOKEN)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let resp: BasicResponse<SecurityTokenInfo> = resp.json().await?;
-        if !resp.success {
-            return Err(Error::Runtime(format!(
-                "Get security token failed: ({}); {}",
-                resp.status_code, resp.message,
-            )));
-        }
-
-        match resp.data {
-            Some(v) => Ok(v),
-            None => Err(Error::EmptyResp),
-        }
+        let call = GetSecurityToken {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+        };
+
+        send(&self.client, &self.config, &call).await
     }
 
     /// Get image captcha
     ///
     /// Return image captcha base64 string
     pub async fn captcha_image(&self, security_token: &str) -> Result<String> {
-        let mut body = self.req_body();
-        body.push(("securityToken", security_token));
-
-        let mut resp = self
-            .client
-            .post(GET_IMAGE_CAPTCHA)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let resp: BasicResponse<String> = resp.json().await?;
-        if !resp.success {
-            if resp.message == error_messages::BAD_TOKEN {
-                return Err(Error::BadInput(resp.message));
-            }
-
-            Err(Error::Runtime(format!(
-                "Get image captcha failed: ({}); {}",
-                resp.status_code, resp.message
-            )))
-        } else if let Some(v) = resp.data {
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        let call = GetCaptchaImage {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+            security_token,
+        };
+
+        send(&self.client, &self.config, &call).await
     }
 
     /// Request to send login verification code SMS
@@ -110,117 +71,34 @@ impl LoginHandler {
         security_token: &str,
         captcha: Option<&str>,
     ) -> Result<bool> {
-        let mut body = self.req_body();
-
-        let app_security_token = app_security_token(security_token, &self.device_id)?; // Important
-
-        body.push(("appSecurityToken", &app_security_token));
-        body.push(("securityToken", security_token));
-        body.push(("sendCount", "1"));
-        body.push(("mobilePhone", phone_num));
-
-        // If image captcha required
-        if let Some(v) = captcha {
-            body.push(("imageCaptchaValue", v));
-        }
-
-        let mut resp = self
-            .client
-            .post(SEND_VERIFICATION_CODE)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        /// Define data object
-        #[derive(Debug, Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Data {
-            user_exists: bool,
-        }
-
-        let resp: BasicResponse<Data> = resp.json().await?;
-        if !resp.success {
-            if resp.status_code == 203 {
-                if resp.message == error_messages::BAD_PHONE_NUM
-                    || resp.message == error_messages::BAD_PHONE_NUM_FORMAT
-                {
-                    return Err(Error::BadPhoneNumber);
-                }
-                if resp.message.starts_with(error_messages::TOO_FREQUENT)
-                    || resp.message == error_messages::FLOW_CONTROL
-                    || resp.message == error_messages::TOO_MANY_TRIES
-                {
-                    return Err(Error::Limited);
-                }
-            }
-
-            if resp.message == error_messages::VERIFICATION_EXPIRED {
-                return Err(Error::BadInput(resp.message));
-            }
-
-            return Err(Error::Runtime(format!(
-                "Send verification code error: ({}); {}",
-                resp.status_code, resp.message
-            )));
-        }
-
-        // User status
-        if let Some(v) = resp.data {
-            Ok(v.user_exists)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        let call = SendVerificationCode {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+            phone_num,
+            security_token,
+            captcha,
+        };
+
+        send(&self.client, &self.config, &call).await
     }
 
     /// Do login by verification code
     ///
     /// return [`LoginInfo`]
     pub async fn login_by_code(&self, phone_num: &str, code: &str) -> Result<LoginInfo> {
-        let mut body = self.req_body();
-        body.push(("clientId", super::CLIENT_ID));
-        body.push(("mobilePhone", phone_num));
-        body.push(("osType", super::OS_TYPE));
-        body.push(("osUuid", &self.device_id));
-        body.push(("osVersion", super::OS_VERSION));
-        body.push(("verificationCode", code));
-
-        let mut resp = self
-            .client
-            .post(DO_LOGIN_BY_CODE)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: BasicResponse<LoginInfo> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
+        let call = LoginByCode {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+            phone_num,
+            code,
         };
 
-        if !resp.success {
-            if resp.message.starts_with(error_messages::WRONG_SECRET) {
-                return Err(Error::BadLoginSecret);
-            }
-
-            return Err(Error::Runtime(format!(
-                "Login error: ({}); {}",
-                resp.status_code, resp.message
-            )));
-        }
-
-        if let Some(v) = resp.data {
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        send(&self.client, &self.config, &call).await
     }
 
     /// Do login in silent
     ///
-    /// Bind to [`DO_LOGIN_BY_TOKEN`]
+    /// Bind to [`DO_LOGIN_BY_TOKEN`](crate::url::campus::login::DO_LOGIN_BY_TOKEN)
     ///
     /// **token** is optional.
     /// If `None` is provided, a random one will be generated.
@@ -230,87 +108,24 @@ impl LoginHandler {
     /// Used to get new [`LoginInfo`] (contains new token)
     /// Also can be used to check specific device user login status.
     pub async fn silent_login(&self, uid: &str, token: Option<&str>) -> Result<LoginInfo> {
-        let mut body = self.req_body();
-        body.push(("clientId", super::CLIENT_ID));
-        body.push(("osType", super::OS_TYPE));
-        body.push(("osUuid", &self.device_id));
-        body.push(("osVersion", super::OS_VERSION));
-        body.push(("ymId", uid));
-
-        let mut resp = if let Some(t) = token {
-            body.push(("token", t));
-            self.client
-                .post(DO_LOGIN_BY_TOKEN)
-                .form(&body)
-                .send()
-                .await?
-        } else {
-            let t = gen_random_fake_md5();
-            body.push(("token", &t));
-            self.client
-                .post(DO_LOGIN_BY_TOKEN)
-                .form(&body)
-                .send()
-                .await?
+        let call = SilentLogin {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+            uid,
+            token,
         };
 
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: CommonResponse<LoginInfo> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
-        };
-
-        if check_auth_status(&resp)? {
-            return Err(Error::Runtime(format!(
-                "Login error: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default(),
-            )));
-        }
-
-        if let Some(v) = resp.data {
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        send(&self.client, &self.config, &call).await
     }
 
     /// Get the public key used to encrypt the password
     pub async fn public_key(&self) -> Result<String> {
-        let body = self.req_body();
-
-        let mut resp = self.client.post(GET_PUBLIC_KEY).form(&body).send().await?;
-
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        #[derive(Debug, Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct PublicKey {
-            public_key: String,
-        }
-
-        let resp: BasicResponse<PublicKey> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
+        let call = GetPublicKey {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
         };
 
-        if !resp.success {
-            return Err(Error::Runtime(format!(
-                "Get public key error: ({}); {}",
-                resp.status_code, resp.message
-            )));
-        }
-
-        if let Some(v) = resp.data {
-            Ok(v.public_key)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        send(&self.client, &self.config, &call).await
     }
 
     /// Do login by password
@@ -339,56 +154,31 @@ impl LoginHandler {
         password: &str,
         public_key: &str,
     ) -> Result<LoginInfo> {
-        let mut body = self.req_body();
-
-        let encrypted_password = crate::utils::encrypt_password(password, public_key)?;
-
-        body.push(("clientId", super::CLIENT_ID));
-        body.push(("mobilePhone", phone_num));
-        body.push(("osType", super::OS_TYPE));
-        body.push(("osUuid", &self.device_id));
-        body.push(("osVersion", super::OS_VERSION));
-        body.push(("password", &encrypted_password));
-
-        let mut resp = self.client.post(DO_LOGIN_BY_PWD).form(&body).send().await?;
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: BasicResponse<LoginInfo> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
+        let call = LoginByPassword {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+            phone_num,
+            password,
+            public_key,
         };
 
-        if !resp.success {
-            if resp.message.starts_with(error_messages::WRONG_SECRET) {
-                return Err(Error::BadLoginSecret);
-            } else if resp.message == error_messages::DEVICE_CHANGED {
-                return Err(Error::AuthDeviceChanged);
-            }
-
-            return Err(Error::Runtime(format!(
-                "Login error: ({}); {}",
-                resp.status_code, resp.message
-            )));
-        }
-
-        if let Some(v) = resp.data {
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
-        }
+        send(&self.client, &self.config, &call).await
     }
 }
 
 /// Generate app security token
 ///
-/// `appSecurityToken` is the device id encrypted with `AES`.
-pub fn app_security_token(security_token: &str, device_id: &str) -> Result<String> {
-    let key = GenericArray::clone_from_slice(security_token[..16].as_bytes());
+/// `appSecurityToken` is the device id encrypted with `AES`,
+/// signed with the platform and app version of `profile`.
+pub fn app_security_token(
+    security_token: &str,
+    device_id: &str,
+    profile: &DeviceProfile,
+) -> Result<String> {
+    let key = GenericArray::clone_from_slice(&security_token.as_bytes()[..16]);
     let cipher = Aes128::new(&key);
 
-    let text = general_purpose::STANDARD.decode(security_token[32..].as_bytes())?;
+    let text = general_purpose::STANDARD.decode(&security_token.as_bytes()[32..])?;
 
     let mut blocks = Vec::new();
     (0..text.len()).step_by(16).for_each(|x| {
@@ -406,23 +196,16 @@ pub fn app_security_token(security_token: &str, device_id: &str) -> Result<Strin
     let time_stamp = chrono::prelude::Local::now().timestamp();
 
     let stage_1 = md5(format!(
-        "{}|YUNMA_APP|{}|{}|{}",
-        device_id,
-        t_final,
-        time_stamp,
-        super::APP_VER_NAME
+        "{}|{}|{}|{}|{}",
+        device_id, profile.platform, t_final, time_stamp, profile.app_ver_name
     ))
     .to_uppercase();
 
     let stage_2 = md5(stage_1).to_uppercase();
 
     let stage_3 = format!(
-        "{}|YUNMA_APP|{}|{}|{}|{}",
-        device_id,
-        t_final,
-        time_stamp,
-        super::APP_VER_NAME,
-        stage_2
+        "{}|{}|{}|{}|{}|{}",
+        device_id, profile.platform, t_final, time_stamp, profile.app_ver_name, stage_2
     );
 
     let padded_text = pkcs7_padding(&stage_3, 16);
@@ -464,6 +247,7 @@ mod test {
         let result = app_security_token(
             "ce295733862b93cb376efef661c21b4dEW6CpH8wFHp/RvViKZiJ8A==",
             "12345678",
+            &DeviceProfile::default(),
         )?;
         assert_eq!("RxTdUD90Eg91tGZHyhTKwjX9v3fH8WWGgQ3vQ5CuiC", &result[..42]);
 
@@ -482,80 +266,87 @@ mod test {
 // ====== Models ======
 // =====================
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct BasicResponse<T> {
-    pub status_code: i32,
-    pub success: bool,
-    pub message: String,
-    pub data: Option<T>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SecurityTokenInfo {
     /// Level 0: No captcha required.
     pub level: u8,
+    #[serde(alias = "securityToken")]
     pub security_token: String,
 }
 
 /// Login result
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LoginInfo {
     /// UID
     pub id: String,
     /// App session token
     pub token: String,
     pub account: String,
+    #[serde(alias = "accountEncrypt")]
     pub account_encrypt: String,
+    #[serde(alias = "mobilePhone")]
     pub mobile_phone: String,
     /// 1 as male, 0 as female
     pub sex: Option<i8>,
+    #[serde(alias = "schoolCode")]
     pub school_code: Option<String>,
+    #[serde(alias = "schoolName")]
     pub school_name: Option<String>,
+    #[serde(alias = "qrcodePayType")]
     pub qrcode_pay_type: Option<u8>,
+    #[serde(alias = "userName")]
     pub user_name: Option<String>,
+    #[serde(alias = "userType")]
     pub user_type: Option<String>,
+    #[serde(alias = "jobNo")]
     pub job_no: Option<String>,
+    #[serde(alias = "userIdcard")]
     pub user_idcard: Option<String>,
+    #[serde(alias = "identityNo")]
     pub identity_no: Option<String>,
+    #[serde(alias = "userClass")]
     pub user_class: Option<String>,
+    #[serde(alias = "realNameStatus")]
     pub real_name_status: i32,
     /// register time
-    pub regiser_time: Option<String>,
+    #[serde(alias = "regiserTime")]
+    pub regiser_time: Option<Timestamp>,
+    #[serde(alias = "bindCardStatus")]
     pub bind_card_status: i32,
-    pub last_login: String,
+    #[serde(alias = "lastLogin")]
+    pub last_login: Timestamp,
+    #[serde(alias = "headImg")]
     pub head_img: String,
+    #[serde(alias = "deviceId")]
     pub device_id: String,
+    #[serde(alias = "testAccount")]
     pub test_account: i32,
+    #[serde(alias = "joinNewactivityStatus")]
     pub join_newactivity_status: i32,
+    #[serde(alias = "isNew")]
     pub is_new: Option<i8>,
+    #[serde(alias = "createStatus")]
     pub create_status: i32,
+    #[serde(alias = "eacctStatus")]
     pub eacct_status: i32,
+    #[serde(alias = "schoolClasses")]
     pub school_classes: Option<i32>,
+    #[serde(alias = "schoolNature")]
     pub school_nature: Option<i32>,
     pub platform: String,
     /// Unknown usage
+    #[serde(alias = "uuToken")]
     pub uu_token: Option<String>,
+    #[serde(alias = "qrcodePrivateKey")]
     pub qrcode_private_key: String,
+    #[serde(alias = "bindCardRate")]
     pub bind_card_rate: Option<i32>,
     pub points: Option<i32>,
+    #[serde(alias = "schoolIdentityType")]
     pub school_identity_type: Option<i32>,
+    #[serde(alias = "alumniFlag")]
     pub alumni_flag: Option<i32>,
     /// Some json extensions
+    #[serde(alias = "extJson")]
     pub ext_json: Option<String>,
 }
-
-/// Define login error response messages
-mod error_messages {
-    pub const WRONG_SECRET: &str = "您已输错";
-    pub const BAD_PHONE_NUM: &str = "请输入正确的手机号";
-    pub const BAD_PHONE_NUM_FORMAT: &str = "手机号码格式错误";
-    pub const TOO_FREQUENT: &str = "经过你的";
-    pub const TOO_MANY_TRIES: &str = "发送超限，请明天再来";
-    pub const FLOW_CONTROL: &str = "触发号码天级流控";
-    pub const DEVICE_CHANGED: &str = "设备已更换";
-    pub const VERIFICATION_EXPIRED: &str = "验证码已失效";
-    pub const BAD_TOKEN: &str = "token无效";
-}
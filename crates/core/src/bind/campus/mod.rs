@@ -2,22 +2,40 @@
 //!
 //! See [`login`] for authorize.
 
-use serde::Deserialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 
 use super::*;
-use crate::{url::campus, utils::gen_random_fake_md5};
+use crate::error::Error;
+use crate::protocol::campus::{self as protocol, login::SilentLogin, Identity};
+use crate::store::{CredentialStore, Credentials, Token};
+use crate::utils::gen_random_fake_md5;
 
 pub mod login;
 pub mod user;
 
 /// Handler for Campus API
+///
+/// With [auto refresh](Self::auto_refresh), the session token is refreshed by silent login
+/// once it expires, by the device ID and UID of the handler. If the device of the account
+/// has changed, [`Error::AuthDeviceChanged`] is returned by every later call without
+/// sending it, as only an interactive login on a device can recover, see [`Self::needs_login`].
+///
+/// Built [from a store](Self::from_store), refreshed tokens are written back to it.
 pub struct CampusHandler {
     client: Client,
+    config: HandlerConfig,
     device_id: String,
     /// Session token
-    pub token: String,
+    token: RwLock<String>,
     pub uid: String,
     pub school_code: String,
+    auto_refresh: bool,
+    /// Held while refreshing token
+    refreshing: tokio::sync::Mutex<()>,
+    device_changed: AtomicBool,
+    store: Option<Arc<dyn CredentialStore>>,
 }
 
 impl CampusHandler {
@@ -28,88 +46,187 @@ impl CampusHandler {
         school_code: &str,
         token: Option<&str>,
     ) -> Result<Self> {
-        let client = init_app_sim_client(device_id)?;
+        Self::build_with_config(device_id, uid, school_code, token, HandlerConfig::default())
+    }
+
+    /// Build handler by session token & device id with specific [`HandlerConfig`]
+    pub fn build_with_config(
+        device_id: &str,
+        uid: &str,
+        school_code: &str,
+        token: Option<&str>,
+        config: HandlerConfig,
+    ) -> Result<Self> {
+        let client = init_app_sim_client(&config, device_id)?;
 
         Ok(Self {
             client,
+            config,
             device_id: device_id.to_string(),
-            token: {
-                match token {
-                    Some(v) => v.to_string(),
-                    None => gen_random_fake_md5(),
-                }
-            },
+            token: RwLock::new(match token {
+                Some(v) => v.to_string(),
+                None => gen_random_fake_md5(),
+            }),
             uid: uid.to_string(),
             school_code: school_code.to_string(),
+            auto_refresh: false,
+            refreshing: Default::default(),
+            device_changed: AtomicBool::new(false),
+            store: None,
+        })
+    }
+
+    /// Build auto-refreshing handler by the credentials stored in `store`
+    ///
+    /// The device ID must be stored, e.g. from a [`LoginInfo`](login::LoginInfo).
+    /// Without a stored token, it is refreshed on the first call.
+    pub fn from_store(
+        store: Arc<dyn CredentialStore>,
+        uid: &str,
+        config: HandlerConfig,
+    ) -> Result<Self> {
+        let credentials = store.load(uid)?.unwrap_or_else(|| Credentials::new(uid));
+        let device_id = credentials.device_id.as_deref().ok_or_else(|| {
+            Error::BadInput("No device ID of the campus login stored".to_string())
+        })?;
+
+        let handler = Self::build_with_config(
+            device_id,
+            uid,
+            credentials.school_code.as_deref().unwrap_or_default(),
+            credentials.campus_token(),
+            config,
+        )?;
+
+        Ok(Self {
+            store: Some(store),
+            ..handler.auto_refresh(true)
         })
     }
 
-    fn req_body(&self) -> Vec<(&str, &str)> {
-        vec![
-            ("appVersion", APP_VER),
-            ("deviceId", &self.device_id),
-            ("platform", PLATFORM),
-            ("testAccount", "1"),
-            ("token", &self.token),
-            ("ymId", &self.uid),
-            ("schoolCode", &self.school_code),
-        ]
+    /// Build auto-refreshing handler by silent login of the UID-DeviceID binding
+    pub async fn silent_login(device_id: &str, uid: &str, config: HandlerConfig) -> Result<Self> {
+        let handler = Self::build_with_config(device_id, uid, "", None, config)?.auto_refresh(true);
+        let info = handler.login().await?;
+
+        Ok(Self {
+            token: RwLock::new(info.token),
+            school_code: info.school_code.unwrap_or_default(),
+            ..handler
+        })
     }
-}
 
-/// Init App simulated client
-///
-/// ## Contains
-/// - [`reqwest::Client`]
-/// - 5s timeout
-/// - UA header
-pub fn init_app_sim_client(device_id: &str) -> Result<Client> {
-    let builder = Client::builder();
-
-    let result: Client = builder
-        .connect_timeout(std::time::Duration::new(5, 0))
-        .user_agent(format!("{}{}", USER_AGENT, device_id))
-        .build()?;
+    /// Refresh the token by silent login once it expires, disabled by default
+    pub fn auto_refresh(mut self, enabled: bool) -> Self {
+        self.auto_refresh = enabled;
+        self
+    }
 
-    Ok(result)
-}
+    /// Current session token
+    pub fn token(&self) -> String {
+        self.token.read().unwrap().clone()
+    }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct CommonResponse<D = (), R = ()> {
-    pub status_code: i64,
-    /// Error code
-    pub biz_code: Option<String>,
-    pub message: Option<String>,
-    pub success: bool,
-    pub data: Option<D>,
-    pub rows: Option<R>,
-}
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
 
-/// # Returns
-/// - `Err` for handled errors.
-/// - `Ok(true)` for some error unhandled.
-/// - `Ok(false)` for no error.
-fn check_auth_status<D, R>(resp: &CommonResponse<D, R>) -> Result<bool> {
-    if !resp.success {
-        match resp.status_code {
-            203 => {
-                return Err(Error::NoBind);
+    /// The device of the account has changed, interactive login is required
+    pub fn needs_login(&self) -> bool {
+        self.device_changed.load(Ordering::Relaxed)
+    }
+
+    /// Refresh the token by silent login now, returning the new token
+    pub async fn refresh(&self) -> Result<String> {
+        let token = self.token();
+        self.refresh_stale(&token).await
+    }
+
+    /// Replace `stale` token by a new one, unless it was already replaced
+    async fn refresh_stale(&self, stale: &str) -> Result<String> {
+        let _refreshing = self.refreshing.lock().await;
+
+        let current = self.token();
+        if current != stale {
+            return Ok(current);
+        }
+
+        let info = self.login().await?;
+        *self.token.write().unwrap() = info.token.clone();
+
+        if let Some(ref store) = self.store {
+            let result = store.update(&self.uid, &mut |c| {
+                c.device_id = Some(self.device_id.clone());
+                c.campus_token = Some(Token::new(&info.token));
+                if info.school_code.is_some() {
+                    c.school_code.clone_from(&info.school_code);
+                }
+            });
+            if let Err(e) = result {
+                crate::trace::store_failed(&e);
             }
-            204 => {
-                if let Some(ref code) = resp.biz_code {
-                    let code = code.parse::<i64>();
-                    match code {
-                        Ok(10010) => return Err(Error::AuthUserNotFound),
-                        Ok(10011) => return Err(Error::AuthDeviceChanged),
-                        // Ignore
-                        _ => {}
-                    }
+        }
+
+        Ok(info.token)
+    }
+
+    async fn login(&self) -> Result<login::LoginInfo> {
+        let call = SilentLogin {
+            device_id: &self.device_id,
+            profile: &self.config.profile,
+            uid: &self.uid,
+            token: None,
+        };
+
+        send(&self.client, &self.config, &call).await
+    }
+
+    /// Run `f` with the token, refresh and retry once if it has expired
+    async fn authorized<T, F, Fut>(&self, f: F) -> Result<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if self.needs_login() {
+            return Err(Error::AuthDeviceChanged);
+        }
+
+        let token = self.token();
+        let result = match f(token.clone()).await {
+            Err(e) if self.auto_refresh && e.is_auth_expired() => {
+                match self.refresh_stale(&token).await {
+                    Ok(token) => f(token).await,
+                    Err(e) => Err(e),
                 }
             }
-            _ => {}
+            result => result,
+        };
+
+        if self.auto_refresh && matches!(result, Err(ref e) if e.requires_login()) {
+            self.device_changed.store(true, Ordering::Relaxed);
+        }
+
+        result
+    }
+
+    fn identity<'a>(&'a self, token: &'a str) -> Identity<'a> {
+        Identity {
+            device_id: &self.device_id,
+            uid: &self.uid,
+            school_code: &self.school_code,
+            token,
+            profile: &self.config.profile,
         }
-        return Ok(true); // Unhandled errors
     }
-    Ok(false)
+}
+
+/// Init App simulated client
+///
+/// Built by [`HandlerConfig::client`], with the user agent of
+/// [`HandlerConfig::profile`] suffixed by `device_id`.
+pub fn init_app_sim_client(config: &HandlerConfig, device_id: &str) -> Result<Client> {
+    let user_agent = format!("{}{}", config.profile.user_agent(), device_id);
+    let result = config.client.builder(&user_agent).build()?;
+
+    Ok(result)
 }
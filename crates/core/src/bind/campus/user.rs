@@ -1,146 +1,97 @@
 //! Campus user API bindings
 
+use std::ops::RangeInclusive;
+
+use chrono::NaiveDate;
+use futures_core::Stream;
+use serde::{Deserialize, Serialize};
+
 use super::*;
-use campus::user::*;
+use crate::bind::page::{paginate, Page};
+use crate::time::Timestamp;
+use crate::units::Money;
+use protocol::user::*;
 
 impl CampusHandler {
     /// Query campus card balance
     ///
-    /// Returns in string, like "20.01"
-    pub async fn card_balance(&self) -> Result<String> {
-        let body = self.req_body();
-
-        let mut resp = self
-            .client
-            .post(QUERY_CARD_BALANCE)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: CommonResponse<String> = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
-        };
-
-        if check_auth_status(&resp)? {
-            return Err(Error::Runtime(format!(
-                "Fail to query card balance: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default(),
-            )));
-        }
+    /// Returns like 20.01 yuan
+    pub async fn card_balance(&self) -> Result<Money> {
+        self.authorized(|token| async move {
+            let call = QueryCardBalance {
+                identity: self.identity(&token),
+            };
 
-        if let Some(v) = resp.data {
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
-        }
+            send(&self.client, &self.config, &call).await
+        })
+        .await
     }
 
-    /// Query consumption records
+    /// Query consumption records of a day
     ///
-    /// The `query_time` parameter expects a `yyyymmdd` style time string. e.g. "20220101"  
-    pub async fn consumption_records(&self, query_time: &str) -> Result<Vec<ConsumptionRecord>> {
-        let mut body = self.req_body();
-        body.push(("queryTime", query_time));
-
-        let mut resp = self
-            .client
-            .post(QUERY_CONSUMPTION_RECORDS)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        let buf = resp.bytes().await?;
-
-        let resp: CommonResponse<(), Vec<ConsumptionRecord>> =
-            match serde_json::from_slice(buf.as_ref()) {
-                Ok(v) => v,
-                Err(e) => return Err(Error::Deserialize(e, buf)),
+    /// See [`crate::time::today`] for the current date.
+    pub async fn consumption_records(&self, date: NaiveDate) -> Result<Vec<ConsumptionRecord>> {
+        self.authorized(|token| async move {
+            let call = QueryConsumptionRecords {
+                identity: self.identity(&token),
+                date,
             };
 
-        if check_auth_status(&resp)? {
-            return Err(Error::Runtime(format!(
-                "Fail to query consumption records: ({}); {}",
-                resp.status_code,
-                resp.message.unwrap_or_default(),
-            )));
-        }
+            send(&self.client, &self.config, &call).await
+        })
+        .await
+    }
 
-        if let Some(v) = resp.rows {
-            if v.is_empty() {
-                return Err(Error::EmptyResp);
+    /// Query consumption records of each day in `dates`, one request per day
+    pub async fn consumption_records_between(
+        &self,
+        dates: RangeInclusive<NaiveDate>,
+    ) -> Result<Vec<ConsumptionRecord>> {
+        let mut records = Vec::new();
+        for date in dates.start().iter_days() {
+            if date > *dates.end() {
+                break;
             }
-            Ok(v)
-        } else {
-            Err(Error::EmptyResp)
+            records.append(&mut self.consumption_records(date).await?);
         }
+
+        Ok(records)
     }
 
     /// Qeury campus APP account transaction records
     ///
     /// Pay attention to distinguish it from [`Self::consumption_records`].
     ///
-    /// Query results will be paginated (Using `offset` and `limit`).
-    pub async fn transaction_records(&self, offset: u32, limit: u32) -> Result<TransactionRecords> {
-        let mut body = self.req_body();
-        let offset = offset.to_string();
-        let limit = limit.to_string();
-        body.push(("offset", &offset));
-        body.push(("limit", &limit));
-
-        let mut resp = self
-            .client
-            .post(QUERY_TRANSACTION_RECORDS)
-            .form(&body)
-            .send()
-            .await?;
-        check_response(&mut resp).await?;
-
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Response {
-            pub status_code: serde_json::Value,
-            pub message: String,
-            pub data: Option<TransactionRecords>,
-        }
+    /// Query results will be paginated (Using `offset` and `limit`), the cursor of the
+    /// next page is an offset.
+    pub async fn transaction_records(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Page<TransactionDetail>> {
+        self.authorized(|token| async move {
+            let call = QueryTransactionRecords {
+                identity: self.identity(&token),
+                offset,
+                limit,
+            };
 
-        let buf = resp.bytes().await?;
-
-        let resp: Response = match serde_json::from_slice(buf.as_ref()) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Deserialize(e, buf)),
-        };
-
-        match resp.status_code.as_i64() {
-            Some(0) => {}
-            Some(203) => return Err(Error::NoBind),
-            Some(204) => return Err(Error::Auth("Unauthorized".to_string())),
-            None => match resp.status_code.as_str() {
-                Some("204") => return Err(Error::Auth("Unauthorized".to_string())),
-                _ => {
-                    return Err(Error::Runtime(format!(
-                        "Fail to query transaction records: ({}); {}",
-                        resp.status_code, resp.message,
-                    )))
-                }
-            },
-            _ => {
-                return Err(Error::Runtime(format!(
-                    "Fail to query transaction records: ({}); {}",
-                    resp.status_code, resp.message,
-                )))
-            }
-        }
+            Ok(send(&self.client, &self.config, &call)
+                .await?
+                .into_page(offset))
+        })
+        .await
+    }
 
-        match resp.data {
-            Some(v) if v.total != 0 => Ok(v),
-            _ => Err(Error::EmptyResp),
-        }
+    /// All pages of [`Self::transaction_records`] by `limit`, at most `max_pages` requests
+    pub fn transaction_records_stream(
+        &self,
+        limit: u32,
+        max_pages: Option<u32>,
+    ) -> impl Stream<Item = Result<TransactionDetail>> + '_ {
+        paginate(0, max_pages, move |offset| {
+            self.transaction_records(offset, limit)
+        })
     }
 }
 
@@ -148,51 +99,75 @@ impl CampusHandler {
 // ==== Models ====
 // ================
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConsumptionRecord {
-    #[serde(rename = "type")]
+    #[serde(alias = "type")]
     pub row_type: String,
-    pub time: String,
+    pub time: Timestamp,
     pub serialno: String,
+    #[serde(alias = "feeName")]
     pub fee_name: String,
-    pub money: String,
-    pub dealtime: String,
+    pub money: Money,
+    pub dealtime: Timestamp,
     pub address: String,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionRecords {
     pub total: i64,
+    #[serde(alias = "tradeDetails")]
     pub trade_details: Vec<TransactionDetail>,
+    #[serde(alias = "tradeCounts")]
     pub trade_counts: Vec<TransactionCount>,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+impl TransactionRecords {
+    /// Details as the page at `offset`, without the monthly counts
+    pub fn into_page(self, offset: u32) -> Page<TransactionDetail> {
+        Page::offset(self.trade_details, Some(self.total), offset)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionDetail {
+    #[serde(alias = "tranNo")]
     pub tran_no: String,
-    pub create_time: String,
-    pub pay_time: Option<String>,
-    pub tran_money: i64,
+    #[serde(alias = "createTime")]
+    pub create_time: Timestamp,
+    #[serde(alias = "payTime")]
+    pub pay_time: Option<Timestamp>,
+    #[serde(alias = "tranMoney", with = "crate::units::fen")]
+    pub tran_money: Money,
+    #[serde(alias = "prodName")]
     pub prod_name: String,
+    #[serde(alias = "tranState")]
     pub tran_state: i64,
+    #[serde(alias = "tranStateName")]
     pub tran_state_name: String,
+    #[serde(alias = "refundState")]
     pub refund_state: i64,
+    #[serde(alias = "refundStateName")]
     pub refund_state_name: String,
+    #[serde(alias = "payName")]
     pub pay_name: Option<String>,
+    #[serde(alias = "weekName")]
     pub week_name: String,
+    #[serde(alias = "applicationId")]
     pub application_id: String,
+    #[serde(alias = "iconUrl")]
     pub icon_url: String,
-    pub real_money: i64,
+    #[serde(alias = "realMoney", with = "crate::units::fen")]
+    pub real_money: Money,
 }
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionCount {
+    #[serde(alias = "countMonth")]
     pub count_month: String,
+    #[serde(alias = "totalNum")]
     pub total_num: i64,
-    pub total_income_amount: i64,
-    pub total_expend_amount: i64,
+    #[serde(alias = "totalIncomeAmount", with = "crate::units::fen")]
+    pub total_income_amount: Money,
+    #[serde(alias = "totalExpendAmount", with = "crate::units::fen")]
+    pub total_expend_amount: Money,
 }
@@ -0,0 +1,124 @@
+//! HTTP client settings shared by handlers
+//!
+//! Every handler builds its [`reqwest`] client from
+//! [`HandlerConfig::client`](super::HandlerConfig::client).
+//!
+//! ```no_run
+//! use yxy::{ClientConfig, HandlerConfig};
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let pem = std::fs::read("corporate-ca.pem")?;
+//!
+//! let config = HandlerConfig {
+//!     client: ClientConfig::default()
+//!         .with_proxy("http://proxy.corp:3128")?
+//!         .with_root_certificate(&pem)?,
+//!     ..Default::default()
+//! };
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Certificate, Proxy};
+
+use crate::error::Error;
+
+/// Settings of HTTP clients
+///
+/// Proxies are also read from the `HTTP_PROXY`/`HTTPS_PROXY` environment variables
+/// unless [`proxy`](Self::proxy) is set. SOCKS5 proxies require the `socks` feature.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    pub connect_timeout: Duration,
+    /// Timeout of a whole request, no timeout if `None`
+    pub timeout: Option<Duration>,
+    /// HTTP or SOCKS5 proxy of all requests
+    pub proxy: Option<Proxy>,
+    /// Trusted in addition to the system ones
+    pub root_certificates: Vec<Certificate>,
+    /// Local address to bind, e.g. to pick an interface
+    pub local_address: Option<IpAddr>,
+    /// Sent with every request, overriding the user agent if `User-Agent` is set
+    pub default_headers: HeaderMap,
+}
+
+impl Default for ClientConfig {
+    /// 10s to connect, 30s per request
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Some(Duration::from_secs(30)),
+            proxy: None,
+            root_certificates: Vec::new(),
+            local_address: None,
+            default_headers: HeaderMap::new(),
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Send all requests through the proxy at `url`,
+    /// e.g. `http://proxy.corp:3128` or `socks5://127.0.0.1:1080`
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, Error> {
+        self.proxy = Some(Proxy::all(url)?);
+        Ok(self)
+    }
+
+    /// Trust a PEM encoded root certificate in addition
+    pub fn with_root_certificate(mut self, pem: &[u8]) -> Result<Self, Error> {
+        self.root_certificates.push(Certificate::from_pem(pem)?);
+        Ok(self)
+    }
+
+    /// Client builder with these settings and `user_agent`
+    pub fn builder(&self, user_agent: &str) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .user_agent(user_agent)
+            .default_headers(self.default_headers.clone())
+            .local_address(self.local_address);
+
+        if let Some(ref proxy) = self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        match self.timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let mut default_headers = HeaderMap::new();
+        default_headers.insert("x-test", "1".parse().unwrap());
+
+        let config = ClientConfig {
+            timeout: None,
+            local_address: Some("127.0.0.1".parse().unwrap()),
+            default_headers,
+            ..Default::default()
+        }
+        .with_proxy("http://127.0.0.1:3128")
+        .unwrap();
+
+        assert!(config.proxy.is_some());
+        assert!(config.builder("ua").build().is_ok());
+        assert!(ClientConfig::default().with_proxy("not a url").is_err());
+        assert!(ClientConfig::default()
+            .with_root_certificate(b"not a certificate")
+            .is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! Client-side rate limiter
+//!
+//! The platform locks accounts out for a day once SMS or login requests are sent
+//! too frequently, see [`Error::Limited`]. A [`RateLimiter`] set in
+//! [`HandlerConfig::limiter`](super::HandlerConfig::limiter) refuses such calls locally by
+//! [`Error::Throttled`] before they reach the platform.
+//!
+//! Every account has a token bucket per endpoint, sized by the [`Quota`] of the
+//! [`CallKind`]. Clones of a limiter share the buckets, so one limiter can be set
+//! on the handlers of all users, e.g. in a server.
+//!
+//! ```
+//! use yxy::bind::limit::{Quota, RateLimiter, RateLimits};
+//! use yxy::HandlerConfig;
+//!
+//! let limiter = RateLimiter::new(RateLimits {
+//!     query: Quota::per_second(5),
+//!     ..Default::default()
+//! });
+//!
+//! let config = HandlerConfig {
+//!     limiter: Some(limiter),
+//!     ..Default::default()
+//! };
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::protocol::{url_path, CallKind};
+
+/// Buckets are pruned once the count exceeds it
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Token bucket size and refill rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// Maximum calls in a burst
+    pub burst: u32,
+    /// Time to regain one call
+    pub period: Duration,
+}
+
+impl Quota {
+    pub const fn new(burst: u32, period: Duration) -> Self {
+        Self { burst, period }
+    }
+
+    /// `n` calls per second
+    pub fn per_second(n: u32) -> Self {
+        Self::new(n, Duration::from_secs(1) / n.max(1))
+    }
+
+    /// `n` calls per minute
+    pub fn per_minute(n: u32) -> Self {
+        Self::new(n, Duration::from_secs(60) / n.max(1))
+    }
+}
+
+/// Quotas of each [`CallKind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimits {
+    pub query: Quota,
+    pub login: Quota,
+    pub sms: Quota,
+}
+
+impl Default for RateLimits {
+    /// - Query: 10 in a burst, then 2 per second
+    /// - Login: 3 in a burst, then 1 per minute
+    /// - SMS: 1 per minute
+    fn default() -> Self {
+        Self {
+            query: Quota::new(10, Duration::from_millis(500)),
+            login: Quota::new(3, Duration::from_secs(60)),
+            sms: Quota::new(1, Duration::from_secs(60)),
+        }
+    }
+}
+
+impl RateLimits {
+    pub fn quota(&self, kind: CallKind) -> Quota {
+        match kind {
+            CallKind::Query => self.query,
+            CallKind::Login => self.login,
+            CallKind::Sms => self.sms,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    quota: Quota,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(quota: Quota, now: Instant) -> Self {
+        Self {
+            quota,
+            tokens: quota.burst as f64,
+            updated: now,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= self.quota.burst as f64
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() / self.quota.period.as_secs_f64())
+            .min(self.quota.burst as f64);
+        self.updated = now;
+    }
+
+    /// Take one token, or the time to wait for it
+    fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.quota.period.mul_f64(1.0 - self.tokens))
+        }
+    }
+}
+
+/// Per-account, per-endpoint token bucket rate limiter
+///
+/// Clones share the same buckets.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    /// Keyed by account and URL path
+    buckets: Arc<Mutex<HashMap<(String, String), Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Default::default(),
+        }
+    }
+
+    pub fn limits(&self) -> &RateLimits {
+        &self.limits
+    }
+
+    /// Take a call of `account` to `url`, [`Error::Throttled`] if the quota is used up
+    pub fn acquire(&self, kind: CallKind, account: &str, url: &str) -> Result<(), Error> {
+        self.acquire_at(kind, account, url, Instant::now())
+    }
+
+    fn acquire_at(
+        &self,
+        kind: CallKind,
+        account: &str,
+        url: &str,
+        now: Instant,
+    ) -> Result<(), Error> {
+        let quota = self.limits.quota(kind);
+        if quota.period.is_zero() {
+            return Ok(());
+        }
+
+        let endpoint = url_path(url);
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > PRUNE_THRESHOLD {
+            // Full buckets are the same as absent ones
+            buckets.retain(|_, bucket| {
+                bucket.refill(now);
+                !bucket.is_full()
+            });
+        }
+
+        let bucket = buckets
+            .entry((account.to_string(), endpoint.to_string()))
+            .or_insert_with(|| Bucket::full(quota, now));
+
+        bucket.take(now).map_err(|retry_after| Error::Throttled {
+            endpoint: endpoint.to_string(),
+            retry_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const URL: &str = "https://compus.xiaofubao.com/login/doLoginByPwd";
+
+    #[test]
+    fn test_bucket() {
+        let limiter = RateLimiter::new(RateLimits {
+            login: Quota::new(2, Duration::from_secs(10)),
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at(CallKind::Login, "a", URL, now).is_ok());
+        assert!(limiter.acquire_at(CallKind::Login, "a", URL, now).is_ok());
+        let result = limiter.acquire_at(CallKind::Login, "a", URL, now);
+        assert!(matches!(
+            result,
+            Err(Error::Throttled { endpoint, retry_after })
+                if endpoint == "/login/doLoginByPwd" && retry_after == Duration::from_secs(10)
+        ));
+
+        // Other accounts and endpoints are not affected
+        assert!(limiter.acquire_at(CallKind::Login, "b", URL, now).is_ok());
+        let other = "https://compus.xiaofubao.com/login/doLoginByVerificationCode";
+        assert!(limiter.acquire_at(CallKind::Login, "a", other, now).is_ok());
+
+        let later = now + Duration::from_secs(5);
+        let result = limiter.acquire_at(CallKind::Login, "a", URL, later);
+        assert!(matches!(
+            result,
+            Err(Error::Throttled { retry_after, .. }) if retry_after == Duration::from_secs(5)
+        ));
+
+        let later = now + Duration::from_secs(10);
+        assert!(limiter.acquire_at(CallKind::Login, "a", URL, later).is_ok());
+    }
+
+    #[test]
+    fn test_shared() {
+        let limiter = RateLimiter::new(RateLimits {
+            sms: Quota::per_minute(1),
+            ..Default::default()
+        });
+        let clone = limiter.clone();
+
+        assert!(limiter.acquire(CallKind::Sms, "a", URL).is_ok());
+        assert!(clone.acquire(CallKind::Sms, "a", URL).is_err());
+    }
+}
@@ -1,74 +1,195 @@
 //! API request bindings
 
-use std::time::Duration;
+use std::time::Instant;
 
-use reqwest::{Client, Response};
+use reqwest::Client;
 
+use crate::cassette::Cassette;
 use crate::error::Error;
+use crate::protocol::{self, Call};
+use crate::trace::CallTrace;
+use crate::url::Endpoints;
 
 pub mod app;
+pub mod bulk;
 pub mod campus;
+pub mod client;
+pub mod limit;
+pub mod page;
 pub mod pay;
+pub mod profile;
+pub mod retry;
 
-// Constant values
-const OS_TYPE: &str = "iOS";
-const MOBILE_TYPE: &str = "iPad8,6";
-const OS_VERSION: &str = "15.6";
-const APP_VER: &str = "411";
-const APP_VER_NAME: &str = "4.2.1";
-const PLATFORM: &str = "YUNMA_APP";
-const USER_AGENT: &str = const_format::formatcp!(
-    "{}/{}/{}/WKWebview ZJYXYwebviewbroswer ZJYXYIphone tourCustomer /yunmaapp.NET/{}/",
-    OS_TYPE,
-    OS_VERSION,
-    MOBILE_TYPE,
-    APP_VER_NAME,
-);
-const CLIENT_ID: &str = "65l09sfwa9ao2dc";
+pub use client::ClientConfig;
+pub use limit::RateLimiter;
+pub use page::Page;
+pub use profile::DeviceProfile;
+pub use retry::RetryPolicy;
 
 type Result<T> = std::result::Result<T, Error>;
 
-/// Build a default [`reqwest::Client`].
-pub fn build_default_client() -> Result<Client> {
-    let builder = Client::builder();
-    let result = builder
-        .connect_timeout(Duration::new(10, 0))
-        .timeout(Duration::new(30, 0))
-        .user_agent(USER_AGENT)
+/// Configuration shared by handlers
+///
+/// Every handler has a `*_with_config` constructor accepting it,
+/// the plain constructors use [`HandlerConfig::default`].
+#[derive(Debug, Clone, Default)]
+pub struct HandlerConfig {
+    /// API hosts to send requests to
+    pub endpoints: Endpoints,
+    /// Record or replay exchanges, see [`crate::cassette`]
+    pub cassette: Option<Cassette>,
+    /// Retry failed calls, no retry if `None`
+    pub retry: Option<RetryPolicy>,
+    /// Refuse calls over quota locally, see [`limit`]
+    pub limiter: Option<RateLimiter>,
+    /// Simulated device, see [`profile`]
+    pub profile: DeviceProfile,
+    /// Timeouts, proxy and TLS of HTTP clients, see [`client`]
+    pub client: ClientConfig,
+}
+
+impl From<Endpoints> for HandlerConfig {
+    fn from(endpoints: Endpoints) -> Self {
+        Self {
+            endpoints,
+            ..Default::default()
+        }
+    }
+}
+
+/// Build a default [`reqwest::Client`] by [`HandlerConfig::client`] and [`HandlerConfig::profile`].
+pub fn build_default_client(config: &HandlerConfig) -> Result<Client> {
+    let result = config
+        .client
+        .builder(&config.profile.user_agent())
         .build()?;
 
     Ok(result)
 }
 
-/// Build non-redirect [`reqwest::Client`].
+/// Build non-redirect [`reqwest::Client`] by [`HandlerConfig::client`] and [`HandlerConfig::profile`].
 ///
 /// This client is used to request OAuth code.
-pub fn build_non_redirect_client() -> Result<Client> {
-    let builder = Client::builder();
-    let result = builder
-        .connect_timeout(Duration::new(10, 0))
-        .timeout(Duration::new(30, 0))
-        .user_agent(USER_AGENT)
+pub fn build_non_redirect_client(config: &HandlerConfig) -> Result<Client> {
+    let result = config
+        .client
+        .builder(&config.profile.user_agent())
         .redirect(reqwest::redirect::Policy::none())
         .build()?;
 
     Ok(result)
 }
 
-/// Check response status code.
-async fn check_response(res: &mut Response) -> Result<()> {
-    if !res.status().is_success() {
-        let text = res.chunk().await?;
-        if let Some(text) = text {
-            return Err(Error::Runtime(format!(
-                "Bad response: ({}); {:?}",
-                res.status(),
-                text,
-            )));
-        } else {
-            return Err(Error::Runtime(format!("Bad response: ({});", res.status())));
+/// Perform a [`protocol::Request`] by [`reqwest`]
+///
+/// Redirections are returned as is, so the client should use a none redirect policy.
+pub async fn execute(client: &Client, request: protocol::Request) -> Result<protocol::Response> {
+    let method = match request.method {
+        protocol::Method::Get => reqwest::Method::GET,
+        protocol::Method::Post => reqwest::Method::POST,
+    };
+
+    let mut builder = client.request(method, &request.url);
+    if !request.query.is_empty() {
+        builder = builder.query(&request.query);
+    }
+    if request.method == protocol::Method::Post {
+        builder = builder.form(&request.form);
+    }
+    if let Some(cookie) = request.cookie_header() {
+        builder = builder.header(reqwest::header::COOKIE, cookie);
+    }
+
+    let response = builder.send().await?;
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.as_str().to_string(),
+                String::from_utf8_lossy(v.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    let body = response.bytes().await?;
+
+    Ok(protocol::Response {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Drive a protocol [`Call`] by [`reqwest`]
+///
+/// Calls are checked by [`HandlerConfig::limiter`] if set,
+/// exchanges are recorded or replayed by [`HandlerConfig::cassette`] if set,
+/// failed calls are retried by [`HandlerConfig::retry`] if set,
+/// and traced with the `tracing` feature, see [`crate::trace`].
+pub async fn send<C: Call>(client: &Client, config: &HandlerConfig, call: &C) -> Result<C::Output> {
+    let request = call.request(&config.endpoints)?;
+    let trace = CallTrace::new(call, &request);
+
+    let future = send_retrying(client, config, call, &request, &trace);
+    #[cfg(feature = "tracing")]
+    let future = tracing::Instrument::instrument(future, trace.span());
+
+    future.await
+}
+
+async fn send_retrying<C: Call>(
+    client: &Client,
+    config: &HandlerConfig,
+    call: &C,
+    request: &protocol::Request,
+    trace: &CallTrace,
+) -> Result<C::Output> {
+    let mut attempts = 1;
+    loop {
+        let start = Instant::now();
+        let result = send_once(client, config, call, request, trace).await;
+        trace.attempt(attempts, start.elapsed(), &result);
+
+        let error = match result {
+            Err(e) if C::IDEMPOTENT => e,
+            result => return result,
+        };
+
+        let delay = config
+            .retry
+            .as_ref()
+            .and_then(|policy| policy.backoff(attempts, &error));
+        match delay {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return Err(error),
         }
+        attempts += 1;
     }
+}
+
+async fn send_once<C: Call>(
+    client: &Client,
+    config: &HandlerConfig,
+    call: &C,
+    request: &protocol::Request,
+    trace: &CallTrace,
+) -> Result<C::Output> {
+    if let Some(ref limiter) = config.limiter {
+        limiter.acquire(C::KIND, call.account(), &request.url)?;
+    }
+
+    let response = match config.cassette {
+        Some(ref cassette) if cassette.is_replay() => cassette.play(request)?,
+        Some(ref cassette) => {
+            let response = execute(client, request.clone()).await?;
+            cassette.record_exchange(request, &response)?;
+            response
+        }
+        None => execute(client, request.clone()).await?,
+    };
+    trace.response(&response);
 
-    Ok(())
+    call.parse(response)
 }
@@ -0,0 +1,182 @@
+//! Paginated results
+//!
+//! Record queries return a [`Page`] with the cursor of the next page, and have `*_stream`
+//! variants walking all pages lazily:
+//!
+//! ```no_run
+//! use futures_util::TryStreamExt;
+//!
+//! # async fn run(app: yxy::AppSession) -> Result<(), yxy::error::Error> {
+//! // At most 12 requests
+//! let records: Vec<_> = app.user_recharge_records_stream(None, Some(12)).try_collect().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use futures_core::Stream;
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use super::Result;
+
+/// Items of a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items of all pages, if sent
+    pub total: Option<u64>,
+    /// Cursor of the next page, `None` on the last page
+    ///
+    /// A page number or an offset, like the cursor of the query.
+    pub next: Option<u32>,
+}
+
+impl<T> Page<T> {
+    /// Page `page` of numbered pages, counted from 1
+    ///
+    /// Pages are assumed to be of the same size, so the cursor after a short last page
+    /// may lead to an empty page.
+    pub fn numbered(items: Vec<T>, total: Option<i64>, page: u32) -> Self {
+        let total = total.and_then(|v| u64::try_from(v).ok());
+        let seen = page as u64 * items.len() as u64;
+        let next = match total {
+            _ if items.is_empty() => None,
+            Some(total) if seen >= total => None,
+            _ => page.checked_add(1),
+        };
+
+        Self { items, total, next }
+    }
+
+    /// Page at `offset` of all items
+    pub fn offset(items: Vec<T>, total: Option<i64>, offset: u32) -> Self {
+        let total = total.and_then(|v| u64::try_from(v).ok());
+        let end = u32::try_from(items.len())
+            .ok()
+            .and_then(|len| offset.checked_add(len));
+        let next = match (end, total) {
+            _ if items.is_empty() => None,
+            (Some(end), Some(total)) if end as u64 >= total => None,
+            (end, _) => end,
+        };
+
+        Self { items, total, next }
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.next.is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<T> IntoIterator for Page<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// Items of the pages from cursor `first`, fetched by `fetch`
+///
+/// Stops after the last page, a page shorter than the first one, an error,
+/// or `max_pages` requests.
+pub(crate) fn paginate<'a, T, F, Fut>(
+    first: u32,
+    max_pages: Option<u32>,
+    fetch: F,
+) -> impl Stream<Item = Result<T>> + 'a
+where
+    T: 'a,
+    F: FnMut(u32) -> Fut + 'a,
+    Fut: Future<Output = Result<Page<T>>> + 'a,
+{
+    struct State<F> {
+        cursor: Option<u32>,
+        fetched: u32,
+        page_size: Option<usize>,
+        fetch: F,
+    }
+
+    let state = State {
+        cursor: Some(first),
+        fetched: 0,
+        page_size: None,
+        fetch,
+    };
+
+    stream::unfold(state, move |mut state| async move {
+        let cursor = state.cursor.take()?;
+        if max_pages.is_some_and(|max| state.fetched >= max) {
+            return None;
+        }
+        state.fetched += 1;
+
+        let page = match (state.fetch)(cursor).await {
+            Ok(page) => page,
+            Err(e) => return Some((vec![Err(e)], state)),
+        };
+        let page_size = *state.page_size.get_or_insert(page.items.len());
+        if page.items.len() >= page_size {
+            state.cursor = page.next;
+        }
+
+        Some((page.items.into_iter().map(Ok).collect(), state))
+    })
+    .flat_map(stream::iter)
+}
+
+#[cfg(test)]
+mod test {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_numbered() {
+        let page = Page::numbered(vec![1, 2], Some(5), 1);
+        assert_eq!(page.next, Some(2));
+        assert_eq!(Page::numbered(vec![5, 6], Some(6), 3).next, None);
+        assert_eq!(Page::numbered(vec![1, 2], None, 1).next, Some(2));
+        assert_eq!(Page::<i32>::numbered(vec![], None, 1).next, None);
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(Page::offset(vec![1, 2], Some(5), 0).next, Some(2));
+        assert_eq!(Page::offset(vec![5], Some(5), 4).next, None);
+        assert_eq!(Page::<i32>::offset(vec![], Some(5), 5).next, None);
+    }
+
+    /// 5 items in pages of 2
+    async fn fetch(page: u32) -> Result<Page<u32>> {
+        let items = (page * 2 - 1..=page * 2).filter(|&v| v <= 5).collect();
+        Ok(Page::numbered(items, None, page))
+    }
+
+    #[tokio::test]
+    async fn test_paginate() {
+        let all: Vec<_> = paginate(1, None, fetch).try_collect().await.unwrap();
+        assert_eq!(all, [1, 2, 3, 4, 5]);
+
+        let bounded: Vec<_> = paginate(1, Some(2), fetch).try_collect().await.unwrap();
+        assert_eq!(bounded, [1, 2, 3, 4]);
+
+        let failing = paginate(1, None, |page| async move {
+            match page {
+                1 => fetch(page).await,
+                _ => Err(Error::EmptyResp),
+            }
+        });
+        let items: Vec<_> = failing.collect().await;
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[2], Err(Error::EmptyResp)));
+    }
+}
@@ -1,8 +1,19 @@
 //! Paying APIs
 
+use crate::url::Endpoints;
+
 /// Create cashier URL by transaction No.
 pub fn to_cashier(tran_no: &str) -> String {
-    format!("{}?tran_no={}", crate::url::pay::TO_CASHIER, tran_no)
+    to_cashier_with(&Endpoints::default(), tran_no)
+}
+
+/// Create cashier URL by transaction No. on specific [`Endpoints`]
+pub fn to_cashier_with(endpoints: &Endpoints, tran_no: &str) -> String {
+    format!(
+        "{}?tran_no={}",
+        endpoints.resolve(crate::url::pay::TO_CASHIER),
+        tran_no
+    )
 }
 
 #[cfg(test)]
@@ -0,0 +1,122 @@
+//! Simulated device profile
+//!
+//! The platform identifies the app by its user agent, the device fields of request
+//! forms and the `appSecurityToken`. A [`DeviceProfile`] set in
+//! [`HandlerConfig::profile`](super::HandlerConfig::profile) keeps all of them consistent.
+//!
+//! ```
+//! use yxy::{DeviceProfile, HandlerConfig};
+//!
+//! let config = HandlerConfig {
+//!     profile: DeviceProfile {
+//!         app_ver_name: "4.2.2".to_string(),
+//!         ..DeviceProfile::android()
+//!     },
+//!     ..Default::default()
+//! };
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Device and app version presented to the platform
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    /// `iOS` or `Android`
+    pub os_type: String,
+    pub os_version: String,
+    /// Device model
+    pub mobile_type: String,
+    /// App version code, e.g. `411`
+    pub app_ver: String,
+    /// App version name, e.g. `4.2.1`
+    pub app_ver_name: String,
+    pub platform: String,
+    /// OAuth client id of the app
+    pub client_id: String,
+}
+
+impl Default for DeviceProfile {
+    /// Same as [`DeviceProfile::ios`]
+    fn default() -> Self {
+        Self::ios()
+    }
+}
+
+impl DeviceProfile {
+    /// iPad with iOS 15.6
+    pub fn ios() -> Self {
+        Self {
+            os_type: "iOS".to_string(),
+            os_version: "15.6".to_string(),
+            mobile_type: "iPad8,6".to_string(),
+            ..Self::common()
+        }
+    }
+
+    /// Android 12 phone
+    pub fn android() -> Self {
+        Self {
+            os_type: "Android".to_string(),
+            os_version: "12".to_string(),
+            mobile_type: "M2012K11AC".to_string(),
+            ..Self::common()
+        }
+    }
+
+    /// App fields shared by presets
+    fn common() -> Self {
+        Self {
+            os_type: String::new(),
+            os_version: String::new(),
+            mobile_type: String::new(),
+            app_ver: "411".to_string(),
+            app_ver_name: "4.2.1".to_string(),
+            platform: "YUNMA_APP".to_string(),
+            client_id: "65l09sfwa9ao2dc".to_string(),
+        }
+    }
+
+    pub fn is_android(&self) -> bool {
+        self.os_type.eq_ignore_ascii_case("android")
+    }
+
+    /// User agent of the app webview
+    pub fn user_agent(&self) -> String {
+        if self.is_android() {
+            format!(
+                "Mozilla/5.0 (Linux; Android {}; {} Build/SKQ1.211006.001; wv) \
+                AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/96.0.4664.104 \
+                Mobile Safari/537.36 ZJYXYwebviewbroswer ZJYXYAndroid tourCustomer \
+                /yunmaapp.NET/{}/",
+                self.os_version, self.mobile_type, self.app_ver_name,
+            )
+        } else {
+            format!(
+                "{}/{}/{}/WKWebview ZJYXYwebviewbroswer ZJYXYIphone tourCustomer /yunmaapp.NET/{}/",
+                self.os_type, self.os_version, self.mobile_type, self.app_ver_name,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_user_agent() {
+        assert_eq!(
+            DeviceProfile::ios().user_agent(),
+            "iOS/15.6/iPad8,6/WKWebview ZJYXYwebviewbroswer ZJYXYIphone tourCustomer /yunmaapp.NET/4.2.1/"
+        );
+
+        let profile = DeviceProfile {
+            app_ver_name: "4.2.2".to_string(),
+            ..DeviceProfile::android()
+        };
+        let ua = profile.user_agent();
+        assert!(ua.contains("Android 12; M2012K11AC"));
+        assert!(ua.contains("ZJYXYAndroid"));
+        assert!(ua.ends_with("/yunmaapp.NET/4.2.2/"));
+    }
+}
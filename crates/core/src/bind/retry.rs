@@ -0,0 +1,178 @@
+//! Retry policy of failed calls
+
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::Error;
+
+/// Retry with exponential backoff
+///
+/// Set by [`HandlerConfig::retry`](super::HandlerConfig::retry) to apply on every call
+/// of a handler. Non-idempotent calls (see [`Call::IDEMPOTENT`](crate::protocol::Call::IDEMPOTENT)),
+/// e.g. recharge and sending SMS verification code, are never retried.
+///
+/// ```
+/// use std::time::Duration;
+/// use yxy::{HandlerConfig, RetryPolicy};
+///
+/// let config = HandlerConfig {
+///     retry: Some(RetryPolicy {
+///         max_attempts: 5,
+///         base_delay: Duration::from_secs(1),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each of the following
+    pub base_delay: Duration,
+    /// Upper bound of delays
+    pub max_delay: Duration,
+    /// Randomize each delay between its half and itself
+    pub jitter: bool,
+    /// Retry transient failures, see [`Error::is_retryable`]
+    pub transient: bool,
+    /// Retry calls refused by the local rate limiter, see [`Error::Throttled`]
+    pub rate_limited: bool,
+    /// Retry the flow control of the server, see [`Error::Limited`]
+    ///
+    /// Off by default, as it usually lasts until the next day.
+    pub server_limited: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            transient: true,
+            rate_limited: true,
+            server_limited: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether the error class is retried by this policy
+    pub fn retries(&self, error: &Error) -> bool {
+        match error {
+            Error::Throttled { .. } => self.rate_limited,
+            Error::Limited => self.server_limited,
+            e => self.transient && e.is_retryable(),
+        }
+    }
+
+    /// Delay before the next attempt after `attempts` failed ones,
+    /// `None` if `error` should be returned as is.
+    ///
+    /// Calls refused by the local rate limiter are retried after the required wait,
+    /// if it is not longer than [`max_delay`](Self::max_delay).
+    pub fn backoff(&self, attempts: u32, error: &Error) -> Option<Duration> {
+        if attempts >= self.max_attempts || !self.retries(error) {
+            return None;
+        }
+
+        if let Error::Throttled { retry_after, .. } = error {
+            return (*retry_after <= self.max_delay).then_some(*retry_after);
+        }
+
+        let exp = attempts.saturating_sub(1).min(31);
+        let delay = self.base_delay.saturating_mul(1 << exp).min(self.max_delay);
+
+        if self.jitter {
+            let half = delay / 2;
+            Some(half + rand::thread_rng().gen_range(Duration::ZERO..=half))
+        } else {
+            Some(delay)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(3),
+            jitter: false,
+            ..Default::default()
+        }
+    }
+
+    fn unavailable() -> Error {
+        Error::Http {
+            status: 503,
+            body: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = policy();
+        let error = unavailable();
+
+        assert_eq!(policy.backoff(1, &error), Some(Duration::from_secs(1)));
+        assert_eq!(policy.backoff(2, &error), Some(Duration::from_secs(2)));
+        assert_eq!(policy.backoff(3, &error), Some(Duration::from_secs(3)));
+        assert_eq!(policy.backoff(4, &error), None);
+
+        assert_eq!(policy.backoff(1, &Error::NoBind), None);
+
+        let throttled = |secs| Error::Throttled {
+            endpoint: "/app/electric/queryBind".into(),
+            retry_after: Duration::from_secs(secs),
+        };
+        assert_eq!(
+            policy.backoff(1, &throttled(2)),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(policy.backoff(1, &throttled(60)), None);
+    }
+
+    #[test]
+    fn test_error_classes() {
+        let throttled = Error::Throttled {
+            endpoint: "/app/electric/queryBind".into(),
+            retry_after: Duration::from_secs(1),
+        };
+
+        let policy = RetryPolicy::default();
+        assert!(policy.retries(&unavailable()));
+        assert!(policy.retries(&throttled));
+        assert!(!policy.retries(&Error::Limited));
+        assert_eq!(policy.backoff(1, &Error::Limited), None);
+
+        let policy = RetryPolicy {
+            transient: false,
+            rate_limited: false,
+            server_limited: true,
+            ..policy
+        };
+        assert!(!policy.retries(&unavailable()));
+        assert!(!policy.retries(&throttled));
+        assert!(policy.retries(&Error::Limited));
+    }
+
+    #[test]
+    fn test_jitter() {
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy()
+        };
+
+        for _ in 0..16 {
+            let delay = policy.backoff(2, &unavailable()).unwrap();
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+        }
+    }
+}
@@ -1,235 +1,46 @@
 //! Application authorize APIs
 
-use reqwest::{
-    blocking::Client,
-    cookie::{Cookie, Jar},
-};
-use serde::Deserialize;
-use std::{io::Read, sync::Arc, time::Duration};
+use reqwest::Client;
 
-use crate::blocking::bind::check_response;
+use super::AppHandler;
+use crate::bind::HandlerConfig;
+use crate::blocking::block_on;
 use crate::error::Error;
 
-/// Authorize API response definition
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AuthResponse {
-    // status_code: i32,
-    message: String,
-    success: bool,
-    data: Option<UserInfo>,
-}
-
-/// User info provided by platform
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UserInfo {
-    /// UID: User ID
-    pub id: String,
-    pub mobile_phone: String,
-    pub sex: i8,
-    pub test_account: i8,
-    pub platform: String,
-    pub third_openid: String,
-    pub school_code: Option<String>,
-    pub school_name: Option<String>,
-    pub user_name: Option<String>,
-    pub user_type: Option<String>,
-    pub job_no: Option<String>,
-    pub user_idcard: Option<String>,
-    pub user_class: Option<String>,
-    pub bind_card_status: Option<i8>,
-}
+pub use crate::bind::app::auth::UserInfo;
 
-/// Extract code value from redirection URL query string
-///
-/// # Example match URL
-///
-/// ```text
-/// https://application.xiaofubao.com/?code=b3cb4e67111b453488d826ba4397d921&errCode=0&status=null
-/// ```
-///
-/// If matched, return the `code` value
-fn extract_code(url: &str) -> Option<String> {
-    match reqwest::Url::parse(url) {
-        Ok(url) => {
-            let query = url.query_pairs();
-
-            for (key, value) in query {
-                if key == "code" {
-                    return Some(value.to_string());
-                }
-            }
-
-            None
-        }
-        Err(_) => None,
-    }
-}
-
-/// Request and extract code from redirect `Location`
-///
-/// # Example request
-/// ```http
-/// GET /authoriz/getCodeV2?bindSkip=1&authType=2&appid=1810181825222034&callbackUrl=https%3A%2F%2Fapplication.xiaofubao.com%2F&unionid=1234567890&schoolCode=2333 HTTP/1.1
-/// User-Agent: Mozilla/5.0 (Linux; Android 11; Android for arm64; wv) AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/66.0.3359.158 Mobile Safari/537.36 ZJYXYwebviewbroswer ZJYXYAndroid tourCustomer/yunmaapp.NET/4.2.0/
-/// X-Requested-With: cn.com.yunma.school.app
-/// Host: auth.xiaofubao.com
-/// Connection: close
-/// ```
-///
-/// # Example response
-/// ```http
-/// HTTP/1.1 302 Found
-/// Server: SCDN
-/// Date: XXXXXXXXXXX
-/// Content-Length: 0
-/// Connection: close
-/// X-Content-Type-Options: nosniff
-/// X-XSS-Protection: 1; mode=block
-/// Cache-Control: no-cache, no-store, max-age=0, must-revalidate
-/// Pragma: no-cache
-/// Expires: 0
-/// Location: https://application.xiaofubao.com/?code=d15246f70c1d48658231153c75feb73f&errCode=0&
-/// Content-Language: en-US
-/// Strict-Transport-Security: max-age=604800; includeSubDomains;
-/// ```
+/// Request and extract code from redirect `Location`, see [`crate::bind::app::auth::get_oauth_code`]
 ///
 /// **Must use a none redirect policy client**
-pub fn get_oauth_code(client: &Client, id: &str) -> Result<String, Error> {
-    let query = [
-        ("bindSkip", "1"),
-        ("authType", "2"),
-        ("appid", super::APP_ID),
-        (
-            "callbackUrl",
-            &format!("{}/", crate::url::application::BASE_URL),
-        ),
-        ("unionid", id),
-    ];
-
-    let response = client
-        .get(crate::url::auth::OAUTH_URL)
-        .query(&query)
-        .send()?;
-
-    if !response.status().is_redirection() {
-        return Err(Error::Auth("OAuth Error.".to_string()));
-    }
-
-    let header_location = match response.headers().get("Location") {
-        Some(header) => header,
-        None => return Err(Error::EmptyResp),
-    };
-
-    match extract_code(header_location.to_str().unwrap()) {
-        Some(t) => Ok(t),
-        None => Err(Error::EmptyResp),
-    }
+pub fn get_oauth_code(client: &Client, config: &HandlerConfig, id: &str) -> Result<String, Error> {
+    block_on(crate::bind::app::auth::get_oauth_code(client, config, id))
 }
 
 /// Authorize the handler and fetch user infos
-pub fn authorize(client: &Client, code: &str) -> Result<(String, UserInfo), Error> {
-    // Form data
-    let params = [("code", code)];
-
-    let mut response = client
-        .post(crate::url::application::GET_USER_FOR_AUTHORIZE)
-        .form(&params)
-        .send()?;
-
-    check_response(&mut response)?;
-
-    let cookies: Vec<Cookie> = response.cookies().collect();
-
-    // get session
-    match cookies.iter().find(|x| x.name() == super::SESSION_KEY) {
-        Some(v) => {
-            let session = v.value().to_string();
-            let mut resp = String::new();
-            response.read_to_string(&mut resp)?;
-            let resp_ser: AuthResponse = match serde_json::from_str(&resp) {
-                Ok(v) => v,
-                Err(e) => return Err(Error::Runtime(format!("Parsing error: {e}\nData: {resp}"))),
-            };
-            if !resp_ser.success {
-                return Err(Error::Auth(resp_ser.message));
-            }
-            match resp_ser.data {
-                Some(v) => Ok((session, v)),
-                None => Err(Error::Auth(resp_ser.message)),
-            }
-        }
-        None => {
-            let resp_ser: AuthResponse = response.json()?;
-            Err(Error::Auth(resp_ser.message))
-        }
-    }
+pub fn authorize(
+    client: &Client,
+    config: &HandlerConfig,
+    code: &str,
+) -> Result<(String, UserInfo), Error> {
+    block_on(crate::bind::app::auth::authorize(client, config, code))
 }
 
-impl super::AppHandler {
+impl AppHandler {
     /// Create new app handler with authorize
     pub fn build_by_uid(uid: &str) -> Result<Self, Error> {
-        // Store session in cookie jar
-        let jar = Jar::default();
-
-        let client = reqwest::blocking::Client::builder()
-            .connect_timeout(Duration::new(5, 0))
-            .user_agent(super::super::USER_AGENT)
-            .redirect(reqwest::redirect::Policy::none())
-            .cookie_provider(Arc::new(jar))
-            .build()?;
-
-        let code = get_oauth_code(&client, uid)?;
-
-        // Form data
-        let params = [("code", code.as_str())];
-
-        let mut response = client
-            .post(crate::url::application::GET_USER_FOR_AUTHORIZE)
-            .form(&params)
-            .send()?;
-
-        check_response(&mut response)?;
-
-        let mut resp = String::new();
-        response.read_to_string(&mut resp)?;
-        let resp_ser: AuthResponse = match serde_json::from_str(&resp) {
-            Ok(v) => v,
-            Err(e) => return Err(Error::Runtime(format!("Parsing error: {e}\nData: {resp}"))),
-        };
-        if !resp_ser.success {
-            return Err(Error::Auth(resp_ser.message));
-        }
-
-        Ok(Self { client })
+        Self::build_by_uid_with_config(uid, HandlerConfig::default())
     }
 
-    /// Get user info
-    pub fn get_user_info(&self) -> Result<UserInfo, Error> {
-        // Form data
-        let params = [("userId", rand::random::<u8>())];
-
-        let mut response = self
-            .client
-            .post(crate::url::application::GET_USER_FOR_AUTHORIZE)
-            .form(&params)
-            .send()?;
-        check_response(&mut response)?;
+    /// Create new app handler with authorize and specific [`HandlerConfig`]
+    pub fn build_by_uid_with_config(uid: &str, config: HandlerConfig) -> Result<Self, Error> {
+        block_on(crate::bind::app::AppHandler::build_by_uid_with_config(
+            uid, config,
+        ))
+        .map(Self::from)
+    }
 
-        let resp_ser: AuthResponse = response.json()?;
-        if !resp_ser.success {
-            return Err(Error::Runtime(format!(
-                "Get user info failed: {}",
-                resp_ser.message
-            )));
-        }
-        match resp_ser.data {
-            Some(v) => Ok(v),
-            None => Err(Error::Runtime(format!(
-                "Get user info failed: {}",
-                resp_ser.message
-            ))),
-        }
+    blocking_methods! {
+        /// Get user info
+        pub fn user_info(&self) -> Result<UserInfo, Error>;
     }
 }
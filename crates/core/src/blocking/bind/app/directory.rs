@@ -0,0 +1,40 @@
+//! Room directory, see [`crate::bind::app::directory`]
+
+use super::electricity::RoomInfo;
+use super::{AppHandler, AppSession};
+use crate::error::Error;
+
+pub use crate::bind::app::directory::*;
+
+macro_rules! directory_methods {
+    ($($handler:ty),*) => {
+        $(impl $handler {
+            blocking_methods! {
+                /// Query areas of the room directory
+                pub fn areas(&self) -> Result<Vec<Area>, Error>;
+
+                /// Query buildings of the area
+                pub fn buildings(&self, area_id: &str) -> Result<Vec<Building>, Error>;
+
+                /// Query floors of the building
+                pub fn floors(&self, area_id: &str, building_code: &str) -> Result<Vec<Floor>, Error>;
+
+                /// Query rooms of the floor
+                pub fn rooms(
+                    &self,
+                    area_id: &str,
+                    building_code: &str,
+                    floor_code: &str,
+                ) -> Result<Vec<Room>, Error>;
+
+                /// Search rooms by a path of names, see [`RoomQuery`]
+                pub fn find_rooms(&self, path: &str) -> Result<Vec<RoomEntry>, Error>;
+
+                /// The only room of the path, [`Error::BadInput`] if no room or several rooms match
+                pub fn resolve_room(&self, path: &str) -> Result<RoomInfo, Error>;
+            }
+        })*
+    };
+}
+
+directory_methods!(AppHandler, AppSession);
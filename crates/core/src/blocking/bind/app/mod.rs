@@ -4,40 +4,49 @@
 
 pub mod auth;
 pub mod common;
+pub mod directory;
 pub mod electricity;
+pub mod order;
+pub mod session;
 
-use reqwest::cookie::Jar;
-use std::{sync::Arc, time::Duration};
+pub use session::AppSession;
 
+use super::HandlerConfig;
 use crate::error::Error;
 
-const APP_ID: &str = "1810181825222034";
-const SESSION_KEY: &str = "shiroJID";
-
-/// Authorized session handle
-#[derive(Debug)]
+/// Authorized session handle, see [`crate::bind::app::AppHandler`]
 pub struct AppHandler {
-    client: reqwest::blocking::Client,
+    inner: crate::bind::app::AppHandler,
 }
 
 impl AppHandler {
     /// Using session string to create
     pub fn build(session: &str) -> Result<Self, Error> {
-        Ok(Self {
-            client: {
-                // Store session in cookie jar
-                let jar = Jar::default();
-                jar.add_cookie_str(
-                    &format!("{}={}", SESSION_KEY, session),
-                    &reqwest::Url::parse(crate::url::application::BASE_URL).unwrap(),
-                );
-
-                reqwest::blocking::Client::builder()
-                    .connect_timeout(Duration::new(5, 0))
-                    .user_agent(super::USER_AGENT)
-                    .cookie_provider(Arc::new(jar))
-                    .build()?
-            },
-        })
+        crate::bind::app::AppHandler::build(session).map(Self::from)
+    }
+
+    /// Using session string to create with specific [`HandlerConfig`]
+    pub fn build_with_config(session: &str, config: HandlerConfig) -> Result<Self, Error> {
+        crate::bind::app::AppHandler::build_with_config(session, config).map(Self::from)
+    }
+
+    /// Current session string
+    pub fn session(&self) -> &str {
+        self.inner.session()
+    }
+
+    /// The async handler
+    pub fn as_async(&self) -> &crate::bind::app::AppHandler {
+        &self.inner
+    }
+
+    pub fn into_async(self) -> crate::bind::app::AppHandler {
+        self.inner
+    }
+}
+
+impl From<crate::bind::app::AppHandler> for AppHandler {
+    fn from(inner: crate::bind::app::AppHandler) -> Self {
+        Self { inner }
     }
 }
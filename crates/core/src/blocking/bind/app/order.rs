@@ -0,0 +1,41 @@
+//! Recharge order tracking, see [`crate::bind::app::order`]
+
+use super::electricity::{RoomInfo, UserRechargeRecord};
+use super::{AppHandler, AppSession};
+use crate::error::Error;
+
+pub use crate::bind::app::order::*;
+
+macro_rules! order_methods {
+    ($($handler:ty),*) => {
+        $(impl $handler {
+            blocking_methods! {
+                /// Create a recharge transaction as a trackable [`RechargeOrder`]
+                pub fn recharge_order(
+                    &self,
+                    info: &RoomInfo,
+                    amount: u32,
+                    cztype: &str,
+                    mdname: &str,
+                    submit_token: &str,
+                    uid: &str,
+                ) -> Result<RechargeOrder, Error>;
+
+                /// Record of the order in the user recharge records, `None` if not listed yet
+                pub fn order_record(
+                    &self,
+                    order: &RechargeOrder,
+                ) -> Result<Option<UserRechargeRecord>, Error>;
+
+                /// Poll the order until it settles, see [`OrderOutcome::is_settled`], or times out
+                pub fn track_order(
+                    &self,
+                    order: &RechargeOrder,
+                    options: &TrackOptions,
+                ) -> Result<OrderOutcome, Error>;
+            }
+        })*
+    };
+}
+
+order_methods!(AppHandler, AppSession);
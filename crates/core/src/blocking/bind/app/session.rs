@@ -0,0 +1,165 @@
+//! Self-healing application session
+
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+
+use super::electricity::*;
+use super::AppHandler;
+use crate::bind::app::auth::UserInfo;
+use crate::bind::bulk::BulkOptions;
+use crate::bind::HandlerConfig;
+use crate::bind::Page;
+use crate::blocking::{block_on, block_on_iter};
+use crate::error::Error;
+use crate::store::CredentialStore;
+
+/// Application session of a UID, re-authorized on expiry,
+/// see [`crate::bind::app::AppSession`]
+#[derive(Debug, Clone)]
+pub struct AppSession {
+    pub(super) inner: crate::bind::app::AppSession,
+}
+
+impl AppSession {
+    /// Create by UID and a cached session if any
+    pub fn new(uid: &str, session: Option<String>) -> Result<Self, Error> {
+        crate::bind::app::AppSession::new(uid, session).map(Self::from)
+    }
+
+    /// [`AppSession::new`] with specific [`HandlerConfig`]
+    pub fn with_config(
+        uid: &str,
+        session: Option<String>,
+        config: HandlerConfig,
+    ) -> Result<Self, Error> {
+        crate::bind::app::AppSession::with_config(uid, session, config).map(Self::from)
+    }
+
+    /// Create by UID with the session stored in `store`, which is updated by re-authorizations
+    pub fn from_store(
+        store: Arc<dyn CredentialStore>,
+        uid: &str,
+        config: HandlerConfig,
+    ) -> Result<Self, Error> {
+        crate::bind::app::AppSession::from_store(store, uid, config).map(Self::from)
+    }
+
+    /// Set a hook called with the new session after each re-authorization
+    ///
+    /// Must be set before cloning.
+    pub fn on_change(self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self::from(self.inner.on_change(hook))
+    }
+
+    pub fn uid(&self) -> &str {
+        self.inner.uid()
+    }
+
+    /// Current session string, `None` if not authorized yet
+    pub fn session(&self) -> Option<String> {
+        self.inner.session()
+    }
+
+    /// Handler of the current session, authorized first if needed
+    pub fn handler(&self) -> Result<AppHandler, Error> {
+        block_on(self.inner.handler()).map(AppHandler::from)
+    }
+
+    /// The async session
+    pub fn as_async(&self) -> &crate::bind::app::AppSession {
+        &self.inner
+    }
+
+    blocking_methods! {
+        /// Re-authorize now, returning the new session
+        pub fn refresh(&self) -> Result<String, Error>;
+
+        /// Get user info
+        pub fn user_info(&self) -> Result<UserInfo, Error>;
+
+        /// See [`AppHandler::binding_info`]
+        pub fn binding_info(&self) -> Result<BindInfo, Error>;
+
+        /// See [`AppHandler::bindings`]
+        pub fn bindings(&self) -> Result<Vec<BindInfo>, Error>;
+
+        /// See [`AppHandler::surplus`]
+        pub fn surplus(&self, info: &RoomInfo) -> Result<SurplusInfo, Error>;
+
+        /// See [`AppHandler::surplus_many`]
+        pub fn surplus_many(
+            &self,
+            rooms: &[RoomInfo],
+            options: &BulkOptions,
+        ) -> Vec<Result<SurplusInfo, Error>>;
+
+        /// See [`AppHandler::usage_records`]
+        pub fn usage_records(
+            &self,
+            room_info: &RoomInfo,
+            md_type: &str,
+        ) -> Result<Vec<UsageRecord>, Error>;
+
+        /// See [`AppHandler::user_recharge_records`]
+        pub fn user_recharge_records(
+            &self,
+            page: u32,
+            month: Option<NaiveDate>,
+        ) -> Result<Page<UserRechargeRecord>, Error>;
+
+        /// See [`AppHandler::room_recharge_records`]
+        pub fn room_recharge_records(
+            &self,
+            page: u32,
+            room_info: &RoomInfo,
+        ) -> Result<Page<RechargeRecord>, Error>;
+
+        /// See [`AppHandler::recharge`]
+        pub fn recharge(
+            &self,
+            info: &RoomInfo,
+            amount: u32,
+            cztype: &str,
+            mdname: &str,
+            submit_token: &str,
+            uid: &str,
+        ) -> Result<String, Error>;
+
+        /// See [`AppHandler::bind_room`]
+        pub fn bind_room(&self, room: &RoomInfo) -> Result<(), Error>;
+
+        /// See [`AppHandler::rebind_room`]
+        pub fn rebind_room(&self, binding: &BindInfo, room: &RoomInfo) -> Result<(), Error>;
+
+        /// See [`AppHandler::unbind_room`]
+        pub fn unbind_room(&self, binding: &BindInfo) -> Result<(), Error>;
+    }
+
+    /// All pages of [`Self::user_recharge_records`], at most `max_pages` requests
+    pub fn user_recharge_records_iter(
+        &self,
+        month: Option<NaiveDate>,
+        max_pages: Option<u32>,
+    ) -> impl Iterator<Item = Result<UserRechargeRecord, Error>> + '_ {
+        block_on_iter(self.inner.user_recharge_records_stream(month, max_pages))
+    }
+
+    /// All pages of [`Self::room_recharge_records`], at most `max_pages` requests
+    pub fn room_recharge_records_iter<'a>(
+        &'a self,
+        room_info: &'a RoomInfo,
+        max_pages: Option<u32>,
+    ) -> impl Iterator<Item = Result<RechargeRecord, Error>> + 'a {
+        block_on_iter(
+            self.inner
+                .room_recharge_records_stream(room_info, max_pages),
+        )
+    }
+}
+
+impl From<crate::bind::app::AppSession> for AppSession {
+    fn from(inner: crate::bind::app::AppSession) -> Self {
+        Self { inner }
+    }
+}
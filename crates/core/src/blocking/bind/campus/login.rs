@@ -0,0 +1,81 @@
+//! Campus login APIs
+
+use crate::bind::HandlerConfig;
+use crate::error::Error;
+
+pub use crate::bind::campus::login::{
+    app_security_token, gen_device_id, LoginInfo, SecurityTokenInfo,
+};
+
+/// Handle of login procedure, see [`crate::bind::campus::login::LoginHandler`]
+pub struct LoginHandler {
+    inner: crate::bind::campus::login::LoginHandler,
+}
+
+impl LoginHandler {
+    /// Create handler with generated UUID in place of `device_id`
+    pub fn new() -> Result<Self, Error> {
+        crate::bind::campus::login::LoginHandler::new().map(Self::from)
+    }
+
+    /// Init handler by specific device id
+    pub fn build(device_id: String) -> Result<Self, Error> {
+        crate::bind::campus::login::LoginHandler::build(device_id).map(Self::from)
+    }
+
+    /// Init handler by specific device id and [`HandlerConfig`]
+    pub fn build_with_config(device_id: String, config: HandlerConfig) -> Result<Self, Error> {
+        crate::bind::campus::login::LoginHandler::build_with_config(device_id, config)
+            .map(Self::from)
+    }
+
+    /// The async handler
+    pub fn as_async(&self) -> &crate::bind::campus::login::LoginHandler {
+        &self.inner
+    }
+
+    pub fn into_async(self) -> crate::bind::campus::login::LoginHandler {
+        self.inner
+    }
+
+    blocking_methods! {
+        /// Return security token & level
+        pub fn security_token(&self) -> Result<SecurityTokenInfo, Error>;
+
+        /// Get image captcha
+        ///
+        /// Return image captcha base64 string
+        pub fn captcha_image(&self, security_token: &str) -> Result<String, Error>;
+
+        /// Request to send login verification code SMS
+        pub fn send_verification_code(
+            &self,
+            phone_num: &str,
+            security_token: &str,
+            captcha: Option<&str>,
+        ) -> Result<bool, Error>;
+
+        /// Do login by verification code
+        pub fn login_by_code(&self, phone_num: &str, code: &str) -> Result<LoginInfo, Error>;
+
+        /// Do login in silent, see [`crate::bind::campus::login::LoginHandler::silent_login`]
+        pub fn silent_login(&self, uid: &str, token: Option<&str>) -> Result<LoginInfo, Error>;
+
+        /// Get the public key used to encrypt the password
+        pub fn public_key(&self) -> Result<String, Error>;
+
+        /// Do login by password, see [`crate::bind::campus::login::LoginHandler::login_by_password`]
+        pub fn login_by_password(
+            &self,
+            phone_num: &str,
+            password: &str,
+            public_key: &str,
+        ) -> Result<LoginInfo, Error>;
+    }
+}
+
+impl From<crate::bind::campus::login::LoginHandler> for LoginHandler {
+    fn from(inner: crate::bind::campus::login::LoginHandler) -> Self {
+        Self { inner }
+    }
+}
@@ -0,0 +1,103 @@
+//! Campus(yiSchool) APP API bindings
+//!
+//! See [`login`] for authorize.
+
+pub mod login;
+pub mod user;
+
+use std::sync::Arc;
+
+use super::HandlerConfig;
+use crate::blocking::block_on;
+use crate::error::Error;
+use crate::store::CredentialStore;
+
+pub use crate::bind::campus::init_app_sim_client;
+
+/// Handler for Campus API, see [`crate::bind::campus::CampusHandler`]
+pub struct CampusHandler {
+    inner: crate::bind::campus::CampusHandler,
+}
+
+impl CampusHandler {
+    /// Build handler by session token & device id
+    pub fn build(
+        device_id: &str,
+        uid: &str,
+        school_code: &str,
+        token: Option<&str>,
+    ) -> Result<Self, Error> {
+        crate::bind::campus::CampusHandler::build(device_id, uid, school_code, token)
+            .map(Self::from)
+    }
+
+    /// Build handler by session token & device id with specific [`HandlerConfig`]
+    pub fn build_with_config(
+        device_id: &str,
+        uid: &str,
+        school_code: &str,
+        token: Option<&str>,
+        config: HandlerConfig,
+    ) -> Result<Self, Error> {
+        crate::bind::campus::CampusHandler::build_with_config(
+            device_id,
+            uid,
+            school_code,
+            token,
+            config,
+        )
+        .map(Self::from)
+    }
+
+    /// Build auto-refreshing handler by silent login of the UID-DeviceID binding
+    pub fn silent_login(device_id: &str, uid: &str, config: HandlerConfig) -> Result<Self, Error> {
+        block_on(crate::bind::campus::CampusHandler::silent_login(
+            device_id, uid, config,
+        ))
+        .map(Self::from)
+    }
+
+    /// Build auto-refreshing handler by the credentials stored in `store`
+    pub fn from_store(
+        store: Arc<dyn CredentialStore>,
+        uid: &str,
+        config: HandlerConfig,
+    ) -> Result<Self, Error> {
+        crate::bind::campus::CampusHandler::from_store(store, uid, config).map(Self::from)
+    }
+
+    /// Refresh the token by silent login once it expires, disabled by default
+    pub fn auto_refresh(self, enabled: bool) -> Self {
+        Self::from(self.inner.auto_refresh(enabled))
+    }
+
+    /// Current session token
+    pub fn token(&self) -> String {
+        self.inner.token()
+    }
+
+    /// The device of the account has changed, interactive login is required
+    pub fn needs_login(&self) -> bool {
+        self.inner.needs_login()
+    }
+
+    blocking_methods! {
+        /// Refresh the token by silent login now, returning the new token
+        pub fn refresh(&self) -> Result<String, Error>;
+    }
+
+    /// The async handler, with the UID and school code
+    pub fn as_async(&self) -> &crate::bind::campus::CampusHandler {
+        &self.inner
+    }
+
+    pub fn into_async(self) -> crate::bind::campus::CampusHandler {
+        self.inner
+    }
+}
+
+impl From<crate::bind::campus::CampusHandler> for CampusHandler {
+    fn from(inner: crate::bind::campus::CampusHandler) -> Self {
+        Self { inner }
+    }
+}
@@ -0,0 +1,49 @@
+//! Campus user API bindings
+
+use std::ops::RangeInclusive;
+
+use chrono::NaiveDate;
+
+use super::CampusHandler;
+use crate::bind::Page;
+use crate::blocking::block_on_iter;
+use crate::error::Error;
+use crate::units::Money;
+
+pub use crate::bind::campus::user::*;
+
+impl CampusHandler {
+    blocking_methods! {
+        /// Query campus card balance
+        ///
+        /// Returns like 20.01 yuan
+        pub fn card_balance(&self) -> Result<Money, Error>;
+
+        /// Query consumption records of a day
+        pub fn consumption_records(&self, date: NaiveDate) -> Result<Vec<ConsumptionRecord>, Error>;
+
+        /// Query consumption records of each day in `dates`, one request per day
+        pub fn consumption_records_between(
+            &self,
+            dates: RangeInclusive<NaiveDate>,
+        ) -> Result<Vec<ConsumptionRecord>, Error>;
+
+        /// Qeury campus APP account transaction records
+        ///
+        /// Query results will be paginated (Using `offset` and `limit`).
+        pub fn transaction_records(
+            &self,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Page<TransactionDetail>, Error>;
+    }
+
+    /// All pages of [`Self::transaction_records`] by `limit`, at most `max_pages` requests
+    pub fn transaction_records_iter(
+        &self,
+        limit: u32,
+        max_pages: Option<u32>,
+    ) -> impl Iterator<Item = Result<TransactionDetail, Error>> + '_ {
+        block_on_iter(self.inner.transaction_records_stream(limit, max_pages))
+    }
+}
@@ -1,12 +1,78 @@
-//! Using [`reqwest`] **blocking** clients
+//! Blocking facade of the async API, enabled by the `blocking` feature
 //!
-//! # Warning: Not actively maintained
-//! Provides backward compatibility only, not recommended for new code.
+//! Exposes the same handlers, methods and models as the crate root, e.g.
+//! [`blocking::AppHandler::surplus`](bind::app::AppHandler::surplus) for
+//! [`AppHandler::surplus`](crate::AppHandler::surplus). Every call is driven to
+//! completion on an internal runtime, so it must not be made within an async context.
+//!
+//! ```no_run
+//! # fn run() -> Result<(), yxy::error::Error> {
+//! let handler = yxy::blocking::AppHandler::build_by_uid("your_user_id")?;
+//! let room = yxy::RoomInfo::from(handler.binding_info()?);
+//! let info = handler.surplus(&room)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::OnceLock;
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+use tokio::runtime::Runtime;
+
+/// Define blocking methods calling the async ones of `self.inner` with the same signatures
+macro_rules! blocking_methods {
+    ($(
+        $(#[$attr:meta])*
+        pub fn $name:ident(&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                $crate::blocking::block_on(self.inner.$name($($arg),*))
+            }
+        )*
+    };
+}
 
 pub mod bind;
 pub mod wrapper;
 
+pub use crate::url::Endpoints;
 pub use bind::app::auth::UserInfo;
-pub use bind::app::electricity::{EleBindInfo, ElectricityInfo, RoomInfo};
-pub use bind::app::AppHandler;
-pub use bind::login::{LoginHandler, LoginInfo, SecurityTokenInfo};
+pub use bind::app::electricity::{
+    BindInfo, RechargeRecord, RoomInfo, SurplusInfo, UsageRecord, UserRechargeRecord,
+};
+pub use bind::app::{AppHandler, AppSession};
+pub use bind::campus::login::{LoginHandler, LoginInfo, SecurityTokenInfo};
+pub use bind::campus::CampusHandler;
+pub use bind::{ClientConfig, DeviceProfile, HandlerConfig, Page, RateLimiter, RetryPolicy};
+
+/// Runtime shared by all blocking calls
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("yxy-blocking")
+            .enable_all()
+            .build()
+            .expect("failed to build runtime of blocking API")
+    })
+}
+
+/// Drive `future` to completion on the shared runtime
+///
+/// # Panics
+/// If called within an async context.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
+
+/// Iterate `stream`, driving each item to completion on the shared runtime
+pub(crate) fn block_on_iter<S: Stream>(stream: S) -> impl Iterator<Item = S::Item> {
+    let mut stream = Box::pin(stream);
+    std::iter::from_fn(move || block_on(stream.next()))
+}
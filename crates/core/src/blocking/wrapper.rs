@@ -1,67 +1,103 @@
-//! Wrappers of API procedures
+//! Wrappers of API procedures, see [`crate::wrapper`]
+//!
+//! Every wrapper has a `*_with_config` variant accepting [`HandlerConfig`].
 
-use super::bind::*;
+use super::bind::app;
+use super::block_on;
+use crate::bind::HandlerConfig;
 use crate::error::Error;
+use crate::wrapper as wrapped;
+
+pub use crate::wrapper::BoundSurplus;
 
 /// Authorize
 ///
-/// Wrapper of Authorization procedure.
-///
-/// just input uid to get authorization :) .
-/// This could be a vulnerability of yxy API.
-///
-/// returns a tuple of (Session Token, [`app::auth::UserInfo`])
+/// Returns a tuple of (Session Token, [`app::auth::UserInfo`])
 pub fn app_auth(uid: &str) -> Result<(String, app::auth::UserInfo), Error> {
-    // Init non-redirect client to catch redirect response
-    let client = build_non_redirect_client()?;
-
-    let oauth_code = app::auth::get_oauth_code(&client, uid)?;
-
-    let (ses, user) = app::auth::authorize(&client, &oauth_code)?;
+    block_on(wrapped::app_auth(uid))
+}
 
-    Ok((ses, user))
+/// [`app_auth`] with specific [`HandlerConfig`]
+pub fn app_auth_with_config(
+    uid: &str,
+    config: HandlerConfig,
+) -> Result<(String, app::auth::UserInfo), Error> {
+    block_on(wrapped::app_auth_with_config(uid, config))
 }
 
 /// Query electricity binding info
 ///
-/// Wrapper of user's electricity binding info query procedure.
-///
 /// If no binding info, return Err([`Error::NoBind`])
-pub fn query_ele_bind(session: &str) -> Result<app::electricity::EleBindInfo, Error> {
-    let handler = app::AppHandler::build(session)?;
-
-    let bind = handler.query_electricity_binding()?;
+pub fn query_ele_bind(session: &str) -> Result<app::electricity::BindInfo, Error> {
+    block_on(wrapped::query_ele_bind(session))
+}
 
-    Ok(bind)
+/// [`query_ele_bind`] with specific [`HandlerConfig`]
+pub fn query_ele_bind_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<app::electricity::BindInfo, Error> {
+    block_on(wrapped::query_ele_bind_with_config(session, config))
 }
 
-/// Query electricity
-///
-/// Wrapper of query electricity procedure.
+/// Query all electricity bindings
 ///
-/// Default query electricity by user's electricity(room) binding info.
+/// Empty if no binding info.
+pub fn query_ele_binds(session: &str) -> Result<Vec<app::electricity::BindInfo>, Error> {
+    block_on(wrapped::query_ele_binds(session))
+}
+
+/// [`query_ele_binds`] with specific [`HandlerConfig`]
+pub fn query_ele_binds_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<Vec<app::electricity::BindInfo>, Error> {
+    block_on(wrapped::query_ele_binds_with_config(session, config))
+}
+
+/// Query electricity by user's electricity(room) binding info
 ///
 /// If no binding info, return Err([`Error::NoBind`])
-pub fn query_ele(session: &str) -> Result<app::electricity::ElectricityInfo, Error> {
-    // Init authorized handler
-    let handler = app::AppHandler::build(session)?;
-
-    // Query Bind Info
-    let bind_info = handler.query_electricity_binding()?;
+pub fn query_ele(session: &str) -> Result<app::electricity::SurplusInfo, Error> {
+    block_on(wrapped::query_ele(session))
+}
 
-    // Query Electricity Info
-    handler.query_electricity(&bind_info.into())
+/// [`query_ele`] with specific [`HandlerConfig`]
+pub fn query_ele_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<app::electricity::SurplusInfo, Error> {
+    block_on(wrapped::query_ele_with_config(session, config))
 }
 
 /// Query electricity by [`app::electricity::RoomInfo`]
-///
-/// Wrapper of query electricity procedure.
 pub fn query_ele_by_room_info(
     session: &str,
     room_info: &app::electricity::RoomInfo,
-) -> Result<app::electricity::ElectricityInfo, Error> {
-    // Init authorized handler
-    let handler = app::AppHandler::build(session)?;
+) -> Result<app::electricity::SurplusInfo, Error> {
+    block_on(wrapped::query_ele_by_room_info(session, room_info))
+}
+
+/// [`query_ele_by_room_info`] with specific [`HandlerConfig`]
+pub fn query_ele_by_room_info_with_config(
+    session: &str,
+    room_info: &app::electricity::RoomInfo,
+    config: HandlerConfig,
+) -> Result<app::electricity::SurplusInfo, Error> {
+    block_on(wrapped::query_ele_by_room_info_with_config(
+        session, room_info, config,
+    ))
+}
 
-    handler.query_electricity(room_info)
+/// Query electricity of every bound room, see [`wrapped::query_ele_all`]
+pub fn query_ele_all(session: &str) -> Result<Vec<BoundSurplus>, Error> {
+    block_on(wrapped::query_ele_all(session))
+}
+
+/// [`query_ele_all`] with specific [`HandlerConfig`]
+pub fn query_ele_all_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<Vec<BoundSurplus>, Error> {
+    block_on(wrapped::query_ele_all_with_config(session, config))
 }
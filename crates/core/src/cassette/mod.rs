@@ -0,0 +1,241 @@
+//! Record/replay cassettes of API traffic
+//!
+//! A [`Cassette`] set in [`HandlerConfig`](crate::HandlerConfig) works in one of two modes:
+//!
+//! - **Record**: every request/response exchange performed by the handler is
+//!   [scrubbed](scrub) and appended to a JSON cassette file.
+//! - **Replay**: requests are served from a cassette file without any network access,
+//!   each recorded exchange is served once, in the recorded order.
+//!
+//! Cassettes are plain JSON, which can be attached to bug reports as debugging artifacts.
+//!
+//! ```no_run
+//! use yxy::cassette::Cassette;
+//! use yxy::{AppHandler, HandlerConfig};
+//!
+//! # async fn run() -> Result<(), yxy::error::Error> {
+//! // Record real traffic
+//! let config = HandlerConfig {
+//!     cassette: Some(Cassette::record("bind.json")),
+//!     ..Default::default()
+//! };
+//! let handler = AppHandler::build_with_config("session", config)?;
+//! handler.binding_info().await?;
+//!
+//! // Serve it back later
+//! let config = HandlerConfig {
+//!     cassette: Some(Cassette::replay("bind.json")?),
+//!     ..Default::default()
+//! };
+//! let handler = AppHandler::build_with_config("session", config)?;
+//! let bind_info = handler.binding_info().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::protocol::{url_path, Method, Request, Response};
+
+pub mod scrub;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A recorded request/response exchange
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: Request,
+    pub response: RecordedResponse,
+}
+
+/// Recorded [`Response`] with text body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl From<&Response> for RecordedResponse {
+    fn from(response: &Response) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: String::from_utf8_lossy(&response.body).into_owned(),
+        }
+    }
+}
+
+impl From<RecordedResponse> for Response {
+    fn from(response: RecordedResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            body: response.body.into(),
+        }
+    }
+}
+
+/// Cassette file content
+#[derive(Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone)]
+enum Mode {
+    /// Write to the file on every exchange
+    Record(PathBuf),
+    Replay,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    interactions: Vec<Interaction>,
+    /// Whether the interaction of the same index has been served
+    served: Vec<bool>,
+}
+
+/// Shared handle of a cassette
+///
+/// Clones share the same interactions, so one cassette can be used by multiple handlers.
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    mode: Mode,
+    state: Arc<Mutex<State>>,
+}
+
+impl Cassette {
+    /// Record exchanges to the file, the file is overwritten
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: Mode::Record(path.into()),
+            state: Default::default(),
+        }
+    }
+
+    /// Replay exchanges from the file
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read(path)?;
+        let file: CassetteFile = serde_json::from_slice(&content)?;
+
+        Ok(Self::from_interactions(file.interactions))
+    }
+
+    /// Replay exchanges from memory
+    pub fn from_interactions(interactions: Vec<Interaction>) -> Self {
+        let served = vec![false; interactions.len()];
+
+        Self {
+            mode: Mode::Replay,
+            state: Arc::new(Mutex::new(State {
+                interactions,
+                served,
+            })),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay)
+    }
+
+    /// Snapshot of the interactions
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().unwrap().interactions.clone()
+    }
+
+    /// Serve the first unserved interaction of the same method & URL path
+    pub(crate) fn play(&self, request: &Request) -> Result<Response> {
+        let mut state = self.state.lock().unwrap();
+        // Host is ignored as cassettes may be replayed on different `Endpoints`
+        let path = url_path(&request.url);
+
+        let State {
+            interactions,
+            served,
+        } = &mut *state;
+        let found = interactions
+            .iter()
+            .zip(served.iter_mut())
+            .find(|(i, served)| {
+                !**served && i.request.method == request.method && url_path(&i.request.url) == path
+            });
+
+        match found {
+            Some((interaction, served)) => {
+                *served = true;
+                Ok(interaction.response.clone().into())
+            }
+            None => Err(Error::Runtime(format!(
+                "No recorded interaction for {} {}",
+                method_name(request.method),
+                path
+            ))),
+        }
+    }
+
+    /// Scrub and append the exchange, then write the cassette file
+    pub(crate) fn record_exchange(&self, request: &Request, response: &Response) -> Result<()> {
+        let path = match self.mode {
+            Mode::Record(ref path) => path,
+            Mode::Replay => return Ok(()),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .interactions
+            .push(scrub::interaction(request, response));
+        state.served.push(false);
+
+        let file = CassetteFile {
+            interactions: state.interactions.clone(),
+        };
+        std::fs::write(path, serde_json::to_vec_pretty(&file)?)?;
+
+        Ok(())
+    }
+}
+
+fn method_name(method: Method) -> &'static str {
+    match method {
+        Method::Get => "GET",
+        Method::Post => "POST",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn interaction(url: &str, body: &str) -> Interaction {
+        Interaction {
+            request: Request::post(url.to_string()),
+            response: RecordedResponse {
+                status: 200,
+                headers: vec![],
+                body: body.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_play() {
+        let cassette = Cassette::from_interactions(vec![
+            interaction("https://a.com/x", "1"),
+            interaction("https://a.com/y", "2"),
+            interaction("https://a.com/x", "3"),
+        ]);
+
+        let request = Request::post("http://127.0.0.1/x".to_string());
+        assert_eq!(cassette.play(&request).unwrap().body, "1");
+        assert_eq!(cassette.play(&request).unwrap().body, "3");
+        assert!(matches!(cassette.play(&request), Err(Error::Runtime(_))));
+
+        let request = Request::get("http://127.0.0.1/y".to_string());
+        assert!(cassette.play(&request).is_err());
+    }
+}
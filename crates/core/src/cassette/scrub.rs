@@ -0,0 +1,257 @@
+//! Secret scrubbing of recorded exchanges
+//!
+//! Replaced by [`PLACEHOLDER`]:
+//! - Request fields carrying tokens, passwords, phone numbers and UIDs,
+//!   and the same values appearing anywhere in the response.
+//! - All cookie values.
+//! - Response JSON string fields of [`SECRET_KEYS`].
+//! - Phone numbers (11 digits) and ID card numbers (18 characters) in any text.
+
+use serde_json::Value;
+
+use super::{Interaction, RecordedResponse};
+use crate::protocol::{Request, Response};
+
+pub const PLACEHOLDER: &str = "[scrubbed]";
+
+/// Request query/form fields to scrub
+pub const SECRET_FIELDS: &[&str] = &[
+    "token",
+    "securityToken",
+    "appSecurityToken",
+    "password",
+    "mobilePhone",
+    "verificationCode",
+    "ymId",
+    "unionid",
+];
+
+/// Response JSON keys to scrub
+pub const SECRET_KEYS: &[&str] = &[
+    "token",
+    "uuToken",
+    "securityToken",
+    "mobilePhone",
+    "account",
+    "accountEncrypt",
+    "userIdcard",
+    "identityNo",
+    "qrcodePrivateKey",
+    "thirdOpenid",
+];
+
+/// Scrub a request/response exchange
+pub fn interaction(request: &Request, response: &Response) -> Interaction {
+    Interaction {
+        request: self::request(request),
+        response: self::response(&response.into(), &secrets(request)),
+    }
+}
+
+/// Secret values of request fields and cookies, to be searched in other text
+pub(crate) fn secrets(request: &Request) -> Vec<String> {
+    let fields = request
+        .query
+        .iter()
+        .chain(request.form.iter())
+        .filter(|(k, _)| SECRET_FIELDS.contains(&k.as_str()));
+
+    fields
+        .chain(request.cookies.iter())
+        .filter(|(_, v)| v.len() >= 4)
+        .map(|(_, v)| v.clone())
+        .collect()
+}
+
+/// Scrub request fields and cookies
+pub fn request(request: &Request) -> Request {
+    let scrub_fields = |pairs: &[(String, String)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| {
+                if SECRET_FIELDS.contains(&k.as_str()) {
+                    (k.clone(), PLACEHOLDER.to_string())
+                } else {
+                    (k.clone(), text(v, &[]))
+                }
+            })
+            .collect()
+    };
+
+    Request {
+        method: request.method,
+        url: request.url.clone(),
+        query: scrub_fields(&request.query),
+        form: scrub_fields(&request.form),
+        cookies: request
+            .cookies
+            .iter()
+            .map(|(k, _)| (k.clone(), PLACEHOLDER.to_string()))
+            .collect(),
+    }
+}
+
+/// Scrub response headers and body, `secrets` are replaced anywhere
+pub fn response(response: &RecordedResponse, secrets: &[String]) -> RecordedResponse {
+    let headers = response
+        .headers
+        .iter()
+        .map(|(k, v)| {
+            if k.eq_ignore_ascii_case("set-cookie") {
+                (k.clone(), set_cookie(v))
+            } else {
+                (k.clone(), text(v, secrets))
+            }
+        })
+        .collect();
+
+    let body = match serde_json::from_str::<Value>(&response.body) {
+        Ok(mut v) => {
+            json(&mut v, secrets);
+            v.to_string()
+        }
+        Err(_) => text(&response.body, secrets),
+    };
+
+    RecordedResponse {
+        status: response.status,
+        headers,
+        body,
+    }
+}
+
+/// Scrub cookie value of `Set-Cookie` header, attributes are kept
+fn set_cookie(header: &str) -> String {
+    match header.split_once('=') {
+        Some((name, rest)) => {
+            let attributes = rest.find(';').map(|i| &rest[i..]).unwrap_or_default();
+            format!("{}={}{}", name, PLACEHOLDER, attributes)
+        }
+        None => header.to_string(),
+    }
+}
+
+fn json(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::String(s) => *s = text(s, secrets),
+        Value::Array(v) => v.iter_mut().for_each(|v| json(v, secrets)),
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                match v {
+                    Value::String(s) if SECRET_KEYS.contains(&k.as_str()) && !s.is_empty() => {
+                        *s = PLACEHOLDER.to_string();
+                    }
+                    _ => json(v, secrets),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace secrets, phone numbers and ID card numbers in text
+pub(crate) fn text(s: &str, secrets: &[String]) -> String {
+    let mut s = s.to_string();
+    for secret in secrets {
+        s = s.replace(secret.as_str(), PLACEHOLDER);
+    }
+
+    mask_numbers(&s)
+}
+
+/// Replace standalone phone numbers (`1` and 10 digits) and ID card numbers
+/// (17 digits and a digit or `X`)
+fn mask_numbers(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut result = String::with_capacity(s.len());
+
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() || (i > 0 && chars[i - 1].is_ascii_alphanumeric()) {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let mut end = i;
+        while end < chars.len() && chars[end].is_ascii_digit() {
+            end += 1;
+        }
+        let digits = end - i;
+        if digits == 17 && end < chars.len() && (chars[end] == 'X' || chars[end] == 'x') {
+            end += 1;
+        }
+        let standalone = end == chars.len() || !chars[end].is_ascii_alphanumeric();
+
+        let len = end - i;
+        if standalone && ((len == 11 && chars[i] == '1') || len == 18) {
+            result.push_str(PLACEHOLDER);
+        } else {
+            result.extend(&chars[i..end]);
+        }
+        i = end;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mask_numbers() {
+        assert_eq!(mask_numbers("tel:18888888888."), "tel:[scrubbed].");
+        assert_eq!(mask_numbers("11010119900307803X"), "[scrubbed]");
+        assert_eq!(mask_numbers("110101199003078030"), "[scrubbed]");
+        // Transaction No. and timestamps
+        assert_eq!(mask_numbers("20220101000000000001"), "20220101000000000001");
+        assert_eq!(mask_numbers("20220101120000"), "20220101120000");
+        assert_eq!(mask_numbers("a18888888888"), "a18888888888");
+    }
+
+    #[test]
+    fn test_interaction() {
+        let request = Request::post("https://compus.xiaofubao.com/login/doLoginBySilent".into())
+            .field("deviceId", "device")
+            .field("ymId", "uid-123456")
+            .field("token", "secret-token")
+            .cookie("shiroJID", "session");
+
+        let response = Response {
+            status: 200,
+            headers: vec![(
+                "Set-Cookie".into(),
+                "shiroJID=session-2; Path=/; HttpOnly".into(),
+            )],
+            body: r#"{"data":{"id":"uid-123456","token":"new-token","sex":1,"jobNo":"2020","mobilePhone":"18888888888","remark":"call 18888888888"}}"#.into(),
+        };
+
+        let result = interaction(&request, &response);
+
+        assert_eq!(
+            result.request.form,
+            vec![
+                ("deviceId".into(), "device".into()),
+                ("ymId".into(), PLACEHOLDER.into()),
+                ("token".into(), PLACEHOLDER.into()),
+            ]
+        );
+        assert_eq!(
+            result.request.cookies,
+            vec![("shiroJID".into(), PLACEHOLDER.into())]
+        );
+        assert_eq!(
+            result.response.headers[0].1,
+            "shiroJID=[scrubbed]; Path=/; HttpOnly"
+        );
+
+        let body: Value = serde_json::from_str(&result.response.body).unwrap();
+        assert_eq!(body["data"]["id"], PLACEHOLDER);
+        assert_eq!(body["data"]["token"], PLACEHOLDER);
+        assert_eq!(body["data"]["mobilePhone"], PLACEHOLDER);
+        assert_eq!(body["data"]["remark"], "call [scrubbed]");
+        assert_eq!(body["data"]["sex"], 1);
+        assert_eq!(body["data"]["jobNo"], "2020");
+    }
+}
@@ -1,5 +1,7 @@
 //! Library Errors
 
+use std::time::Duration;
+
 use bytes::Bytes;
 use thiserror::Error;
 
@@ -21,6 +23,9 @@ pub enum Error {
     EmptyResp,
     #[error("No bind info.")]
     NoBind,
+    /// Creating, changing or removing the binding is refused, see [`BindInfo::is_changeable`](crate::BindInfo::is_changeable)
+    #[error("Binding change not allowed: {0}")]
+    BindNotAllowed(String),
     #[error("Request limited.")]
     Limited,
     #[error("Invalid phone number.")]
@@ -29,6 +34,28 @@ pub enum Error {
     BadLoginSecret,
     #[error("Bad Input: {0}")]
     BadInput(String),
+    /// Locked, wrong passphrase or malformed vault, see `store::VaultStore`
+    #[error("Vault Error: {0}")]
+    Vault(String),
+    /// Unsuccessful response envelope not covered by other variants
+    #[error("API Error: {endpoint} ({status_code}, {biz_code:?}); {message}")]
+    Api {
+        /// URL path of the API
+        endpoint: String,
+        status_code: i64,
+        biz_code: Option<String>,
+        message: String,
+    },
+    /// Refused by the local rate limiter without sending, see [`crate::bind::limit`]
+    #[error("Rate limited locally: {endpoint}, retry after {retry_after:?}")]
+    Throttled {
+        /// URL path of the API
+        endpoint: String,
+        retry_after: Duration,
+    },
+    /// Unsuccessful HTTP status
+    #[error("Bad response: ({status}); {body:?}")]
+    Http { status: u16, body: Bytes },
 
     #[error(transparent)]
     IO(#[from] std::io::Error),
@@ -45,3 +72,92 @@ pub enum Error {
     #[error(transparent)]
     SerdeJSON(#[from] serde_json::Error),
 }
+
+impl Error {
+    /// Session or token expired, re-authentication is required
+    pub fn is_auth_expired(&self) -> bool {
+        matches!(
+            self,
+            Error::Auth(_) | Error::AuthUserNotFound | Error::Http { status: 401, .. }
+        )
+    }
+
+    /// Credentials are no longer usable, interactive login is required
+    ///
+    /// The device of the account has changed, so silent login fails as well.
+    pub fn requires_login(&self) -> bool {
+        matches!(self, Error::AuthDeviceChanged)
+    }
+
+    /// Transient failure, the same request may succeed later
+    ///
+    /// Network timeouts, connection failures and HTTP `429`/`5xx`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Http { status, .. } => *status == 429 || *status >= 500,
+            Error::Request(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Rejected for too many requests, by the server or the local rate limiter
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            Error::Limited | Error::Throttled { .. } | Error::Http { status: 429, .. }
+        )
+    }
+
+    /// Requested binding or resource does not exist
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Error::NoBind | Error::Http { status: 404, .. })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn api(status_code: i64) -> Error {
+        Error::Api {
+            endpoint: "/app/electric/queryBind".into(),
+            status_code,
+            biz_code: None,
+            message: "x".into(),
+        }
+    }
+
+    fn http(status: u16) -> Error {
+        Error::Http {
+            status,
+            body: Bytes::new(),
+        }
+    }
+
+    #[test]
+    fn test_classification() {
+        assert!(Error::Auth("x".into()).is_auth_expired());
+        assert!(!Error::AuthDeviceChanged.is_auth_expired());
+        assert!(http(401).is_auth_expired());
+        assert!(Error::AuthDeviceChanged.requires_login());
+        assert!(!Error::AuthUserNotFound.requires_login());
+
+        assert!(http(502).is_retryable());
+        assert!(http(429).is_retryable());
+        assert!(!http(400).is_retryable());
+        assert!(!api(500).is_retryable());
+        assert!(!Error::Limited.is_retryable());
+
+        assert!(Error::Limited.is_rate_limited());
+        assert!(http(429).is_rate_limited());
+        assert!(Error::Throttled {
+            endpoint: "/login/doLoginByPwd".into(),
+            retry_after: Duration::from_secs(1),
+        }
+        .is_rate_limited());
+
+        assert!(Error::NoBind.is_not_found());
+        assert!(http(404).is_not_found());
+        assert!(!api(203).is_not_found());
+    }
+}
@@ -164,10 +164,41 @@
 //! # }
 //! ```
 //!
+//! ## Models
 //!
+//! Models like [`BindInfo`], [`SurplusInfo`] and [`LoginInfo`] are
+//! `Clone + Serialize + Deserialize`, to be cached, stored or served as they are.
+//!
+//! They are serialized by their field names in `snake_case`, which is independent of the
+//! `camelCase` names of the platform, so caches and logs survive upstream renames.
+//! Deserializing accepts both. The output schema is versioned by [`MODEL_SCHEMA_VERSION`].
+//!
+//! Amounts are exact [`Money`] and energy is [`KilowattHours`], both serialized as decimal
+//! strings like `"20.01"`, see [`units`]. Times are [`Timestamp`]s of China Standard Time,
+//! serialized in RFC 3339 and keeping the raw string, see [`time`].
+//!
+//! ```rust
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let room: yxy::RoomInfo = serde_json::from_str(
+//!     r#"{"areaId":"1","buildingCode":"2","floorCode":"3","roomCode":"4"}"#,
+//! )?;
+//!
+//! assert_eq!(
+//!     serde_json::to_string(&room)?,
+//!     r#"{"area_id":"1","building_code":"2","floor_code":"3","room_code":"4"}"#
+//! );
+//! # Ok(())
+//! # }
+//! ```
 
 pub mod bind;
+pub mod cassette;
 pub mod error;
+pub mod protocol;
+pub mod store;
+pub mod time;
+pub mod trace;
+pub mod units;
 pub mod url;
 pub mod utils;
 pub mod wrapper;
@@ -179,5 +210,15 @@ pub use bind::app::auth::UserInfo;
 pub use bind::app::electricity::{
     BindInfo, RechargeRecord, RoomInfo, SurplusInfo, UsageRecord, UserRechargeRecord,
 };
-pub use bind::app::AppHandler;
+pub use bind::app::{AppHandler, AppSession};
 pub use bind::campus::login::{LoginHandler, LoginInfo, SecurityTokenInfo};
+pub use bind::{ClientConfig, DeviceProfile, HandlerConfig, Page, RateLimiter, RetryPolicy};
+pub use time::Timestamp;
+pub use units::{KilowattHours, Money};
+pub use url::Endpoints;
+
+/// Version of the serialized schema of models
+///
+/// Bumped on breaking changes of the output, e.g. a renamed or retyped field,
+/// so persisted models of an older version can be discarded.
+pub const MODEL_SCHEMA_VERSION: u32 = 3;
@@ -0,0 +1,199 @@
+//! Application authorize APIs protocol
+
+use super::*;
+use crate::bind::app::auth::UserInfo;
+use crate::url::application::GET_USER_FOR_AUTHORIZE;
+use crate::url::auth::OAUTH_URL;
+
+/// Extract code value from redirection URL query string
+///
+/// # Example match URL
+///
+/// ```text
+/// https://application.xiaofubao.com/?code=b3cb4e67111b453488d826ba4397d921&errCode=0&status=null
+/// ```
+///
+/// If matched, return the `code` value
+fn extract_code(url: &str) -> Option<String> {
+    match reqwest::Url::parse(url) {
+        Ok(url) => {
+            let query = url.query_pairs();
+
+            for (key, value) in query {
+                if key == "ymCode" {
+                    return Some(value.to_string());
+                }
+            }
+
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+/// Request OAuth code from redirect `Location`
+///
+/// **The response must not be redirected by driver**
+pub struct GetOAuthCode<'a> {
+    pub uid: &'a str,
+}
+
+impl Call for GetOAuthCode<'_> {
+    type Output = String;
+
+    const KIND: CallKind = CallKind::Login;
+
+    fn account(&self) -> &str {
+        self.uid
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let callback = format!("{}/", endpoints.application.trim_end_matches('/'));
+
+        Ok(Request::get(endpoints.resolve(OAUTH_URL))
+            .query("bindSkip", "1")
+            .query("authType", "2")
+            .query("appid", APP_ID)
+            .query("callbackUrl", &callback)
+            .query("unionid", self.uid))
+    }
+
+    fn parse(&self, response: Response) -> Result<String> {
+        if !response.is_redirection() {
+            return Err(Error::Auth("OAuth failed.".to_string()));
+        }
+
+        let header_location = match response.header("Location") {
+            Some(header) => header,
+            None => return Err(Error::EmptyResp),
+        };
+
+        match extract_code(header_location) {
+            Some(t) => Ok(t),
+            None => Err(Error::EmptyResp),
+        }
+    }
+}
+
+/// Authorize by OAuth code
+///
+/// Returns session & user infos
+pub struct Authorize<'a> {
+    pub code: &'a str,
+}
+
+impl Call for Authorize<'_> {
+    type Output = (String, UserInfo);
+
+    /// The OAuth code, issued per user
+    fn account(&self) -> &str {
+        self.code
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(Request::post(endpoints.resolve(GET_USER_FOR_AUTHORIZE)).field("code", self.code))
+    }
+
+    fn parse(&self, response: Response) -> Result<(String, UserInfo)> {
+        response.check_status()?;
+
+        // get session
+        match response.cookie(SESSION_KEY) {
+            Some(v) => {
+                let session = v.to_string();
+
+                let resp: Envelope<UserInfo> = response.json()?;
+                if !resp.is_success() {
+                    return Err(Error::Auth(resp.message().to_string()));
+                }
+                match resp.data {
+                    Some(v) => Ok((session, v)),
+                    None => Err(Error::Auth(resp.message().to_string())),
+                }
+            }
+            None => {
+                let resp: Envelope = response.json()?;
+                Err(Error::Auth(resp.message().to_string()))
+            }
+        }
+    }
+}
+
+/// Get user info of authorized session
+pub struct GetUserInfo<'a> {
+    pub session: &'a str,
+}
+
+impl Call for GetUserInfo<'_> {
+    type Output = UserInfo;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(
+            session_post(endpoints, GET_USER_FOR_AUTHORIZE, self.session)
+                .field("userId", &rand::random::<u8>().to_string()),
+        )
+    }
+
+    fn parse(&self, response: Response) -> Result<UserInfo> {
+        response
+            .envelope::<UserInfo, ()>(GET_USER_FOR_AUTHORIZE)?
+            .into_data()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_oauth_code() {
+        let call = GetOAuthCode { uid: "123" };
+        let req = call
+            .request(&Endpoints::with_base("http://localhost"))
+            .unwrap();
+        assert_eq!(req.method, Method::Get);
+        assert_eq!(req.url, "http://localhost/authoriz/getCodeV2");
+        assert!(req
+            .query
+            .contains(&("callbackUrl".into(), "http://localhost/".into())));
+
+        let response = Response {
+            status: 302,
+            headers: vec![(
+                "Location".into(),
+                "https://application.xiaofubao.com/?ymCode=d15246f7&errCode=0&".into(),
+            )],
+            body: Default::default(),
+        };
+        assert_eq!(call.parse(response).unwrap(), "d15246f7");
+
+        let response = json_response("{}");
+        assert!(matches!(call.parse(response), Err(Error::Auth(_))));
+    }
+
+    #[test]
+    fn test_authorize() {
+        let body = r#"{"statusCode":0,"success":true,"message":"ok","data":{
+            "id":"1","mobilePhone":"188****8888","sex":1,"testAccount":0,
+            "platform":"YUNMA_APP","thirdOpenid":"x"}}"#;
+        let mut response = json_response(body);
+        response
+            .headers
+            .push(("Set-Cookie".into(), "shiroJID=abc; Path=/".into()));
+
+        assert_eq!(Authorize { code: "c" }.account(), "c");
+        let (session, info) = Authorize { code: "c" }.parse(response).unwrap();
+        assert_eq!(session, "abc");
+        assert_eq!(info.id, "1");
+
+        let response = json_response(r#"{"statusCode":500,"success":false,"message":"bad"}"#);
+        assert!(matches!(
+            Authorize { code: "c" }.parse(response),
+            Err(Error::Auth(m)) if m == "bad"
+        ));
+    }
+}
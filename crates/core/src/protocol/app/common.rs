@@ -0,0 +1,34 @@
+//! Application common APIs protocol
+
+use super::*;
+use crate::url::application::GET_SUBMIT_TOKEN;
+
+/// Get common submit token (formal)
+pub struct GetSubmitToken<'a> {
+    /// Application session, if any
+    pub session: Option<&'a str>,
+    pub uid: &'a str,
+}
+
+impl Call for GetSubmitToken<'_> {
+    type Output = String;
+
+    fn account(&self) -> &str {
+        self.uid
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let req = match self.session {
+            Some(session) => session_post(endpoints, GET_SUBMIT_TOKEN, session),
+            None => Request::post(endpoints.resolve(GET_SUBMIT_TOKEN)),
+        };
+
+        Ok(req.field("ymId", self.uid))
+    }
+
+    fn parse(&self, response: Response) -> Result<String> {
+        response
+            .envelope::<String, ()>(GET_SUBMIT_TOKEN)?
+            .into_data()
+    }
+}
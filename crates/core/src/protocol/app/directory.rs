@@ -0,0 +1,159 @@
+//! Room directory protocol
+//!
+//! Areas, buildings, floors and rooms, each level listed by the codes of the upper ones.
+
+use super::electricity::{parse_rows, BIND_TYPE};
+use super::*;
+
+use crate::bind::app::directory::{Area, Building, Floor, Room};
+use crate::url::application::electricity::{
+    QUERY_AREAS, QUERY_BUILDINGS, QUERY_FLOORS, QUERY_ROOMS,
+};
+
+fn directory_post(endpoints: &Endpoints, url: &str, session: &str) -> Request {
+    session_post(endpoints, url, session).field("bindType", BIND_TYPE)
+}
+
+/// Query areas, like campuses
+pub struct QueryAreas<'a> {
+    pub session: &'a str,
+}
+
+impl Call for QueryAreas<'_> {
+    type Output = Vec<Area>;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(directory_post(endpoints, QUERY_AREAS, self.session))
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<Area>> {
+        parse_rows(response, QUERY_AREAS, Self::REJECTED_IS_NO_BIND)
+    }
+}
+
+/// Query buildings of an area
+pub struct QueryBuildings<'a> {
+    pub session: &'a str,
+    pub area_id: &'a str,
+}
+
+impl Call for QueryBuildings<'_> {
+    type Output = Vec<Building>;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(directory_post(endpoints, QUERY_BUILDINGS, self.session).field("areaId", self.area_id))
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<Building>> {
+        parse_rows(response, QUERY_BUILDINGS, Self::REJECTED_IS_NO_BIND)
+    }
+}
+
+/// Query floors of a building
+pub struct QueryFloors<'a> {
+    pub session: &'a str,
+    pub area_id: &'a str,
+    pub building_code: &'a str,
+}
+
+impl Call for QueryFloors<'_> {
+    type Output = Vec<Floor>;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(directory_post(endpoints, QUERY_FLOORS, self.session)
+            .field("areaId", self.area_id)
+            .field("buildingCode", self.building_code))
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<Floor>> {
+        parse_rows(response, QUERY_FLOORS, Self::REJECTED_IS_NO_BIND)
+    }
+}
+
+/// Query rooms of a floor
+pub struct QueryRooms<'a> {
+    pub session: &'a str,
+    pub area_id: &'a str,
+    pub building_code: &'a str,
+    pub floor_code: &'a str,
+}
+
+impl Call for QueryRooms<'_> {
+    type Output = Vec<Room>;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(directory_post(endpoints, QUERY_ROOMS, self.session)
+            .field("areaId", self.area_id)
+            .field("buildingCode", self.building_code)
+            .field("floorCode", self.floor_code))
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<Room>> {
+        parse_rows(response, QUERY_ROOMS, Self::REJECTED_IS_NO_BIND)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rooms_request() {
+        let req = QueryRooms {
+            session: "s",
+            area_id: "1",
+            building_code: "2",
+            floor_code: "3",
+        }
+        .request(&Endpoints::default())
+        .unwrap();
+
+        assert_eq!(req.url, QUERY_ROOMS);
+        assert_eq!(req.cookie_header().unwrap(), "shiroJID=s");
+        assert_eq!(req.form[1], ("areaId".into(), "1".into()));
+        assert_eq!(req.form[3], ("floorCode".into(), "3".into()));
+    }
+
+    #[test]
+    fn test_nodes_parse() {
+        let response = json_response(
+            r#"{"statusCode":0,"success":true,"rows":[{"id":"1","areaName":"Main"}]}"#,
+        );
+        let areas = QueryAreas { session: "s" }.parse(response).unwrap();
+        assert_eq!(
+            (areas[0].id.as_str(), areas[0].name.as_str()),
+            ("1", "Main")
+        );
+
+        let response = json_response(
+            r#"{"statusCode":0,"success":true,"rows":[{"roomCode":"10101","roomName":"101"}]}"#,
+        );
+        let call = QueryRooms {
+            session: "s",
+            area_id: "1",
+            building_code: "2",
+            floor_code: "3",
+        };
+        let rooms = call.parse(response).unwrap();
+        assert_eq!(
+            (rooms[0].code.as_str(), rooms[0].name.as_str()),
+            ("10101", "101")
+        );
+    }
+}
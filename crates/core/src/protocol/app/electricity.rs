@@ -0,0 +1,519 @@
+//! Electricity APIs protocol
+
+use super::*;
+use chrono::NaiveDate;
+use serde::de::IgnoredAny;
+
+use crate::bind::app::electricity::{
+    BindInfo, RechargeRecord, RoomInfo, SurplusInfo, UsageRecord, UserRechargeRecord,
+};
+use crate::bind::Page;
+use crate::url::application::electricity::*;
+
+pub(super) const BIND_TYPE: &str = "3";
+const SUB_TYPE: &str = "100304";
+
+/// Append room fields to request form
+fn room_fields(req: Request, room: &RoomInfo) -> Request {
+    req.field("areaId", &room.area_id)
+        .field("buildingCode", &room.building_code)
+        .field("floorCode", &room.floor_code)
+        .field("roomCode", &room.room_code)
+}
+
+/// Parse rows response to `endpoint`, see [`Response::envelope_of`] for `no_bind`
+pub(super) fn parse_rows<T: DeserializeOwned>(
+    response: Response,
+    endpoint: &str,
+    no_bind: bool,
+) -> Result<Vec<T>> {
+    Ok(response
+        .envelope_of::<(), T>(endpoint, no_bind)?
+        .into_rows())
+}
+
+/// Parse rows response to `endpoint` as page `page`, see [`Response::envelope_of`] for `no_bind`
+fn parse_page<T: DeserializeOwned>(
+    response: Response,
+    endpoint: &str,
+    no_bind: bool,
+    page: u32,
+) -> Result<Page<T>> {
+    let envelope = response.envelope_of::<(), T>(endpoint, no_bind)?;
+    let total = envelope.total;
+
+    Ok(Page::numbered(envelope.into_rows(), total, page))
+}
+
+/// Query binding info
+///
+/// Only return the last binding of the list, see [`QueryBindings`] for all
+pub struct QueryBind<'a> {
+    pub session: &'a str,
+}
+
+impl Call for QueryBind<'_> {
+    type Output = BindInfo;
+    const REJECTED_IS_NO_BIND: bool = true;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(session_post(endpoints, QUERY_BIND, self.session).field("bindType", BIND_TYPE))
+    }
+
+    fn parse(&self, response: Response) -> Result<BindInfo> {
+        parse_rows::<BindInfo>(response, QUERY_BIND, Self::REJECTED_IS_NO_BIND)?
+            .pop()
+            .ok_or(Error::NoBind)
+    }
+}
+
+/// Query all electricity bindings of the user
+///
+/// Empty rows are an empty list, unlike [`QueryBind`].
+pub struct QueryBindings<'a> {
+    pub session: &'a str,
+}
+
+impl Call for QueryBindings<'_> {
+    type Output = Vec<BindInfo>;
+    const REJECTED_IS_NO_BIND: bool = true;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        QueryBind {
+            session: self.session,
+        }
+        .request(endpoints)
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<BindInfo>> {
+        parse_rows(response, QUERY_BIND, Self::REJECTED_IS_NO_BIND)
+    }
+}
+
+/// Query electricity surplus of room
+pub struct QuerySurplus<'a> {
+    pub session: &'a str,
+    pub room: &'a RoomInfo,
+}
+
+impl Call for QuerySurplus<'_> {
+    type Output = SurplusInfo;
+    const REJECTED_IS_NO_BIND: bool = true;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(room_fields(
+            session_post(endpoints, QUERY_SURPLUS, self.session),
+            self.room,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<SurplusInfo> {
+        response
+            .envelope_of::<SurplusInfo, ()>(QUERY_SURPLUS, Self::REJECTED_IS_NO_BIND)?
+            .into_data()
+    }
+}
+
+/// Query electricity usage records of room
+pub struct QueryUsageRecords<'a> {
+    pub session: &'a str,
+    pub room: &'a RoomInfo,
+    /// Type of meter
+    pub md_type: &'a str,
+}
+
+impl Call for QueryUsageRecords<'_> {
+    type Output = Vec<UsageRecord>;
+    const REJECTED_IS_NO_BIND: bool = true;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(room_fields(
+            session_post(endpoints, QUERY_USAGE_RECORDS, self.session)
+                .field("mdtype", self.md_type),
+            self.room,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<UsageRecord>> {
+        parse_rows(response, QUERY_USAGE_RECORDS, Self::REJECTED_IS_NO_BIND)
+    }
+}
+
+/// Query recharge records of user
+pub struct QueryUserRechargeRecords<'a> {
+    pub session: &'a str,
+    pub page: u32,
+    /// Month of the records, all if `None`
+    pub month: Option<NaiveDate>,
+}
+
+impl Call for QueryUserRechargeRecords<'_> {
+    type Output = Page<UserRechargeRecord>;
+    const REJECTED_IS_NO_BIND: bool = true;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let req = session_post(endpoints, QUERY_USER_RECHARGE_RECORDS, self.session)
+            .field("currentPage", &self.page.to_string())
+            .field("subType", SUB_TYPE);
+
+        Ok(match self.month {
+            Some(month) => req.field("createTime", &month.format("%Y-%m").to_string()),
+            None => req,
+        })
+    }
+
+    fn parse(&self, response: Response) -> Result<Page<UserRechargeRecord>> {
+        parse_page(
+            response,
+            QUERY_USER_RECHARGE_RECORDS,
+            Self::REJECTED_IS_NO_BIND,
+            self.page,
+        )
+    }
+}
+
+/// Query recharge records of room
+pub struct QueryRoomRechargeRecords<'a> {
+    pub session: &'a str,
+    pub page: u32,
+    pub room: &'a RoomInfo,
+}
+
+impl Call for QueryRoomRechargeRecords<'_> {
+    type Output = Page<RechargeRecord>;
+    const REJECTED_IS_NO_BIND: bool = true;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(room_fields(
+            session_post(endpoints, QUERY_ROOM_RECHARGE_RECORDS, self.session)
+                .field("currentPage", &self.page.to_string())
+                .field("subType", SUB_TYPE),
+            self.room,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<Page<RechargeRecord>> {
+        parse_page(
+            response,
+            QUERY_ROOM_RECHARGE_RECORDS,
+            Self::REJECTED_IS_NO_BIND,
+            self.page,
+        )
+    }
+}
+
+/// Create recharge transaction
+///
+/// Returns transaction No.
+pub struct Recharge<'a> {
+    pub session: &'a str,
+    pub room: &'a RoomInfo,
+    pub amount: u32,
+    pub cztype: &'a str,
+    pub mdname: &'a str,
+    pub submit_token: &'a str,
+    pub uid: &'a str,
+}
+
+impl Call for Recharge<'_> {
+    type Output = String;
+    const IDEMPOTENT: bool = false;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(
+            room_fields(session_post(endpoints, RECHARGE, self.session), self.room)
+                .field("money", &self.amount.to_string())
+                .field("cztype", self.cztype)
+                .field("mdname", self.mdname)
+                .field("ymId", self.uid)
+                .field("submitToken", self.submit_token),
+        )
+    }
+
+    fn parse(&self, response: Response) -> Result<String> {
+        // `data` is the redirect URL
+        let data = response.envelope::<String, ()>(RECHARGE)?.into_data()?;
+
+        let callback_url = match reqwest::Url::parse(&data) {
+            Ok(v) => v,
+            Err(_) => return Err(Error::EmptyResp),
+        };
+
+        for (key, value) in callback_url.query_pairs() {
+            if key == "tran_no" {
+                return Ok(value.to_string());
+            }
+        }
+
+        Err(Error::EmptyResp)
+    }
+}
+
+/// Words of the rejection messages of a locked binding, like `"当前绑定不允许修改"`
+const BIND_LOCKED: [&str; 3] = ["不允许", "不可", "锁定"];
+
+/// Check the response of a binding change
+///
+/// Rejections of a locked binding are [`Error::BindNotAllowed`], others are mapped
+/// as usual.
+fn parse_bind_change(response: Response, endpoint: &str) -> Result<()> {
+    response.check_status()?;
+    let envelope = response.json::<Envelope<IgnoredAny, IgnoredAny>>()?;
+    let message = envelope.message();
+    if !envelope.is_success()
+        && envelope.status_code == envelope::status::REJECTED
+        && BIND_LOCKED.iter().any(|w| message.contains(w))
+    {
+        return Err(Error::BindNotAllowed(message.to_string()));
+    }
+    envelope.check(endpoint)?;
+
+    Ok(())
+}
+
+/// Bind a room
+pub struct BindRoom<'a> {
+    pub session: &'a str,
+    pub room: &'a RoomInfo,
+}
+
+impl Call for BindRoom<'_> {
+    type Output = ();
+    const IDEMPOTENT: bool = false;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(room_fields(
+            session_post(endpoints, BIND_ROOM, self.session).field("bindType", BIND_TYPE),
+            self.room,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<()> {
+        parse_bind_change(response, BIND_ROOM)
+    }
+}
+
+/// Change the room of binding `id`
+pub struct UpdateBind<'a> {
+    pub session: &'a str,
+    pub id: &'a str,
+    pub room: &'a RoomInfo,
+}
+
+impl Call for UpdateBind<'_> {
+    type Output = ();
+    const IDEMPOTENT: bool = false;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(room_fields(
+            session_post(endpoints, UPDATE_BIND, self.session)
+                .field("id", self.id)
+                .field("bindType", BIND_TYPE),
+            self.room,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<()> {
+        parse_bind_change(response, UPDATE_BIND)
+    }
+}
+
+/// Remove binding `id`
+pub struct DeleteBind<'a> {
+    pub session: &'a str,
+    pub id: &'a str,
+}
+
+impl Call for DeleteBind<'_> {
+    type Output = ();
+    const IDEMPOTENT: bool = false;
+
+    fn account(&self) -> &str {
+        self.session
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(session_post(endpoints, DELETE_BIND, self.session)
+            .field("id", self.id)
+            .field("bindType", BIND_TYPE))
+    }
+
+    fn parse(&self, response: Response) -> Result<()> {
+        parse_bind_change(response, DELETE_BIND)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn room() -> RoomInfo {
+        RoomInfo {
+            area_id: "1".into(),
+            building_code: "2".into(),
+            floor_code: "3".into(),
+            room_code: "4".into(),
+        }
+    }
+
+    #[test]
+    fn test_surplus_request() {
+        let room = room();
+        let req = QuerySurplus {
+            session: "s",
+            room: &room,
+        }
+        .request(&Endpoints::default())
+        .unwrap();
+
+        assert_eq!(req.method, Method::Post);
+        assert_eq!(req.url, QUERY_SURPLUS);
+        assert_eq!(req.cookie_header().unwrap(), "shiroJID=s");
+        assert_eq!(req.form[0], ("areaId".into(), "1".into()));
+        assert_eq!(req.form[3], ("roomCode".into(), "4".into()));
+    }
+
+    #[test]
+    fn test_bind_parse() {
+        let call = QueryBind { session: "s" };
+
+        let response = json_response(r#"{"statusCode":0,"success":true,"rows":[]}"#);
+        assert!(matches!(call.parse(response), Err(Error::NoBind)));
+
+        let response = json_response(r#"{"statusCode":204,"success":false,"message":"x"}"#);
+        assert!(matches!(call.parse(response), Err(Error::Auth(_))));
+
+        let response = json_response(r#"{"statusCode":203,"success":false,"message":"x"}"#);
+        assert!(matches!(call.parse(response), Err(Error::NoBind)));
+
+        // Not a binding call, mapped by the message
+        let room = room();
+        let recharge = Recharge {
+            session: "s",
+            room: &room,
+            amount: 10,
+            cztype: "50426",
+            mdname: "照明用电",
+            submit_token: "t",
+            uid: "u",
+        };
+        let response = json_response(r#"{"statusCode":203,"success":false,"message":"x"}"#);
+        assert!(matches!(
+            recharge.parse(response),
+            Err(Error::Api {
+                status_code: 203,
+                ..
+            })
+        ));
+
+        let call = QueryBindings { session: "s" };
+        let response = json_response(r#"{"statusCode":0,"success":true,"rows":[]}"#);
+        assert!(call.parse(response).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bind_change_parse() {
+        let room = room();
+        let call = UpdateBind {
+            session: "s",
+            id: "1",
+            room: &room,
+        };
+        let req = call.request(&Endpoints::default()).unwrap();
+        assert_eq!(req.url, UPDATE_BIND);
+        assert_eq!(req.form[0], ("id".into(), "1".into()));
+
+        let response = json_response(r#"{"statusCode":0,"success":true,"data":{"id":"1"}}"#);
+        assert!(call.parse(response).is_ok());
+
+        let response =
+            json_response(r#"{"statusCode":203,"success":false,"message":"当前绑定不允许修改"}"#);
+        assert!(matches!(
+            call.parse(response),
+            Err(Error::BindNotAllowed(_))
+        ));
+
+        let response = json_response(r#"{"statusCode":203,"success":false,"message":"x"}"#);
+        assert!(matches!(
+            call.parse(response),
+            Err(Error::Api { status_code: 203, message, .. }) if message == "x"
+        ));
+
+        let response = json_response(r#"{"statusCode":204,"success":false,"message":"x"}"#);
+        assert!(matches!(call.parse(response), Err(Error::Auth(_))));
+    }
+
+    #[test]
+    fn test_bind_schema() {
+        let response = json_response(
+            r#"{"statusCode":0,"success":true,"rows":[{"id":"1","schoolCode":"s",
+            "schoolName":"n","jobNo":"j","userName":"u","bindTypeStr":"3","areaId":"1",
+            "areaName":"a","buildingCode":"2","buildingName":"b","floorCode":"3",
+            "floorName":"f","roomCode":"4","roomName":"r","createTime":"t","isAllowChange":1}]}"#,
+        );
+        let info = QueryBind { session: "s" }.parse(response).unwrap();
+
+        // Output in snake_case, which is accepted as well
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["school_code"], "s");
+        assert_eq!(json["is_allow_change"], 1);
+        assert!(json.get("schoolCode").is_none());
+        assert_eq!(serde_json::from_value::<BindInfo>(json).unwrap(), info);
+    }
+
+    #[test]
+    fn test_recharge_parse() {
+        let room = room();
+        let call = Recharge {
+            session: "s",
+            room: &room,
+            amount: 10,
+            cztype: "50426",
+            mdname: "照明用电",
+            submit_token: "t",
+            uid: "u",
+        };
+
+        let response = json_response(
+            r#"{"statusCode":0,"success":true,"message":"ok",
+            "data":"https://pay.xiaofubao.com/pay/unified/toCashier.shtml?tran_no=123&a=b"}"#,
+        );
+        assert_eq!(call.parse(response).unwrap(), "123");
+    }
+}
@@ -0,0 +1,19 @@
+//! Application APIs protocol
+//!
+//! Every call except [`auth::GetOAuthCode`] and [`auth::Authorize`] requires an authorized session.
+
+use super::*;
+
+pub mod auth;
+pub mod common;
+pub mod directory;
+pub mod electricity;
+
+pub(crate) const APP_ID: &str = "1810181825222034";
+/// Cookie name of application session
+pub const SESSION_KEY: &str = "shiroJID";
+
+/// Build request carrying the application session cookie
+fn session_post(endpoints: &Endpoints, url: &str, session: &str) -> Request {
+    Request::post(endpoints.resolve(url)).cookie(SESSION_KEY, session)
+}
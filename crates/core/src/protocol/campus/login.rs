@@ -0,0 +1,360 @@
+//! Campus login APIs protocol
+
+use super::*;
+use crate::bind::campus::login::{app_security_token, LoginInfo, SecurityTokenInfo};
+use crate::url::campus::login::*;
+use crate::utils::gen_random_fake_md5;
+
+/// Append login device fields to request form
+fn login_fields(req: Request, profile: &DeviceProfile, device_id: &str) -> Request {
+    req.field("clientId", &profile.client_id)
+        .field("osType", &profile.os_type)
+        .field("osUuid", device_id)
+        .field("osVersion", &profile.os_version)
+}
+
+fn device_post(
+    endpoints: &Endpoints,
+    url: &str,
+    profile: &DeviceProfile,
+    device_id: &str,
+) -> Request {
+    device_fields(Request::post(endpoints.resolve(url)), profile, device_id)
+}
+
+/// Parse envelope data
+fn parse_data<T: DeserializeOwned>(response: Response, endpoint: &str) -> Result<T> {
+    response.envelope::<T, ()>(endpoint)?.into_data()
+}
+
+/// Get security token & level
+pub struct GetSecurityToken<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+}
+
+impl Call for GetSecurityToken<'_> {
+    type Output = SecurityTokenInfo;
+
+    fn account(&self) -> &str {
+        self.device_id
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(device_post(
+            endpoints,
+            GET_SECURITY_TOKEN,
+            self.profile,
+            self.device_id,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<SecurityTokenInfo> {
+        parse_data(response, GET_SECURITY_TOKEN)
+    }
+}
+
+/// Get image captcha in base64
+pub struct GetCaptchaImage<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+    pub security_token: &'a str,
+}
+
+impl Call for GetCaptchaImage<'_> {
+    type Output = String;
+
+    fn account(&self) -> &str {
+        self.device_id
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(
+            device_post(endpoints, GET_IMAGE_CAPTCHA, self.profile, self.device_id)
+                .field("securityToken", self.security_token),
+        )
+    }
+
+    fn parse(&self, response: Response) -> Result<String> {
+        parse_data(response, GET_IMAGE_CAPTCHA)
+    }
+}
+
+/// Send login verification code SMS
+///
+/// Returns whether the user exists
+pub struct SendVerificationCode<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+    pub phone_num: &'a str,
+    pub security_token: &'a str,
+    /// Image captcha, if required
+    pub captcha: Option<&'a str>,
+}
+
+impl Call for SendVerificationCode<'_> {
+    type Output = bool;
+    const IDEMPOTENT: bool = false;
+    const KIND: CallKind = CallKind::Sms;
+
+    fn account(&self) -> &str {
+        self.phone_num
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let app_security_token =
+            app_security_token(self.security_token, self.device_id, self.profile)?; // Important
+
+        let req = device_post(
+            endpoints,
+            SEND_VERIFICATION_CODE,
+            self.profile,
+            self.device_id,
+        )
+        .field("appSecurityToken", &app_security_token)
+        .field("securityToken", self.security_token)
+        .field("sendCount", "1")
+        .field("mobilePhone", self.phone_num);
+
+        // If image captcha required
+        Ok(match self.captcha {
+            Some(v) => req.field("imageCaptchaValue", v),
+            None => req,
+        })
+    }
+
+    fn parse(&self, response: Response) -> Result<bool> {
+        /// Define data object
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Data {
+            user_exists: bool,
+        }
+
+        let data: Data = parse_data(response, SEND_VERIFICATION_CODE)?;
+
+        Ok(data.user_exists)
+    }
+}
+
+/// Login by SMS verification code
+pub struct LoginByCode<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+    pub phone_num: &'a str,
+    pub code: &'a str,
+}
+
+impl Call for LoginByCode<'_> {
+    type Output = LoginInfo;
+
+    const KIND: CallKind = CallKind::Login;
+
+    fn account(&self) -> &str {
+        self.phone_num
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let req = device_post(endpoints, DO_LOGIN_BY_CODE, self.profile, self.device_id)
+            .field("clientId", &self.profile.client_id)
+            .field("mobilePhone", self.phone_num)
+            .field("osType", &self.profile.os_type)
+            .field("osUuid", self.device_id)
+            .field("osVersion", &self.profile.os_version)
+            .field("verificationCode", self.code);
+
+        Ok(req)
+    }
+
+    fn parse(&self, response: Response) -> Result<LoginInfo> {
+        parse_data(response, DO_LOGIN_BY_CODE)
+    }
+}
+
+/// Login in silent by UID-DeviceID binding
+///
+/// If `token` is `None`, a random one will be generated.
+pub struct SilentLogin<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+    pub uid: &'a str,
+    pub token: Option<&'a str>,
+}
+
+impl Call for SilentLogin<'_> {
+    type Output = LoginInfo;
+
+    const KIND: CallKind = CallKind::Login;
+
+    fn account(&self) -> &str {
+        self.uid
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let token = match self.token {
+            Some(t) => t.to_string(),
+            None => gen_random_fake_md5(),
+        };
+
+        let req = login_fields(
+            device_post(endpoints, DO_LOGIN_BY_TOKEN, self.profile, self.device_id),
+            self.profile,
+            self.device_id,
+        )
+        .field("ymId", self.uid)
+        .field("token", &token);
+
+        Ok(req)
+    }
+
+    fn parse(&self, response: Response) -> Result<LoginInfo> {
+        parse_data(response, DO_LOGIN_BY_TOKEN)
+    }
+}
+
+/// Get the public key used to encrypt the password
+pub struct GetPublicKey<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+}
+
+impl Call for GetPublicKey<'_> {
+    type Output = String;
+
+    fn account(&self) -> &str {
+        self.device_id
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(device_post(
+            endpoints,
+            GET_PUBLIC_KEY,
+            self.profile,
+            self.device_id,
+        ))
+    }
+
+    fn parse(&self, response: Response) -> Result<String> {
+        #[derive(Debug, Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PublicKey {
+            public_key: String,
+        }
+
+        let data: PublicKey = parse_data(response, GET_PUBLIC_KEY)?;
+
+        Ok(data.public_key)
+    }
+}
+
+/// Login by password
+///
+/// The password will be encrypted by [`crate::utils::encrypt_password`]
+pub struct LoginByPassword<'a> {
+    pub device_id: &'a str,
+    pub profile: &'a DeviceProfile,
+    pub phone_num: &'a str,
+    pub password: &'a str,
+    pub public_key: &'a str,
+}
+
+impl Call for LoginByPassword<'_> {
+    type Output = LoginInfo;
+
+    const KIND: CallKind = CallKind::Login;
+
+    fn account(&self) -> &str {
+        self.phone_num
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        let encrypted_password = crate::utils::encrypt_password(self.password, self.public_key)?;
+
+        let req = device_post(endpoints, DO_LOGIN_BY_PWD, self.profile, self.device_id)
+            .field("clientId", &self.profile.client_id)
+            .field("mobilePhone", self.phone_num)
+            .field("osType", &self.profile.os_type)
+            .field("osUuid", self.device_id)
+            .field("osVersion", &self.profile.os_version)
+            .field("password", &encrypted_password);
+
+        Ok(req)
+    }
+
+    fn parse(&self, response: Response) -> Result<LoginInfo> {
+        parse_data(response, DO_LOGIN_BY_PWD)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_silent_login_request() {
+        let profile = DeviceProfile::android();
+        let req = SilentLogin {
+            device_id: "d",
+            profile: &profile,
+            uid: "u",
+            token: Some("t"),
+        }
+        .request(&Endpoints::default())
+        .unwrap();
+
+        assert_eq!(req.url, DO_LOGIN_BY_TOKEN);
+        assert!(req.form.contains(&("deviceId".into(), "d".into())));
+        assert!(req.form.contains(&("osUuid".into(), "d".into())));
+        assert!(req.form.contains(&("osType".into(), "Android".into())));
+        assert!(req.form.contains(&("osVersion".into(), "12".into())));
+        assert_eq!(req.form.last().unwrap(), &("token".into(), "t".into()));
+    }
+
+    #[test]
+    fn test_send_verification_code_parse() {
+        let profile = DeviceProfile::default();
+        let call = SendVerificationCode {
+            device_id: "d",
+            profile: &profile,
+            phone_num: "1",
+            security_token: "t",
+            captcha: None,
+        };
+
+        let response = json_response(
+            r#"{"statusCode":0,"success":true,"message":"","data":{"userExists":true}}"#,
+        );
+        assert!(call.parse(response).unwrap());
+
+        let response =
+            json_response(r#"{"statusCode":203,"success":false,"message":"触发号码天级流控"}"#);
+        assert!(matches!(call.parse(response), Err(Error::Limited)));
+
+        let response =
+            json_response(r#"{"statusCode":203,"success":false,"message":"请输入正确的手机号"}"#);
+        assert!(matches!(call.parse(response), Err(Error::BadPhoneNumber)));
+    }
+
+    #[test]
+    fn test_login_parse() {
+        let profile = DeviceProfile::default();
+        let call = LoginByPassword {
+            device_id: "d",
+            profile: &profile,
+            phone_num: "1",
+            password: "p",
+            public_key: "k",
+        };
+
+        let response =
+            json_response(r#"{"statusCode":203,"success":false,"message":"设备已更换"}"#);
+        assert!(matches!(
+            call.parse(response),
+            Err(Error::AuthDeviceChanged)
+        ));
+
+        let response =
+            json_response(r#"{"statusCode":203,"success":false,"message":"您已输错1次密码"}"#);
+        assert!(matches!(call.parse(response), Err(Error::BadLoginSecret)));
+    }
+}
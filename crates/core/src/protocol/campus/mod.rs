@@ -0,0 +1,40 @@
+//! Campus(yiSchool) APP APIs protocol
+
+use super::*;
+use crate::bind::DeviceProfile;
+
+pub mod login;
+pub mod user;
+
+/// Append common device fields to request form
+fn device_fields(req: Request, profile: &DeviceProfile, device_id: &str) -> Request {
+    req.field("appVersion", &profile.app_ver)
+        .field("deviceId", device_id)
+        .field("platform", &profile.platform)
+        .field("testAccount", "1")
+}
+
+/// Logged-in campus account
+#[derive(Debug, Clone, Copy)]
+pub struct Identity<'a> {
+    pub device_id: &'a str,
+    pub uid: &'a str,
+    pub school_code: &'a str,
+    /// Session token
+    pub token: &'a str,
+    pub profile: &'a DeviceProfile,
+}
+
+impl Identity<'_> {
+    /// Build request carrying the account fields
+    fn post(&self, endpoints: &Endpoints, url: &str) -> Request {
+        device_fields(
+            Request::post(endpoints.resolve(url)),
+            self.profile,
+            self.device_id,
+        )
+        .field("token", self.token)
+        .field("ymId", self.uid)
+        .field("schoolCode", self.school_code)
+    }
+}
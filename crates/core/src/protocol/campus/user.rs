@@ -0,0 +1,150 @@
+//! Campus user APIs protocol
+
+use super::*;
+use chrono::NaiveDate;
+
+use crate::bind::campus::user::{ConsumptionRecord, TransactionRecords};
+use crate::units::Money;
+use crate::url::campus::user::*;
+
+/// Query campus card balance
+///
+/// Sent in string, like "20.01"
+pub struct QueryCardBalance<'a> {
+    pub identity: Identity<'a>,
+}
+
+impl Call for QueryCardBalance<'_> {
+    type Output = Money;
+
+    fn account(&self) -> &str {
+        self.identity.uid
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(self.identity.post(endpoints, QUERY_CARD_BALANCE))
+    }
+
+    fn parse(&self, response: Response) -> Result<Money> {
+        response
+            .envelope::<Money, ()>(QUERY_CARD_BALANCE)?
+            .into_data()
+    }
+}
+
+/// Query consumption records of a day
+pub struct QueryConsumptionRecords<'a> {
+    pub identity: Identity<'a>,
+    pub date: NaiveDate,
+}
+
+impl Call for QueryConsumptionRecords<'_> {
+    type Output = Vec<ConsumptionRecord>;
+
+    fn account(&self) -> &str {
+        self.identity.uid
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(self
+            .identity
+            .post(endpoints, QUERY_CONSUMPTION_RECORDS)
+            .field("queryTime", &self.date.format("%Y%m%d").to_string()))
+    }
+
+    fn parse(&self, response: Response) -> Result<Vec<ConsumptionRecord>> {
+        Ok(response
+            .envelope::<(), ConsumptionRecord>(QUERY_CONSUMPTION_RECORDS)?
+            .into_rows())
+    }
+}
+
+/// Query campus APP account transaction records
+///
+/// Paginated by `offset` and `limit`, see [`TransactionRecords::into_page`]
+pub struct QueryTransactionRecords<'a> {
+    pub identity: Identity<'a>,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+impl Call for QueryTransactionRecords<'_> {
+    type Output = TransactionRecords;
+
+    fn account(&self) -> &str {
+        self.identity.uid
+    }
+
+    fn request(&self, endpoints: &Endpoints) -> Result<Request> {
+        Ok(self
+            .identity
+            .post(endpoints, QUERY_TRANSACTION_RECORDS)
+            .field("offset", &self.offset.to_string())
+            .field("limit", &self.limit.to_string()))
+    }
+
+    fn parse(&self, response: Response) -> Result<TransactionRecords> {
+        response
+            .envelope::<TransactionRecords, ()>(QUERY_TRANSACTION_RECORDS)?
+            .into_data()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn identity(profile: &DeviceProfile) -> Identity<'_> {
+        Identity {
+            device_id: "d",
+            uid: "u",
+            school_code: "s",
+            token: "t",
+            profile,
+        }
+    }
+
+    #[test]
+    fn test_card_balance() {
+        let profile = DeviceProfile::android();
+        let call = QueryCardBalance {
+            identity: identity(&profile),
+        };
+
+        let req = call.request(&Endpoints::default()).unwrap();
+        assert_eq!(req.url, QUERY_CARD_BALANCE);
+        assert!(req.form.contains(&("schoolCode".into(), "s".into())));
+        assert!(req
+            .form
+            .contains(&("appVersion".into(), profile.app_ver.clone())));
+
+        let response =
+            json_response(r#"{"statusCode":0,"success":true,"message":"","data":"20.01"}"#);
+        assert_eq!(call.parse(response).unwrap(), Money::from_fen(2001));
+
+        let response =
+            json_response(r#"{"statusCode":204,"success":false,"bizCode":"10011","message":""}"#);
+        assert!(matches!(
+            call.parse(response),
+            Err(Error::AuthDeviceChanged)
+        ));
+    }
+
+    #[test]
+    fn test_transaction_records_parse() {
+        let profile = DeviceProfile::default();
+        let call = QueryTransactionRecords {
+            identity: identity(&profile),
+            offset: 0,
+            limit: 10,
+        };
+
+        let response = json_response(r#"{"statusCode":"204","message":"expired"}"#);
+        assert!(matches!(call.parse(response), Err(Error::Auth(_))));
+
+        let response = json_response(
+            r#"{"statusCode":0,"message":"","data":{"total":0,"tradeDetails":[],"tradeCounts":[]}}"#,
+        );
+        assert_eq!(call.parse(response).unwrap().total, 0);
+    }
+}
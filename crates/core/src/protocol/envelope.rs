@@ -0,0 +1,234 @@
+//! Response envelope shared by all JSON APIs
+//!
+//! ```json
+//! {"statusCode": 0, "success": true, "bizCode": "10011", "message": "", "data": {}, "rows": [], "total": 1}
+//! ```
+//!
+//! Every field is optional, and `statusCode`/`bizCode` may be either numbers or strings.
+//! Unsuccessful envelopes are mapped to [`Error`] by [`Envelope::check`] in one place.
+
+use serde::{Deserialize, Deserializer};
+
+use super::*;
+
+/// Error response messages
+pub mod message {
+    pub const WRONG_SECRET: &str = "您已输错";
+    pub const BAD_PHONE_NUM: &str = "请输入正确的手机号";
+    pub const BAD_PHONE_NUM_FORMAT: &str = "手机号码格式错误";
+    pub const TOO_FREQUENT: &str = "经过你的";
+    pub const TOO_MANY_TRIES: &str = "发送超限，请明天再来";
+    pub const FLOW_CONTROL: &str = "触发号码天级流控";
+    pub const DEVICE_CHANGED: &str = "设备已更换";
+    pub const VERIFICATION_EXPIRED: &str = "验证码已失效";
+    pub const BAD_TOKEN: &str = "token无效";
+}
+
+/// Status codes
+pub mod status {
+    pub const OK: i64 = 0;
+    /// No binding of electricity calls, or rejected input
+    pub const REJECTED: i64 = 203;
+    /// Unauthorized
+    pub const UNAUTHORIZED: i64 = 204;
+}
+
+/// Biz codes of [`status::UNAUTHORIZED`]
+pub mod biz {
+    pub const USER_NOT_FOUND: &str = "10010";
+    pub const DEVICE_CHANGED: &str = "10011";
+}
+
+/// Generic response envelope
+///
+/// `D` is the type of `data`, `R` is the element type of `rows`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope<D = (), R = ()> {
+    #[serde(default, deserialize_with = "code")]
+    pub status_code: i64,
+    pub success: Option<bool>,
+    #[serde(default, deserialize_with = "optional_code")]
+    pub biz_code: Option<String>,
+    pub message: Option<String>,
+    pub data: Option<D>,
+    pub rows: Option<Vec<R>>,
+    pub total: Option<i64>,
+}
+
+impl<D, R> Envelope<D, R> {
+    /// Whether the request succeeded, by `success` or else `statusCode`
+    pub fn is_success(&self) -> bool {
+        self.success.unwrap_or(self.status_code == status::OK)
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_deref().unwrap_or_default()
+    }
+
+    /// Map unsuccessful envelope to [`Error`]
+    ///
+    /// `endpoint` is the URL of the request, e.g. [`QUERY_BIND`](crate::url::application::electricity::QUERY_BIND).
+    pub fn check(self, endpoint: &str) -> Result<Self> {
+        if self.is_success() {
+            return Ok(self);
+        }
+
+        Err(self.error(endpoint))
+    }
+
+    /// Shared mapping from unsuccessful envelope to [`Error`]
+    pub fn error(&self, endpoint: &str) -> Error {
+        let message = self.message();
+
+        if message.starts_with(message::WRONG_SECRET) {
+            return Error::BadLoginSecret;
+        }
+        if message == message::DEVICE_CHANGED {
+            return Error::AuthDeviceChanged;
+        }
+        if message == message::BAD_PHONE_NUM || message == message::BAD_PHONE_NUM_FORMAT {
+            return Error::BadPhoneNumber;
+        }
+        if message.starts_with(message::TOO_FREQUENT)
+            || message == message::FLOW_CONTROL
+            || message == message::TOO_MANY_TRIES
+        {
+            return Error::Limited;
+        }
+        if message == message::VERIFICATION_EXPIRED || message == message::BAD_TOKEN {
+            return Error::BadInput(message.to_string());
+        }
+
+        match self.status_code {
+            status::UNAUTHORIZED => match self.biz_code.as_deref() {
+                Some(biz::USER_NOT_FOUND) => Error::AuthUserNotFound,
+                Some(biz::DEVICE_CHANGED) => Error::AuthDeviceChanged,
+                _ if message.is_empty() => Error::Auth("Unauthorized".to_string()),
+                _ => Error::Auth(message.to_string()),
+            },
+            status_code => Error::Api {
+                endpoint: url_path(endpoint).to_string(),
+                status_code,
+                biz_code: self.biz_code.clone(),
+                message: message.to_string(),
+            },
+        }
+    }
+
+    /// Take `data`, [`Error::EmptyResp`] if absent
+    pub fn into_data(self) -> Result<D> {
+        self.data.ok_or(Error::EmptyResp)
+    }
+
+    /// Take `rows`, empty if absent
+    ///
+    /// No rows is a successful result rather than an error.
+    pub fn into_rows(self) -> Vec<R> {
+        self.rows.unwrap_or_default()
+    }
+}
+
+impl Response {
+    /// Check HTTP status, decode JSON [`Envelope`] and map unsuccessful one to [`Error`]
+    pub fn envelope<D: DeserializeOwned, R: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+    ) -> Result<Envelope<D, R>> {
+        self.check_status()?;
+        self.json::<Envelope<D, R>>()?.check(endpoint)
+    }
+
+    /// Like [`Self::envelope`], mapping [`status::REJECTED`] to [`Error::NoBind`]
+    /// if `no_bind`, see [`Call::REJECTED_IS_NO_BIND`]
+    pub fn envelope_of<D: DeserializeOwned, R: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        no_bind: bool,
+    ) -> Result<Envelope<D, R>> {
+        self.check_status()?;
+        let envelope = self.json::<Envelope<D, R>>()?;
+        if no_bind && !envelope.is_success() && envelope.status_code == status::REJECTED {
+            return Err(Error::NoBind);
+        }
+
+        envelope.check(endpoint)
+    }
+}
+
+/// Numeric or string code
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Code {
+    Number(i64),
+    String(String),
+}
+
+fn code<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<i64, D::Error> {
+    match Code::deserialize(deserializer)? {
+        Code::Number(v) => Ok(v),
+        Code::String(v) => v.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+fn optional_code<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    Ok(match Option::<Code>::deserialize(deserializer)? {
+        Some(Code::Number(v)) => Some(v.to_string()),
+        Some(Code::String(v)) => Some(v),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(body: &'static str) -> Result<Envelope<String, String>> {
+        json_response(body).envelope("https://a.com/test")
+    }
+
+    #[test]
+    fn test_codes() {
+        let envelope = parse(r#"{"statusCode":"0","bizCode":10,"data":"x"}"#).unwrap();
+        assert_eq!(envelope.status_code, 0);
+        assert_eq!(envelope.biz_code.as_deref(), Some("10"));
+        assert_eq!(envelope.into_data().unwrap(), "x");
+
+        let envelope = parse(r#"{"statusCode":0,"success":true,"rows":[],"total":0}"#).unwrap();
+        assert!(envelope.into_rows().is_empty());
+
+        let envelope = parse(r#"{"statusCode":0,"success":true}"#).unwrap();
+        assert!(envelope.into_rows().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse(r#"{"statusCode":"204","message":"expired"}"#),
+            Err(Error::Auth(m)) if m == "expired"
+        ));
+        assert!(matches!(
+            parse(r#"{"statusCode":204,"success":false,"bizCode":"10010"}"#),
+            Err(Error::AuthUserNotFound)
+        ));
+        assert!(matches!(
+            parse(r#"{"statusCode":204,"success":false,"bizCode":10011}"#),
+            Err(Error::AuthDeviceChanged)
+        ));
+        assert!(matches!(
+            parse(r#"{"statusCode":203,"success":false,"message":"未绑定"}"#),
+            Err(Error::Api { status_code: 203, message, .. }) if message == "未绑定"
+        ));
+        assert!(matches!(
+            parse(r#"{"statusCode":203,"success":false,"message":"发送超限，请明天再来"}"#),
+            Err(Error::Limited)
+        ));
+        assert!(matches!(
+            parse(r#"{"statusCode":500,"success":false,"bizCode":1,"message":"x"}"#),
+            Err(Error::Api { endpoint, status_code: 500, biz_code: Some(biz), message })
+                if endpoint == "/test" && biz == "1" && message == "x"
+        ));
+    }
+}
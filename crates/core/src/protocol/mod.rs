@@ -0,0 +1,281 @@
+//! Sans-IO protocol layer
+//!
+//! Every API is described by a [`Call`]: it builds a plain [`Request`] and parses a raw
+//! [`Response`] into a typed result, without performing any I/O.
+//!
+//! The handlers in [`crate::bind`] (and [`crate::blocking`]) are thin drivers over this layer,
+//! so the API can also be driven by any other HTTP stack:
+//!
+//! ```no_run
+//! use yxy::protocol::{Call, Response};
+//! use yxy::protocol::app::electricity::QueryBind;
+//! use yxy::url::Endpoints;
+//!
+//! # fn run() -> Result<(), yxy::error::Error> {
+//! let call = QueryBind { session: "session" };
+//! let request = call.request(&Endpoints::default())?;
+//!
+//! // Perform `request` by your own HTTP client, then
+//! let response = Response {
+//!     status: 200,
+//!     headers: vec![],
+//!     body: r#"{"statusCode":0,"success":true,"rows":[]}"#.into(),
+//! };
+//! let result = call.parse(response);
+//! # Ok(())
+//! # }
+//! ```
+
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::url::Endpoints;
+
+pub mod app;
+pub mod campus;
+pub mod envelope;
+
+pub use envelope::Envelope;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// An API call described without I/O
+pub trait Call {
+    /// Parsed result type
+    type Output;
+
+    /// Whether repeating the call has no extra side effects
+    ///
+    /// Non-idempotent calls, e.g. recharge, are never retried.
+    const IDEMPOTENT: bool = true;
+
+    /// Rate limit class of the call
+    const KIND: CallKind = CallKind::Query;
+
+    /// Whether [`envelope::status::REJECTED`] means no electricity binding, see [`Response::envelope_of`]
+    ///
+    /// Otherwise it is mapped by the message, like other unsuccessful envelopes.
+    const REJECTED_IS_NO_BIND: bool = false;
+
+    /// Account the call acts for, e.g. session, UID or phone number
+    ///
+    /// Rate limits are counted per account.
+    fn account(&self) -> &str {
+        ""
+    }
+
+    /// Build the HTTP request on specific [`Endpoints`]
+    fn request(&self, endpoints: &Endpoints) -> Result<Request>;
+
+    /// Parse the raw HTTP response
+    fn parse(&self, response: Response) -> Result<Self::Output>;
+}
+
+/// Class of [`Call`], each class has its own rate limit quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// Read-only queries
+    Query,
+    /// Login and authorization attempts
+    Login,
+    /// Sending SMS verification code
+    Sms,
+}
+
+/// HTTP request method
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// Plain HTTP request description
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Request {
+    pub method: Method,
+    /// Full URL without query string
+    pub url: String,
+    /// Query string pairs
+    pub query: Vec<(String, String)>,
+    /// `application/x-www-form-urlencoded` body pairs
+    pub form: Vec<(String, String)>,
+    /// Cookies to send
+    pub cookies: Vec<(String, String)>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: String) -> Self {
+        Self {
+            method,
+            url,
+            query: Vec::new(),
+            form: Vec::new(),
+            cookies: Vec::new(),
+        }
+    }
+
+    pub fn get(url: String) -> Self {
+        Self::new(Method::Get, url)
+    }
+
+    pub fn post(url: String) -> Self {
+        Self::new(Method::Post, url)
+    }
+
+    /// Append a query string pair
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Append a form body pair
+    pub fn field(mut self, key: &str, value: &str) -> Self {
+        self.form.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Append a cookie
+    pub fn cookie(mut self, name: &str, value: &str) -> Self {
+        self.cookies.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Value of `Cookie` header
+    pub fn cookie_header(&self) -> Option<String> {
+        if self.cookies.is_empty() {
+            return None;
+        }
+
+        let pairs: Vec<String> = self
+            .cookies
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        Some(pairs.join("; "))
+    }
+}
+
+/// Raw HTTP response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    /// Header pairs, the same name may appear multiple times (e.g. `Set-Cookie`)
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+}
+
+impl Response {
+    /// First header value of name (case-insensitive)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Cookie value set by `Set-Cookie` headers
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case("set-cookie"))
+            .filter_map(|(_, v)| v.split(';').next()?.split_once('='))
+            .find(|(k, _)| k.trim() == name)
+            .map(|(_, v)| v.trim())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.status)
+    }
+
+    /// Check response status code.
+    pub fn check_status(&self) -> Result<()> {
+        if !self.is_success() {
+            return Err(Error::Http {
+                status: self.status,
+                body: self.body.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Deserialize JSON body
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        match serde_json::from_slice(self.body.as_ref()) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(Error::Deserialize(e, self.body.clone())),
+        }
+    }
+}
+
+/// Path of URL, host is ignored
+pub(crate) fn url_path(url: &str) -> &str {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None => url,
+    };
+
+    match rest.find('/') {
+        Some(i) => &rest[i..],
+        None => "/",
+    }
+}
+
+#[cfg(test)]
+fn json_response(body: &'static str) -> Response {
+    Response {
+        status: 200,
+        headers: vec![("Content-Type".into(), "application/json".into())],
+        body: Bytes::from_static(body.as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cookie() {
+        let response = Response {
+            status: 200,
+            headers: vec![
+                ("set-cookie".into(), "a=1; Path=/".into()),
+                (
+                    "Set-Cookie".into(),
+                    "shiroJID=abc-123; Path=/; HttpOnly".into(),
+                ),
+            ],
+            body: Bytes::new(),
+        };
+        assert_eq!(response.cookie("shiroJID"), Some("abc-123"));
+        assert_eq!(response.cookie("b"), None);
+
+        let request = Request::get("http://localhost".into())
+            .cookie("a", "1")
+            .cookie("b", "2");
+        assert_eq!(request.cookie_header().unwrap(), "a=1; b=2");
+    }
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(url_path("https://a.com/b/c"), "/b/c");
+        assert_eq!(url_path("http://127.0.0.1:80"), "/");
+    }
+
+    #[test]
+    fn test_check_status() {
+        let mut response = json_response("{}");
+        assert!(response.check_status().is_ok());
+        response.status = 502;
+        assert!(matches!(
+            response.check_status(),
+            Err(Error::Http { status: 502, .. })
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+//! JSON file backend
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use super::*;
+
+/// Content of the store file
+#[derive(Default, Serialize, Deserialize)]
+pub(super) struct StoreFile {
+    pub accounts: BTreeMap<String, Credentials>,
+}
+
+/// Credentials in a JSON file
+///
+/// The file is read on every access and replaced atomically on every write, with
+/// permission `0600` on Unix. A missing file is an empty store. Writes are serialized
+/// within the process only, so do not share the file between processes writing it.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    /// Held while reading and writing the file
+    lock: Mutex<()>,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<StoreFile> {
+        match std::fs::read(&self.path) {
+            Ok(content) => Ok(serde_json::from_slice(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(StoreFile::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write(&self, file: &StoreFile) -> Result<()> {
+        write_private(&self.path, &serde_json::to_vec_pretty(file)?)
+    }
+
+    /// Read, modify and write the file
+    fn modify<T>(&self, f: impl FnOnce(&mut StoreFile) -> T) -> Result<T> {
+        let _lock = self.lock.lock().unwrap();
+        let mut file = self.read()?;
+        let result = f(&mut file);
+        self.write(&file)?;
+
+        Ok(result)
+    }
+}
+
+impl CredentialStore for FileStore {
+    fn load(&self, uid: &str) -> Result<Option<Credentials>> {
+        let _lock = self.lock.lock().unwrap();
+        Ok(self.read()?.accounts.remove(uid))
+    }
+
+    fn save(&self, credentials: &Credentials) -> Result<()> {
+        self.modify(|file| {
+            file.accounts
+                .insert(credentials.uid.clone(), credentials.clone());
+        })
+    }
+
+    fn remove(&self, uid: &str) -> Result<bool> {
+        self.modify(|file| file.accounts.remove(uid).is_some())
+    }
+
+    fn accounts(&self) -> Result<Vec<String>> {
+        let _lock = self.lock.lock().unwrap();
+        Ok(self.read()?.accounts.into_keys().collect())
+    }
+
+    fn update(&self, uid: &str, f: &mut dyn FnMut(&mut Credentials)) -> Result<Credentials> {
+        self.modify(|file| {
+            let credentials = file
+                .accounts
+                .entry(uid.to_string())
+                .or_insert_with(|| Credentials::new(uid));
+            f(credentials);
+            credentials.updated_at = Utc::now();
+
+            credentials.clone()
+        })
+    }
+}
+
+/// Replace the file by `content` atomically, readable by the owner only on Unix
+///
+/// Written to a temporary file then renamed. The mode is set on every write,
+/// as a leftover temporary file keeps its own.
+pub(super) fn write_private(path: &Path, content: &[u8]) -> Result<()> {
+    let mut temp = path.to_path_buf().into_os_string();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut f = options.open(&temp)?;
+    #[cfg(unix)]
+    f.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    f.write_all(content)?;
+    f.sync_all()?;
+    std::fs::rename(&temp, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_file_store() {
+        let path = std::env::temp_dir().join(format!("yxy-store-{}.json", std::process::id()));
+        let store = FileStore::new(&path);
+        assert!(store.accounts().unwrap().is_empty());
+
+        let mut credentials = Credentials::new("uid");
+        credentials.campus_token = Some(Token::new("token"));
+        store.save(&credentials).unwrap();
+        store
+            .update("other", &mut |c| c.school_code = Some("school".into()))
+            .unwrap();
+
+        // Seen by another instance
+        let store = FileStore::new(&path);
+        assert_eq!(store.load("uid").unwrap(), Some(credentials));
+        assert_eq!(store.accounts().unwrap(), ["other", "uid"]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert!(store.remove("uid").unwrap());
+        assert_eq!(store.load("uid").unwrap(), None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_private_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("yxy-private-{}.json", std::process::id()));
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        // World-readable leftovers
+        for leftover in [path.as_os_str(), temp.as_os_str()] {
+            std::fs::write(leftover, b"{}").unwrap();
+            std::fs::set_permissions(leftover, std::fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        write_private(&path, b"{}").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+//! In-memory backend
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use super::*;
+
+/// Credentials kept in memory
+///
+/// Clones share the same accounts.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    accounts: Arc<Mutex<BTreeMap<String, Credentials>>>,
+}
+
+impl CredentialStore for MemoryStore {
+    fn load(&self, uid: &str) -> Result<Option<Credentials>> {
+        Ok(self.accounts.lock().unwrap().get(uid).cloned())
+    }
+
+    fn save(&self, credentials: &Credentials) -> Result<()> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(credentials.uid.clone(), credentials.clone());
+        Ok(())
+    }
+
+    fn remove(&self, uid: &str) -> Result<bool> {
+        Ok(self.accounts.lock().unwrap().remove(uid).is_some())
+    }
+
+    fn accounts(&self) -> Result<Vec<String>> {
+        Ok(self.accounts.lock().unwrap().keys().cloned().collect())
+    }
+
+    fn update(&self, uid: &str, f: &mut dyn FnMut(&mut Credentials)) -> Result<Credentials> {
+        let mut accounts = self.accounts.lock().unwrap();
+        let credentials = accounts
+            .entry(uid.to_string())
+            .or_insert_with(|| Credentials::new(uid));
+        f(credentials);
+        credentials.updated_at = Utc::now();
+
+        Ok(credentials.clone())
+    }
+}
@@ -0,0 +1,187 @@
+//! Persistent credentials of accounts
+//!
+//! A [`CredentialStore`] keeps the [`Credentials`] of each account by UID: the app session,
+//! the campus token with its device ID, and the school code. Handlers built from a store
+//! write refreshed values back to it:
+//!
+//! - [`AppSession::from_store`](crate::AppSession::from_store)
+//! - [`CampusHandler::from_store`](crate::bind::campus::CampusHandler::from_store)
+//!
+//! Backends:
+//!
+//! - [`FileStore`]: a JSON file, readable by other tools
+//! - [`MemoryStore`]: in memory, e.g. for tests or services with their own persistence
+//! - `VaultStore`: a file encrypted by a passphrase, enabled by the `vault` feature
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use yxy::store::FileStore;
+//!
+//! # async fn run() -> Result<(), yxy::error::Error> {
+//! let store = Arc::new(FileStore::new("credentials.json"));
+//! let app = yxy::AppSession::from_store(store, "your_user_id", Default::default())?;
+//!
+//! let bind_info = app.binding_info().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::bind::campus::login::LoginInfo;
+use crate::error::Error;
+
+mod file;
+mod memory;
+#[cfg(feature = "vault")]
+mod vault;
+
+pub use file::FileStore;
+pub use memory::MemoryStore;
+#[cfg(feature = "vault")]
+pub use vault::{KdfParams, VaultStore};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Storage of [`Credentials`] by UID
+///
+/// Implementations must be safe to share between handlers and threads.
+pub trait CredentialStore: Send + Sync {
+    /// Credentials of the account, `None` if not stored
+    fn load(&self, uid: &str) -> Result<Option<Credentials>>;
+
+    /// Insert or replace the credentials of [`Credentials::uid`]
+    fn save(&self, credentials: &Credentials) -> Result<()>;
+
+    /// Remove the account, returning whether it was stored
+    fn remove(&self, uid: &str) -> Result<bool>;
+
+    /// UIDs of the stored accounts
+    fn accounts(&self) -> Result<Vec<String>>;
+
+    /// Modify the credentials of the account, which are created if not stored
+    ///
+    /// [`Credentials::updated_at`] is set to now. Backends should override it
+    /// to make the load and save atomic.
+    fn update(&self, uid: &str, f: &mut dyn FnMut(&mut Credentials)) -> Result<Credentials> {
+        let mut credentials = self.load(uid)?.unwrap_or_else(|| Credentials::new(uid));
+        f(&mut credentials);
+        credentials.updated_at = Utc::now();
+
+        self.save(&credentials)?;
+        Ok(credentials)
+    }
+}
+
+/// Credentials of an account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credentials {
+    pub uid: String,
+    /// Device ID of the campus login, required by silent login
+    pub device_id: Option<String>,
+    pub school_code: Option<String>,
+    /// Session of the application API
+    pub app_session: Option<Token>,
+    /// Session token of the campus API
+    pub campus_token: Option<Token>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Credentials {
+    pub fn new(uid: &str) -> Self {
+        Self {
+            uid: uid.to_string(),
+            device_id: None,
+            school_code: None,
+            app_session: None,
+            campus_token: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Set the campus credentials of a login, keeping the app session
+    pub fn set_login(&mut self, info: &LoginInfo) {
+        self.device_id = Some(info.device_id.clone());
+        self.school_code.clone_from(&info.school_code);
+        self.campus_token = Some(Token::new(&info.token));
+    }
+
+    pub fn app_session(&self) -> Option<&str> {
+        self.app_session.as_ref().map(|v| v.value.as_str())
+    }
+
+    pub fn campus_token(&self) -> Option<&str> {
+        self.campus_token.as_ref().map(|v| v.value.as_str())
+    }
+}
+
+impl From<&LoginInfo> for Credentials {
+    /// Campus credentials of a login
+    fn from(info: &LoginInfo) -> Self {
+        let mut credentials = Self::new(&info.id);
+        credentials.set_login(info);
+        credentials
+    }
+}
+
+/// Secret value with the time it was issued
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token {
+    pub value: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+impl Token {
+    /// Issued now
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_string(),
+            issued_at: Utc::now(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Token {
+    /// The value is omitted
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Token")
+            .field("issued_at", &self.issued_at)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_store() {
+        let store = MemoryStore::default();
+        assert_eq!(store.load("uid").unwrap(), None);
+
+        let credentials = store
+            .update("uid", &mut |c| c.app_session = Some(Token::new("session")))
+            .unwrap();
+        assert_eq!(credentials.app_session(), Some("session"));
+        assert_eq!(store.load("uid").unwrap(), Some(credentials));
+
+        store
+            .update("uid", &mut |c| c.device_id = Some("device".into()))
+            .unwrap();
+        let credentials = store.load("uid").unwrap().unwrap();
+        assert_eq!(credentials.app_session(), Some("session"));
+        assert_eq!(credentials.device_id.as_deref(), Some("device"));
+
+        assert_eq!(store.accounts().unwrap(), ["uid"]);
+        assert!(store.remove("uid").unwrap());
+        assert!(!store.remove("uid").unwrap());
+        assert!(store.accounts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_token_debug() {
+        let debug = format!("{:?}", Token::new("secret"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -0,0 +1,575 @@
+//! Encrypted file backend
+//!
+//! The vault file is JSON of the key derivation parameters and the encrypted accounts:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "kdf": { "algorithm": "argon2id", "salt": "...", "memory_kib": 19456, "iterations": 2, "parallelism": 1 },
+//!   "cipher": "aes-256-gcm",
+//!   "nonce": "...",
+//!   "ciphertext": "..."
+//! }
+//! ```
+//!
+//! The key is derived from the passphrase by Argon2id, and the accounts are sealed by
+//! AES-256-GCM with a new nonce on every write. The header (`version`, `kdf` and `cipher`)
+//! is authenticated as the associated data, and the costs are bounded by [`KdfParams::MAX`].
+//! Binary fields are in standard base64.
+//!
+//! Version 1 vaults, whose header is not authenticated, are still unlocked and upgraded
+//! on the next write.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use rand::RngCore;
+use zeroize::{Zeroize, Zeroizing};
+
+use super::file::{write_private, StoreFile};
+use super::*;
+
+const VERSION: u32 = 2;
+/// Version with a fixed associated data instead of the header
+const LEGACY_VERSION: u32 = 1;
+const LEGACY_AAD: &[u8] = b"yxy-vault-v1";
+const KDF: &str = "argon2id";
+const CIPHER: &str = "aes-256-gcm";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+type Key = Zeroizing<[u8; 32]>;
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Upper bounds of each cost: 256 MiB, 16 iterations and 8 lanes
+    pub const MAX: Self = Self {
+        memory_kib: 256 * 1024,
+        iterations: 16,
+        parallelism: 8,
+    };
+
+    /// [`Error::Vault`] if any cost exceeds [`Self::MAX`]
+    fn check(&self) -> Result<()> {
+        let max = Self::MAX;
+        if self.memory_kib > max.memory_kib
+            || self.iterations > max.iterations
+            || self.parallelism > max.parallelism
+        {
+            return Err(Error::Vault(format!(
+                "Key derivation costs out of bounds: {:?}",
+                self
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    /// 19 MiB, 2 iterations, no parallelism
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Content of the vault file
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    #[serde(flatten)]
+    header: Header,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Authenticated as the associated data of the ciphertext
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    kdf: Kdf,
+    cipher: String,
+}
+
+impl Header {
+    fn aad(&self) -> Result<Vec<u8>> {
+        Ok(match self.version {
+            LEGACY_VERSION => LEGACY_AAD.to_vec(),
+            _ => serde_json::to_vec(self)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Kdf {
+    algorithm: String,
+    salt: String,
+    #[serde(flatten)]
+    params: KdfParams,
+}
+
+/// Decrypted state
+struct Unlocked {
+    key: Key,
+    salt: Vec<u8>,
+    params: KdfParams,
+    accounts: Accounts,
+}
+
+/// Decrypted accounts, of which the secrets are zeroized on drop
+#[derive(Clone, Default)]
+struct Accounts(BTreeMap<String, Credentials>);
+
+impl Drop for Accounts {
+    fn drop(&mut self) {
+        for credentials in self.0.values_mut() {
+            let tokens = [&mut credentials.app_session, &mut credentials.campus_token];
+            for token in tokens.into_iter().flatten() {
+                token.value.zeroize();
+            }
+            if let Some(ref mut device_id) = credentials.device_id {
+                device_id.zeroize();
+            }
+        }
+    }
+}
+
+/// [`StoreFile`] serialized without cloning the accounts
+#[derive(Serialize)]
+struct StoreFileRef<'a> {
+    accounts: &'a BTreeMap<String, Credentials>,
+}
+
+/// Credentials in a file encrypted by a passphrase
+///
+/// Opened locked, every access fails with [`Error::Vault`] until it is
+/// [unlocked](Self::unlock). The accounts are decrypted into memory by unlocking, and
+/// the file is rewritten on every change, so do not share the file between processes
+/// writing it.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use yxy::store::VaultStore;
+///
+/// # async fn run() -> Result<(), yxy::error::Error> {
+/// let vault = Arc::new(VaultStore::open("credentials.vault"));
+/// vault.unlock("passphrase")?;
+///
+/// let app = yxy::AppSession::from_store(vault.clone(), "your_user_id", Default::default())?;
+/// let bind_info = app.binding_info().await?;
+///
+/// vault.lock();
+/// # Ok(())
+/// # }
+/// ```
+pub struct VaultStore {
+    path: PathBuf,
+    state: Mutex<Option<Unlocked>>,
+}
+
+impl VaultStore {
+    /// Locked vault of the file
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Create an empty vault, which is unlocked
+    ///
+    /// Fails if the file exists.
+    pub fn create(path: impl Into<PathBuf>, passphrase: &str) -> Result<Self> {
+        Self::create_with_params(path, passphrase, KdfParams::default())
+    }
+
+    /// [`VaultStore::create`] with specific key derivation costs
+    pub fn create_with_params(
+        path: impl Into<PathBuf>,
+        passphrase: &str,
+        params: KdfParams,
+    ) -> Result<Self> {
+        let vault = Self::open(path);
+        let salt = random_bytes(SALT_LEN);
+        let unlocked = Unlocked {
+            key: derive_key(passphrase, &salt, &params)?,
+            salt,
+            params,
+            accounts: Accounts::default(),
+        };
+
+        // Claimed exclusively, then replaced by the written vault
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(&vault.path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(Error::Vault(format!(
+                    "{} already exists",
+                    vault.path.display()
+                )));
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if let Err(e) = vault.write(&unlocked, &unlocked.accounts) {
+            let _ = std::fs::remove_file(&vault.path);
+            return Err(e);
+        }
+        *vault.state.lock().unwrap() = Some(unlocked);
+
+        Ok(vault)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Decrypt the vault by the passphrase
+    pub fn unlock(&self, passphrase: &str) -> Result<()> {
+        let file: VaultFile = serde_json::from_slice(&std::fs::read(&self.path)?)?;
+        let header = &file.header;
+        if !matches!(header.version, VERSION | LEGACY_VERSION)
+            || header.kdf.algorithm != KDF
+            || header.cipher != CIPHER
+        {
+            return Err(Error::Vault(format!(
+                "Unsupported vault: version {}, {}, {}",
+                header.version, header.kdf.algorithm, header.cipher
+            )));
+        }
+
+        let salt = decode(&header.kdf.salt)?;
+        let nonce = decode(&file.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(Error::Vault("Malformed nonce".to_string()));
+        }
+
+        let key = derive_key(passphrase, &salt, &header.kdf.params)?;
+        let plaintext = Aes256Gcm::new((&*key).into())
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &decode(&file.ciphertext)?,
+                    aad: &header.aad()?,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| Error::Vault("Wrong passphrase or corrupted vault".to_string()))?;
+        let content: StoreFile = serde_json::from_slice(&plaintext)?;
+
+        *self.state.lock().unwrap() = Some(Unlocked {
+            key,
+            salt,
+            params: header.kdf.params,
+            accounts: Accounts(content.accounts),
+        });
+
+        Ok(())
+    }
+
+    /// Drop the key and the decrypted accounts from memory
+    pub fn lock(&self) {
+        self.state.lock().unwrap().take();
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.state.lock().unwrap().is_none()
+    }
+
+    /// Re-encrypt the vault by a new passphrase, with a new salt
+    pub fn change_passphrase(&self, passphrase: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let unlocked = state.as_mut().ok_or_else(locked)?;
+
+        let salt = random_bytes(SALT_LEN);
+        let changed = Unlocked {
+            key: derive_key(passphrase, &salt, &unlocked.params)?,
+            salt,
+            params: unlocked.params,
+            accounts: std::mem::take(&mut unlocked.accounts),
+        };
+        let result = self.write(&changed, &changed.accounts);
+        match result {
+            Ok(()) => *unlocked = changed,
+            Err(_) => unlocked.accounts = changed.accounts,
+        }
+
+        result
+    }
+
+    /// Encrypt `accounts` by the key of `unlocked` and write the file
+    fn write(&self, unlocked: &Unlocked, accounts: &Accounts) -> Result<()> {
+        let header = Header {
+            version: VERSION,
+            kdf: Kdf {
+                algorithm: KDF.to_string(),
+                salt: STANDARD.encode(&unlocked.salt),
+                params: unlocked.params,
+            },
+            cipher: CIPHER.to_string(),
+        };
+        let file = seal(header, &unlocked.key, accounts)?;
+
+        write_private(&self.path, &serde_json::to_vec_pretty(&file)?)
+    }
+
+    /// Read the accounts of the unlocked vault
+    fn read<T>(&self, f: impl FnOnce(&BTreeMap<String, Credentials>) -> T) -> Result<T> {
+        let state = self.state.lock().unwrap();
+        let unlocked = state.as_ref().ok_or_else(locked)?;
+
+        Ok(f(&unlocked.accounts.0))
+    }
+
+    /// Modify the accounts of the unlocked vault, kept unchanged if failed to write
+    fn modify<T>(&self, f: impl FnOnce(&mut BTreeMap<String, Credentials>) -> T) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+        let unlocked = state.as_mut().ok_or_else(locked)?;
+
+        let mut accounts = unlocked.accounts.clone();
+        let result = f(&mut accounts.0);
+        self.write(unlocked, &accounts)?;
+        unlocked.accounts = accounts;
+
+        Ok(result)
+    }
+}
+
+impl CredentialStore for VaultStore {
+    fn load(&self, uid: &str) -> Result<Option<Credentials>> {
+        self.read(|accounts| accounts.get(uid).cloned())
+    }
+
+    fn save(&self, credentials: &Credentials) -> Result<()> {
+        self.modify(|accounts| {
+            accounts.insert(credentials.uid.clone(), credentials.clone());
+        })
+    }
+
+    fn remove(&self, uid: &str) -> Result<bool> {
+        self.modify(|accounts| accounts.remove(uid).is_some())
+    }
+
+    fn accounts(&self) -> Result<Vec<String>> {
+        self.read(|accounts| accounts.keys().cloned().collect())
+    }
+
+    fn update(&self, uid: &str, f: &mut dyn FnMut(&mut Credentials)) -> Result<Credentials> {
+        self.modify(|accounts| {
+            let credentials = accounts
+                .entry(uid.to_string())
+                .or_insert_with(|| Credentials::new(uid));
+            f(credentials);
+            credentials.updated_at = Utc::now();
+
+            credentials.clone()
+        })
+    }
+}
+
+impl std::fmt::Debug for VaultStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultStore")
+            .field("path", &self.path)
+            .field("locked", &self.is_locked())
+            .finish()
+    }
+}
+
+fn locked() -> Error {
+    Error::Vault("Vault is locked".to_string())
+}
+
+/// Encrypt `accounts` by `key` under `header`
+fn seal(header: Header, key: &Key, accounts: &Accounts) -> Result<VaultFile> {
+    let plaintext = Zeroizing::new(serde_json::to_vec(&StoreFileRef {
+        accounts: &accounts.0,
+    })?);
+    let nonce = random_bytes(NONCE_LEN);
+    let ciphertext = Aes256Gcm::new((&**key).into())
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &header.aad()?,
+            },
+        )
+        .map_err(|_| Error::Vault("Encryption failed".to_string()))?;
+
+    Ok(VaultFile {
+        header,
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+/// Derive the key, [`Error::Vault`] if the costs are out of [`KdfParams::MAX`]
+fn derive_key(passphrase: &str, salt: &[u8], params: &KdfParams) -> Result<Key> {
+    params.check()?;
+    let vault_error = |e: argon2::Error| Error::Vault(e.to_string());
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(vault_error)?;
+
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+        .map_err(vault_error)?;
+
+    Ok(key)
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn decode(s: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(s)
+        .map_err(|e| Error::Vault(format!("Malformed vault: {}", e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Cheap costs for tests
+    const PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_vault() {
+        let path = std::env::temp_dir().join(format!("yxy-vault-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let vault = VaultStore::create_with_params(&path, "passphrase", PARAMS).unwrap();
+        let mut credentials = Credentials::new("uid");
+        credentials.campus_token = Some(Token::new("secret-token"));
+        vault.save(&credentials).unwrap();
+
+        assert!(VaultStore::create(&path, "passphrase").is_err());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("secret-token"));
+        assert!(!content.contains("uid"));
+
+        // Reopened locked
+        let vault = VaultStore::open(&path);
+        assert!(vault.is_locked());
+        assert!(matches!(vault.load("uid"), Err(Error::Vault(_))));
+        assert!(matches!(vault.unlock("wrong"), Err(Error::Vault(_))));
+
+        vault.unlock("passphrase").unwrap();
+        assert_eq!(vault.load("uid").unwrap(), Some(credentials.clone()));
+
+        vault.change_passphrase("new").unwrap();
+        vault.lock();
+        assert!(vault.accounts().is_err());
+        assert!(vault.unlock("passphrase").is_err());
+        vault.unlock("new").unwrap();
+        assert_eq!(vault.accounts().unwrap(), ["uid"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("yxy-{}-{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Edit the JSON of the vault file
+    fn tamper(path: &Path, f: impl FnOnce(&mut serde_json::Value)) {
+        let mut value: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        f(&mut value);
+        std::fs::write(path, serde_json::to_vec(&value).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_header() {
+        let path = temp_path("vault-header");
+        VaultStore::create_with_params(&path, "passphrase", PARAMS).unwrap();
+
+        // Authenticated, so a cheaper cost does not pass
+        tamper(&path, |v| v["kdf"]["iterations"] = 2.into());
+        let vault = VaultStore::open(&path);
+        assert!(matches!(vault.unlock("passphrase"), Err(Error::Vault(_))));
+
+        // Rejected before deriving
+        tamper(&path, |v| v["kdf"]["memory_kib"] = u32::MAX.into());
+        assert!(matches!(vault.unlock("passphrase"), Err(Error::Vault(m)) if m.contains("bounds")));
+
+        let params = KdfParams {
+            parallelism: 64,
+            ..PARAMS
+        };
+        let other = temp_path("vault-params");
+        assert!(VaultStore::create_with_params(&other, "passphrase", params).is_err());
+        assert!(!other.exists());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_vault() {
+        let path = temp_path("vault-legacy");
+        let salt = random_bytes(SALT_LEN);
+        let key = derive_key("passphrase", &salt, &PARAMS).unwrap();
+        let mut accounts = Accounts::default();
+        accounts.0.insert("uid".into(), Credentials::new("uid"));
+        let header = Header {
+            version: LEGACY_VERSION,
+            kdf: Kdf {
+                algorithm: KDF.to_string(),
+                salt: STANDARD.encode(&salt),
+                params: PARAMS,
+            },
+            cipher: CIPHER.to_string(),
+        };
+        let file = seal(header, &key, &accounts).unwrap();
+        std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+
+        let vault = VaultStore::open(&path);
+        vault.unlock("passphrase").unwrap();
+        assert_eq!(vault.accounts().unwrap(), ["uid"]);
+
+        // Upgraded on write
+        vault.remove("other").unwrap();
+        let content: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(content["version"], VERSION);
+        vault.lock();
+        vault.unlock("passphrase").unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
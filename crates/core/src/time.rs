@@ -0,0 +1,265 @@
+//! Timestamps and dates of the platform, in China Standard Time
+//!
+//! The platform sends local times of `UTC+8` without an offset, in several formats like
+//! `"2022-01-01 12:00:00"` or `"20220101120000"`. A [`Timestamp`] keeps the raw string
+//! with the parsed time, so a value in an unknown format is still available.
+//!
+//! ```
+//! use yxy::time::Timestamp;
+//!
+//! let time = Timestamp::parse("2022-01-01 12:00:00");
+//! assert_eq!(time.datetime().unwrap().to_rfc3339(), "2022-01-01T12:00:00+08:00");
+//! assert_eq!(time.date().unwrap().to_string(), "2022-01-01");
+//!
+//! let unknown = Timestamp::parse("01-01");
+//! assert_eq!(unknown.datetime(), None);
+//! assert_eq!(unknown.raw(), "01-01");
+//! ```
+
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Error;
+
+/// Formats of local date times sent by the platform
+const DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y%m%d%H%M%S",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Formats of local dates, parsed as the midnight
+const DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%Y%m%d"];
+
+/// China Standard Time, `UTC+8`
+pub fn cst() -> FixedOffset {
+    FixedOffset::east_opt(8 * 3600).unwrap()
+}
+
+/// Today in China Standard Time
+pub fn today() -> NaiveDate {
+    Utc::now().with_timezone(&cst()).date_naive()
+}
+
+/// Parse a date parameter, like `"2022-01-01"`, `"20220101"` or `"2022-01"`
+///
+/// A month is parsed as its first day.
+pub fn parse_date(s: &str) -> Result<NaiveDate, Error> {
+    let s = s.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(s, f).ok())
+        .or_else(|| NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d").ok())
+        .ok_or_else(|| Error::BadInput(format!("Invalid date: {:?}", s)))
+}
+
+/// Time sent by the platform, with the raw string
+///
+/// Compared by the parsed time, or by the raw string if not parsed. Unparsed values
+/// are ordered first.
+///
+/// Serialized as RFC 3339 if parsed, like `"2022-01-01T12:00:00+08:00"`,
+/// or as the raw string. Deserialized from a string, or from integer epoch seconds.
+#[derive(Debug, Clone)]
+pub struct Timestamp {
+    time: Option<DateTime<FixedOffset>>,
+    raw: String,
+}
+
+impl Timestamp {
+    /// Parse a local time of China Standard Time, or a RFC 3339 time converted to it
+    pub fn parse(raw: &str) -> Self {
+        let s = raw.trim();
+        let local = DATETIME_FORMATS
+            .iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+            .or_else(|| {
+                DATE_FORMATS
+                    .iter()
+                    .find_map(|f| NaiveDate::parse_from_str(s, f).ok())
+                    .map(|d| d.and_time(NaiveTime::MIN))
+            });
+
+        let time = match local {
+            Some(local) => cst().from_local_datetime(&local).single(),
+            None => DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|t| t.with_timezone(&cst())),
+        };
+
+        Self {
+            time,
+            raw: raw.to_string(),
+        }
+    }
+
+    /// String as sent by the platform
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Parsed time, `None` if in an unknown format
+    pub fn datetime(&self) -> Option<DateTime<FixedOffset>> {
+        self.time
+    }
+
+    /// Date in China Standard Time
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.time.map(|t| t.with_timezone(&cst()).date_naive())
+    }
+
+    pub fn is_parsed(&self) -> bool {
+        self.time.is_some()
+    }
+
+    fn key(&self) -> (Option<DateTime<FixedOffset>>, &str) {
+        match self.time {
+            Some(time) => (Some(time), ""),
+            None => (None, &self.raw),
+        }
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl std::hash::Hash for Timestamp {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for Timestamp {
+    fn from(time: DateTime<Tz>) -> Self {
+        let time = time.with_timezone(&cst());
+        Self {
+            raw: time.format(DATETIME_FORMATS[0]).to_string(),
+            time: Some(time),
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    /// The raw string
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.raw)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.time {
+            Some(time) => serializer.collect_str(&time.to_rfc3339()),
+            None => serializer.serialize_str(&self.raw),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a time string or epoch seconds")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Timestamp, E> {
+                Ok(Timestamp::parse(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Timestamp, E> {
+                DateTime::from_timestamp(v, 0)
+                    .map(Timestamp::from)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Timestamp, E> {
+                i64::try_from(v)
+                    .ok()
+                    .and_then(|v| DateTime::from_timestamp(v, 0))
+                    .map(Timestamp::from)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let expected = cst().with_ymd_and_hms(2022, 1, 1, 12, 0, 0).unwrap();
+        for raw in [
+            "2022-01-01 12:00:00",
+            "2022-01-01 12:00:00.000",
+            "20220101120000",
+            "2022-01-01T12:00:00+08:00",
+            "2022-01-01T04:00:00Z",
+        ] {
+            let time = Timestamp::parse(raw).datetime().unwrap();
+            assert_eq!(time, expected, "{}", raw);
+            assert_eq!(time.offset(), &cst(), "{}", raw);
+        }
+
+        let date = Timestamp::parse("2022-01-07");
+        assert_eq!(date.date(), NaiveDate::from_ymd_opt(2022, 1, 7));
+
+        let empty = Timestamp::parse("");
+        assert!(!empty.is_parsed());
+        assert!(empty < date);
+    }
+
+    #[test]
+    fn test_serde() {
+        let time: Timestamp = serde_json::from_str(r#""2022-01-01 12:00:00""#).unwrap();
+        assert_eq!(time.raw(), "2022-01-01 12:00:00");
+
+        let json = serde_json::to_string(&time).unwrap();
+        assert_eq!(json, r#""2022-01-01T12:00:00+08:00""#);
+        assert_eq!(serde_json::from_str::<Timestamp>(&json).unwrap(), time);
+
+        let unknown: Timestamp = serde_json::from_str(r#""01-01""#).unwrap();
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), r#""01-01""#);
+
+        let epoch: Timestamp = serde_json::from_str("1641009600").unwrap();
+        assert_eq!(epoch, time);
+        assert_eq!(epoch.raw(), "2022-01-01 12:00:00");
+        assert!(serde_json::from_str::<Timestamp>("true").is_err());
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let expected = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        for s in ["2022-01-01", "20220101", "2022-01"] {
+            assert_eq!(parse_date(s).unwrap(), expected);
+        }
+        assert!(parse_date("tomorrow").is_err());
+    }
+}
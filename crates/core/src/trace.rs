@@ -0,0 +1,197 @@
+//! Tracing instrumentation
+//!
+//! Enabled by the `tracing` cargo feature, no-op otherwise.
+//!
+//! Every call driven by the handlers runs in a `yxy.call` span with fields:
+//!
+//! - `call`: name of the [`Call`], e.g. `QueryBind`
+//! - `method`, `endpoint`: HTTP method and URL path
+//! - `attempts`: count of attempts, including retries
+//! - `http_status`, `status_code`: HTTP status and envelope `statusCode` of the last response
+//! - `latency_ms`: duration of the last attempt
+//!
+//! Requests are logged at `DEBUG` level, failures at `WARN` level. Session cookies,
+//! tokens, passwords and phone numbers are [scrubbed](crate::cassette::scrub) from
+//! them unless disabled by [`set_redaction`].
+
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::protocol::{Call, Request, Response};
+
+#[cfg(feature = "tracing")]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "tracing")]
+use crate::cassette::scrub;
+#[cfg(feature = "tracing")]
+use crate::protocol::{url_path, Envelope};
+
+#[cfg(feature = "tracing")]
+static REDACTION: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable redaction of secrets in traces, enabled by default
+///
+/// Only disable it for debugging on trusted machines.
+#[cfg(feature = "tracing")]
+pub fn set_redaction(enabled: bool) {
+    REDACTION.store(enabled, Ordering::Relaxed);
+}
+
+#[cfg(feature = "tracing")]
+fn redaction() -> bool {
+    REDACTION.load(Ordering::Relaxed)
+}
+
+/// Trace of one call, across its attempts
+pub(crate) struct CallTrace {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    /// Secret values of the request, empty if redaction is disabled
+    #[cfg(feature = "tracing")]
+    secrets: Vec<String>,
+}
+
+#[cfg(feature = "tracing")]
+impl CallTrace {
+    pub fn new<C: Call>(_call: &C, request: &Request) -> Self {
+        let name = std::any::type_name::<C>();
+        let name = name.split('<').next().unwrap_or(name);
+        let name = name.rsplit("::").next().unwrap_or(name);
+
+        let span = tracing::info_span!(
+            "yxy.call",
+            call = name,
+            method = ?request.method,
+            endpoint = url_path(&request.url),
+            attempts = tracing::field::Empty,
+            http_status = tracing::field::Empty,
+            status_code = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+
+        let secrets = if redaction() {
+            span.in_scope(|| tracing::debug!(request = ?scrub::request(request), "send"));
+            scrub::secrets(request)
+        } else {
+            span.in_scope(|| tracing::debug!(?request, "send"));
+            Vec::new()
+        };
+
+        Self { span, secrets }
+    }
+
+    pub fn span(&self) -> tracing::Span {
+        self.span.clone()
+    }
+
+    /// Record status of a received response
+    pub fn response(&self, response: &Response) {
+        self.span.record("http_status", response.status);
+
+        let envelope = serde_json::from_slice::<
+            Envelope<serde::de::IgnoredAny, serde::de::IgnoredAny>,
+        >(&response.body);
+        if let Ok(envelope) = envelope {
+            self.span.record("status_code", envelope.status_code);
+        }
+    }
+
+    /// Record the result of an attempt
+    pub fn attempt<T>(&self, attempt: u32, elapsed: Duration, result: &Result<T, Error>) {
+        let latency_ms = elapsed.as_millis() as u64;
+        self.span.record("attempts", attempt);
+        self.span.record("latency_ms", latency_ms);
+
+        let _enter = self.span.enter();
+        match result {
+            Ok(_) => tracing::debug!(attempt, latency_ms, "done"),
+            Err(e) => {
+                let error = match redaction() {
+                    true => redact(e, &self.secrets),
+                    false => e.to_string(),
+                };
+                tracing::warn!(attempt, latency_ms, %error, "failed")
+            }
+        }
+    }
+}
+
+/// Error message without secrets, response bodies are omitted
+#[cfg(feature = "tracing")]
+fn redact(error: &Error, secrets: &[String]) -> String {
+    scrub::text(&brief(error), secrets)
+}
+
+/// Error message with response bodies omitted
+pub(crate) fn brief(error: &Error) -> String {
+    match error {
+        Error::Deserialize(e, _) => format!("JSON deserialize Error: {}", e),
+        Error::Http { status, .. } => format!("Bad response: ({})", status),
+        e => e.to_string(),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl CallTrace {
+    pub fn new<C: Call>(_call: &C, _request: &Request) -> Self {
+        Self {}
+    }
+
+    pub fn response(&self, _response: &Response) {}
+
+    pub fn attempt<T>(&self, _attempt: u32, _elapsed: Duration, _result: &Result<T, Error>) {}
+}
+
+/// Report a failed write-back to a [`CredentialStore`](crate::store::CredentialStore),
+/// which does not fail the call
+pub(crate) fn store_failed(error: &Error) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%error, "failed to write back credentials");
+    #[cfg(not(feature = "tracing"))]
+    let _ = error;
+}
+
+/// Report a transient failure while polling, which is retried on the next poll
+///
+/// Response bodies are omitted unless redaction is disabled.
+pub(crate) fn poll_failed(error: &Error) {
+    #[cfg(feature = "tracing")]
+    {
+        let error = match redaction() {
+            true => brief(error),
+            false => error.to_string(),
+        };
+        tracing::warn!(%error, "poll failed, retrying");
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = error;
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let request = Request::post("https://compus.xiaofubao.com/login/doLoginByPwd".into())
+            .field("mobilePhone", "18888888888")
+            .field("password", "encrypted-password");
+        let secrets = scrub::secrets(&request);
+
+        let error = Error::BadInput("encrypted-password".into());
+        assert_eq!(redact(&error, &secrets), "Bad Input: [scrubbed]");
+
+        let error = Error::Api {
+            endpoint: "/login/doLoginByPwd".into(),
+            status_code: 500,
+            biz_code: None,
+            message: "18888888888".into(),
+        };
+        assert!(!redact(&error, &secrets).contains("18888888888"));
+
+        let body = r#"{"data":{"token":"secret"}}"#;
+        let error = Error::Deserialize(serde_json::from_str::<u8>(body).unwrap_err(), body.into());
+        assert!(!redact(&error, &secrets).contains("secret"));
+    }
+}
@@ -0,0 +1,474 @@
+//! Exact quantities of money and electric energy
+//!
+//! The platform sends amounts as floats, decimal strings or integer fen, which do not
+//! add up exactly. [`Money`] keeps an amount in integer fen and [`KilowattHours`] keeps
+//! energy in integer W·h, so sums of records are exact.
+//!
+//! Both are serialized as decimal strings in their major unit, e.g. `"20.01"` yuan and
+//! `"3.50"` kW·h. Deserializing accepts decimal strings and numbers in the major unit;
+//! fields which the platform sends in integer fen use [`fen`].
+//!
+//! ```
+//! use yxy::units::{KilowattHours, Money};
+//!
+//! let balance: Money = "20.01".parse().unwrap();
+//! let total: Money = [balance, Money::from_yuan(0.1), Money::from_fen(-1)].into_iter().sum();
+//! assert_eq!(total, Money::from_fen(2010));
+//! assert_eq!(total.to_string(), "20.10");
+//! assert_eq!(total.fmt_yuan(), "￥20.10");
+//!
+//! let used = KilowattHours::from_kwh(3.5);
+//! assert_eq!(used.wh(), 3500);
+//! assert_eq!(used.fmt_kwh(), "3.50 kW·h");
+//! ```
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Error;
+
+/// Amount of money in fen (0.01 yuan)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_fen(fen: i64) -> Self {
+        Self(fen)
+    }
+
+    /// Rounded to the nearest fen
+    pub fn from_yuan(yuan: f64) -> Self {
+        Self((yuan * 100.0).round() as i64)
+    }
+
+    pub const fn fen(self) -> i64 {
+        self.0
+    }
+
+    /// Approximate, for display or FFI only
+    pub fn yuan(self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub const fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
+    /// Formatted with the currency sign, like `"￥20.01"` or `"-￥10.00"`
+    pub fn fmt_yuan(self) -> String {
+        let sign = if self.is_negative() { "-" } else { "" };
+        format!("{}￥{}", sign, self.abs())
+    }
+}
+
+/// Electric energy in W·h (0.001 kW·h)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KilowattHours(i64);
+
+impl KilowattHours {
+    pub const ZERO: Self = Self(0);
+
+    pub const fn from_wh(wh: i64) -> Self {
+        Self(wh)
+    }
+
+    /// Rounded to the nearest W·h
+    pub fn from_kwh(kwh: f64) -> Self {
+        Self((kwh * 1000.0).round() as i64)
+    }
+
+    pub const fn wh(self) -> i64 {
+        self.0
+    }
+
+    /// Approximate, for display, comparison with thresholds or FFI
+    pub fn kwh(self) -> f64 {
+        self.0 as f64 / 1000.0
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Formatted with the unit, like `"3.50 kW·h"`
+    pub fn fmt_kwh(self) -> String {
+        format!("{} kW·h", self)
+    }
+}
+
+/// Implement arithmetic, parsing, formatting and serde of a fixed point quantity
+macro_rules! quantity {
+    ($ty:ident, $scale:expr, $what:expr) => {
+        impl $ty {
+            /// Minor units of one major unit
+            const ONE: i64 = 10_i64.pow($scale);
+        }
+
+        impl Add for $ty {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $ty {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl Neg for $ty {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl AddAssign for $ty {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl SubAssign for $ty {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Sum for $ty {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                Self(iter.map(|v| v.0).sum())
+            }
+        }
+
+        impl<'a> Sum<&'a $ty> for $ty {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                Self(iter.map(|v| v.0).sum())
+            }
+        }
+
+        impl fmt::Display for $ty {
+            /// Decimal in the major unit, with 2 digits at least or the given precision
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                pad(f, &format_decimal(self.0, $scale, f.precision()))
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = Error;
+
+            /// Decimal in the major unit, like `"20.01"` or `"-3.5"`
+            fn from_str(s: &str) -> Result<Self, Error> {
+                parse_decimal(s, $scale)
+                    .map(Self)
+                    .ok_or_else(|| Error::BadInput(format!("Invalid {}: {:?}", $what, s)))
+            }
+        }
+
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer
+                    .deserialize_any(Visitor::new($what, $scale, |v| v.checked_mul(<$ty>::ONE)))
+                    .map(Self)
+            }
+        }
+    };
+}
+
+quantity!(Money, 2, "amount of money");
+quantity!(KilowattHours, 3, "energy");
+
+/// Deserialize [`Money`] sent in integer fen
+///
+/// Integers are fen, whether numbers or strings like `"1000"`. Strings with a decimal
+/// point are yuan, so the serialized output is accepted too.
+///
+/// ```
+/// # use serde::Deserialize;
+/// # use yxy::units::Money;
+/// #[derive(Deserialize)]
+/// struct Detail {
+///     #[serde(with = "yxy::units::fen")]
+///     tran_money: Money,
+/// }
+///
+/// let detail: Detail = serde_json::from_str(r#"{"tran_money":1000}"#).unwrap();
+/// assert_eq!(detail.tran_money.to_string(), "10.00");
+/// let detail: Detail = serde_json::from_str(r#"{"tran_money":"10.00"}"#).unwrap();
+/// assert_eq!(detail.tran_money.to_string(), "10.00");
+/// ```
+pub mod fen {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Money, serializer: S) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Money, D::Error> {
+        deserializer
+            .deserialize_any(Visitor::new("amount of money in fen", 2, Some))
+            .map(Money::from_fen)
+    }
+}
+
+/// Visitor of a quantity in minor units
+///
+/// Floats and strings with a decimal point are in the major unit. Integers, numbers or
+/// strings, are converted by `integer`.
+struct Visitor {
+    what: &'static str,
+    scale: u32,
+    integer: fn(i64) -> Option<i64>,
+}
+
+impl Visitor {
+    fn new(what: &'static str, scale: u32, integer: fn(i64) -> Option<i64>) -> Self {
+        Self {
+            what,
+            scale,
+            integer,
+        }
+    }
+}
+
+impl de::Visitor<'_> for Visitor {
+    type Value = i64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} as a decimal string or number", self.what)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<i64, E> {
+        let value = match v.contains('.') {
+            true => parse_decimal(v, self.scale),
+            false => v.trim().parse().ok().and_then(self.integer),
+        };
+        value.ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<i64, E> {
+        let minor = (v * 10_f64.powi(self.scale as i32)).round();
+        if minor.is_finite() && minor.abs() < i64::MAX as f64 {
+            Ok(minor as i64)
+        } else {
+            Err(E::invalid_value(de::Unexpected::Float(v), &self))
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<i64, E> {
+        (self.integer)(v).ok_or_else(|| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<i64, E> {
+        i64::try_from(v)
+            .ok()
+            .and_then(self.integer)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+}
+
+/// Write `s` with the width and alignment of `f`, right aligned by default
+///
+/// Unlike [`fmt::Formatter::pad`], the precision is not applied to `s`.
+fn pad(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    let width = f.width().unwrap_or(0);
+    match f.align() {
+        Some(fmt::Alignment::Left) => write!(f, "{:<width$}", s),
+        Some(fmt::Alignment::Center) => write!(f, "{:^width$}", s),
+        _ => write!(f, "{:>width$}", s),
+    }
+}
+
+/// Parse a decimal string to minor units, rounding the extra digits half away from zero
+fn parse_decimal(s: &str, scale: u32) -> Option<i64> {
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    if !int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut value: i64 = 0;
+    for b in int.bytes() {
+        value = value.checked_mul(10)?.checked_add((b - b'0') as i64)?;
+    }
+    let mut digits = frac.bytes();
+    for _ in 0..scale {
+        let digit = digits.next().map_or(0, |b| (b - b'0') as i64);
+        value = value.checked_mul(10)?.checked_add(digit)?;
+    }
+    if digits.next().is_some_and(|b| b >= b'5') {
+        value = value.checked_add(1)?;
+    }
+
+    Some(if negative { -value } else { value })
+}
+
+/// Format minor units as a decimal, with trailing zeros trimmed to 2 digits
+///
+/// With `precision`, rounded half away from zero to that many digits.
+fn format_decimal(value: i64, scale: u32, precision: Option<usize>) -> String {
+    let one = 10_u64.pow(scale);
+    let abs = value.unsigned_abs();
+    let (mut int, mut frac) = (abs / one, abs % one);
+    let mut digits = scale as usize;
+
+    if let Some(precision) = precision.filter(|&p| p < digits) {
+        let unit = 10_u64.pow((digits - precision) as u32);
+        frac = (frac + unit / 2) / unit;
+        if frac == 10_u64.pow(precision as u32) {
+            int += 1;
+            frac = 0;
+        }
+        digits = precision;
+    }
+
+    let mut frac = match digits {
+        0 => String::new(),
+        _ => format!("{:0width$}", frac, width = digits),
+    };
+    match precision {
+        Some(precision) => {
+            while frac.len() < precision {
+                frac.push('0');
+            }
+        }
+        None => {
+            while frac.len() > 2 && frac.ends_with('0') {
+                frac.pop();
+            }
+        }
+    }
+
+    let sign = if value < 0 && (int != 0 || frac.bytes().any(|b| b != b'0')) {
+        "-"
+    } else {
+        ""
+    };
+    match frac.is_empty() {
+        true => format!("{}{}", sign, int),
+        false => format!("{}{}.{}", sign, int, frac),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("20.01".parse::<Money>().unwrap(), Money::from_fen(2001));
+        assert_eq!("-10".parse::<Money>().unwrap(), Money::from_fen(-1000));
+        assert_eq!(".5".parse::<Money>().unwrap(), Money::from_fen(50));
+        assert_eq!("0.005".parse::<Money>().unwrap(), Money::from_fen(1));
+        assert_eq!("3.5".parse::<KilowattHours>().unwrap().wh(), 3500);
+        assert!("".parse::<Money>().is_err());
+        assert!("1.2.3".parse::<Money>().is_err());
+        assert!("￥1".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Money::from_fen(2001).to_string(), "20.01");
+        assert_eq!(Money::from_fen(-5).to_string(), "-0.05");
+        assert_eq!(Money::from_fen(-1000).fmt_yuan(), "-￥10.00");
+        assert_eq!(format!("{:.0}", Money::from_fen(250)), "3");
+        assert_eq!(
+            format!("{:>7.1}|{:<6}|", Money::from_fen(250), Money::ZERO),
+            "    2.5|0.00  |"
+        );
+        assert_eq!(KilowattHours::from_wh(3500).to_string(), "3.50");
+        assert_eq!(KilowattHours::from_wh(1125).to_string(), "1.125");
+        assert_eq!(format!("{:.2}", KilowattHours::from_wh(1125)), "1.13");
+        assert_eq!(format!("{:.1}", KilowattHours::from_wh(-999)), "-1.0");
+        assert_eq!(format!("{:.1}", KilowattHours::from_wh(-10)), "0.0");
+    }
+
+    #[test]
+    fn test_sum() {
+        // 0.1 + 0.2 in floats is not 0.3
+        let sum: Money = [0.1, 0.2].iter().map(|&v| Money::from_yuan(v)).sum();
+        assert_eq!(sum, "0.3".parse().unwrap());
+    }
+
+    #[test]
+    fn test_serde() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Record {
+            money: Money,
+            #[serde(with = "fen")]
+            tran_money: Money,
+            used: KilowattHours,
+        }
+
+        let wire: Record =
+            serde_json::from_str(r#"{"money":45.38,"tran_money":1000,"used":"3.5"}"#).unwrap();
+        assert_eq!(wire.money, Money::from_fen(4538));
+        assert_eq!(wire.tran_money, Money::from_fen(1000));
+        assert_eq!(wire.used, KilowattHours::from_wh(3500));
+
+        let json = serde_json::to_string(&wire).unwrap();
+        assert_eq!(
+            json,
+            r#"{"money":"45.38","tran_money":"10.00","used":"3.50"}"#
+        );
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), wire);
+
+        // Integers out of the fen field are in yuan
+        let money: Money = serde_json::from_str("10").unwrap();
+        assert_eq!(money.fen(), 1000);
+        let money: Money = serde_json::from_str(r#""10""#).unwrap();
+        assert_eq!(money.fen(), 1000);
+    }
+
+    #[test]
+    fn test_fen_string() {
+        #[derive(Deserialize)]
+        struct Detail {
+            #[serde(with = "fen")]
+            tran_money: Money,
+        }
+
+        let parse = |json| serde_json::from_str::<Detail>(json).map(|d| d.tran_money);
+        assert_eq!(
+            parse(r#"{"tran_money":"1000"}"#).unwrap(),
+            Money::from_fen(1000)
+        );
+        assert_eq!(
+            parse(r#"{"tran_money":"-5"}"#).unwrap(),
+            Money::from_fen(-5)
+        );
+        assert_eq!(
+            parse(r#"{"tran_money":"10.5"}"#).unwrap(),
+            Money::from_fen(1050)
+        );
+        assert!(parse(r#"{"tran_money":"10元"}"#).is_err());
+    }
+}
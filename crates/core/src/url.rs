@@ -1,5 +1,7 @@
 //! API URLs
 
+use std::collections::HashMap;
+
 use const_format::concatcp as cc;
 
 pub mod auth {
@@ -37,6 +39,21 @@ pub mod application {
             cc!(BASE_URL, "/app/electric/queryISIMSRoomBuyRecord");
 
         pub const RECHARGE: &str = cc!(BASE_URL, "/app/electric/recharge.htm");
+
+        pub const BIND_ROOM: &str = cc!(BASE_URL, "/app/electric/saveBind");
+
+        pub const UPDATE_BIND: &str = cc!(BASE_URL, "/app/electric/updateBind");
+
+        pub const DELETE_BIND: &str = cc!(BASE_URL, "/app/electric/delBind");
+
+        /// Room directory, each level listed by the codes of the upper ones
+        pub const QUERY_AREAS: &str = cc!(BASE_URL, "/app/electric/queryArea");
+
+        pub const QUERY_BUILDINGS: &str = cc!(BASE_URL, "/app/electric/queryBuilding");
+
+        pub const QUERY_FLOORS: &str = cc!(BASE_URL, "/app/electric/queryFloor");
+
+        pub const QUERY_ROOMS: &str = cc!(BASE_URL, "/app/electric/queryRoom");
     }
 
     pub const GET_SUBMIT_TOKEN: &str = cc!(BASE_URL, "/center/common/token/get.htm");
@@ -88,3 +105,134 @@ pub mod pay {
 
     pub const TO_CASHIER: &str = cc!(BASE_URL, "/pay/unified/toCashier.shtml");
 }
+
+/// Runtime-configurable set of API hosts
+///
+/// Defaults to the official hosts above. Every handler resolves its request
+/// URLs through [`Endpoints::resolve`], so the whole API can be pointed at a
+/// staging host, a reverse proxy or a local fake server.
+///
+/// ```
+/// use yxy::url::Endpoints;
+///
+/// let endpoints = Endpoints::with_base("http://127.0.0.1:8080")
+///     .override_path("/app/electric/queryBind", "http://10.0.0.1/queryBind");
+///
+/// assert_eq!(
+///     endpoints.resolve(yxy::url::application::electricity::QUERY_SURPLUS),
+///     "http://127.0.0.1:8080/app/electric/queryISIMSRoomSurplus"
+/// );
+/// assert_eq!(
+///     endpoints.resolve(yxy::url::application::electricity::QUERY_BIND),
+///     "http://10.0.0.1/queryBind"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Base URL of [`auth`] APIs
+    pub auth: String,
+    /// Base URL of [`application`] APIs
+    pub application: String,
+    /// Base URL of [`campus`] APIs
+    pub campus: String,
+    /// Base URL of [`pay`] pages
+    pub pay: String,
+    /// Full URL overrides keyed by API path, e.g. `/app/electric/queryBind`
+    pub overrides: HashMap<String, String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            auth: auth::BASE_URL.to_string(),
+            application: application::BASE_URL.to_string(),
+            campus: campus::BASE_URL.to_string(),
+            pay: pay::BASE_URL.to_string(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Serve every API from the same base URL
+    pub fn with_base(base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        Self {
+            auth: base.to_string(),
+            application: base.to_string(),
+            campus: base.to_string(),
+            pay: base.to_string(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Replace the full URL of a single API path
+    pub fn override_path(mut self, path: &str, url: &str) -> Self {
+        self.overrides.insert(path.to_string(), url.to_string());
+        self
+    }
+
+    /// Map a default API URL (one of the constants in this module) onto this endpoint set
+    ///
+    /// URLs outside of the default hosts are returned unchanged.
+    pub fn resolve(&self, url: &str) -> String {
+        let hosts = [
+            (auth::BASE_URL, &self.auth),
+            (application::BASE_URL, &self.application),
+            (campus::BASE_URL, &self.campus),
+            (pay::BASE_URL, &self.pay),
+        ];
+
+        for (default, base) in hosts {
+            if let Some(path) = url.strip_prefix(default) {
+                if let Some(v) = self.overrides.get(path) {
+                    return v.clone();
+                }
+                return format!("{}{}", base.trim_end_matches('/'), path);
+            }
+        }
+
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_default() {
+        let endpoints = Endpoints::default();
+        assert_eq!(endpoints.resolve(auth::OAUTH_URL), auth::OAUTH_URL);
+        assert_eq!(
+            endpoints.resolve(campus::user::QUERY_CARD_BALANCE),
+            campus::user::QUERY_CARD_BALANCE
+        );
+    }
+
+    #[test]
+    fn test_resolve_custom() {
+        let endpoints = Endpoints {
+            campus: "http://localhost:3000/compus/".to_string(),
+            ..Default::default()
+        }
+        .override_path("/authoriz/getCodeV2", "http://localhost:3001/oauth");
+
+        assert_eq!(
+            endpoints.resolve(campus::login::GET_SECURITY_TOKEN),
+            "http://localhost:3000/compus/common/security/token"
+        );
+        assert_eq!(
+            endpoints.resolve(auth::OAUTH_URL),
+            "http://localhost:3001/oauth"
+        );
+        assert_eq!(
+            endpoints.resolve(pay::TO_CASHIER),
+            "https://pay.xiaofubao.com/pay/unified/toCashier.shtml"
+        );
+        assert_eq!(
+            endpoints.resolve("http://example.com/a"),
+            "http://example.com/a"
+        );
+    }
+}
@@ -1,8 +1,17 @@
 //! Wrappers of API procedures
+//!
+//! Every wrapper has a `*_with_config` variant accepting [`HandlerConfig`].
 
+use crate::bind::HandlerConfig;
 use crate::bind::*;
 use crate::error::Error;
 
+/// Binding with the electricity of its room, see [`query_ele_all`]
+pub type BoundSurplus = (
+    app::electricity::BindInfo,
+    Result<app::electricity::SurplusInfo, Error>,
+);
+
 /// Authorize
 ///
 /// Wrapper of Authorization procedure.
@@ -11,12 +20,20 @@ use crate::error::Error;
 ///
 /// Returns a tuple of (Session Token, [`app::auth::UserInfo`])
 pub async fn app_auth(uid: &str) -> Result<(String, app::auth::UserInfo), Error> {
+    app_auth_with_config(uid, HandlerConfig::default()).await
+}
+
+/// [`app_auth`] with specific [`HandlerConfig`]
+pub async fn app_auth_with_config(
+    uid: &str,
+    config: HandlerConfig,
+) -> Result<(String, app::auth::UserInfo), Error> {
     // Init non-redirect client to catch redirect response
-    let client = build_non_redirect_client()?;
+    let client = build_non_redirect_client(&config)?;
 
-    let oauth_code = app::auth::get_oauth_code(&client, uid).await?;
+    let oauth_code = app::auth::get_oauth_code(&client, &config, uid).await?;
 
-    let (ses, user) = app::auth::authorize(&client, &oauth_code).await?;
+    let (ses, user) = app::auth::authorize(&client, &config, &oauth_code).await?;
 
     Ok((ses, user))
 }
@@ -27,13 +44,38 @@ pub async fn app_auth(uid: &str) -> Result<(String, app::auth::UserInfo), Error>
 ///
 /// If no binding info, return Err([`Error::NoBind`])
 pub async fn query_ele_bind(session: &str) -> Result<app::electricity::BindInfo, Error> {
-    let handler = app::AppHandler::build(session)?;
+    query_ele_bind_with_config(session, HandlerConfig::default()).await
+}
+
+/// [`query_ele_bind`] with specific [`HandlerConfig`]
+pub async fn query_ele_bind_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<app::electricity::BindInfo, Error> {
+    let handler = app::AppHandler::build_with_config(session, config)?;
 
     let bind = handler.binding_info().await?;
 
     Ok(bind)
 }
 
+/// Query all electricity bindings
+///
+/// Empty if no binding info.
+pub async fn query_ele_binds(session: &str) -> Result<Vec<app::electricity::BindInfo>, Error> {
+    query_ele_binds_with_config(session, HandlerConfig::default()).await
+}
+
+/// [`query_ele_binds`] with specific [`HandlerConfig`]
+pub async fn query_ele_binds_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<Vec<app::electricity::BindInfo>, Error> {
+    let handler = app::AppHandler::build_with_config(session, config)?;
+
+    handler.bindings().await
+}
+
 /// Query electricity
 ///
 /// Wrapper of query electricity procedure.
@@ -42,8 +84,16 @@ pub async fn query_ele_bind(session: &str) -> Result<app::electricity::BindInfo,
 ///
 /// If no binding info, return Err([`Error::NoBind`])
 pub async fn query_ele(session: &str) -> Result<app::electricity::SurplusInfo, Error> {
+    query_ele_with_config(session, HandlerConfig::default()).await
+}
+
+/// [`query_ele`] with specific [`HandlerConfig`]
+pub async fn query_ele_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<app::electricity::SurplusInfo, Error> {
     // Init authorized handler
-    let handler = app::AppHandler::build(session)?;
+    let handler = app::AppHandler::build_with_config(session, config)?;
 
     // Query Bind Info
     let bind_info = handler.binding_info().await?;
@@ -58,9 +108,41 @@ pub async fn query_ele(session: &str) -> Result<app::electricity::SurplusInfo, E
 pub async fn query_ele_by_room_info(
     session: &str,
     room_info: &app::electricity::RoomInfo,
+) -> Result<app::electricity::SurplusInfo, Error> {
+    query_ele_by_room_info_with_config(session, room_info, HandlerConfig::default()).await
+}
+
+/// [`query_ele_by_room_info`] with specific [`HandlerConfig`]
+pub async fn query_ele_by_room_info_with_config(
+    session: &str,
+    room_info: &app::electricity::RoomInfo,
+    config: HandlerConfig,
 ) -> Result<app::electricity::SurplusInfo, Error> {
     // Init authorized handler
-    let handler = app::AppHandler::build(session)?;
+    let handler = app::AppHandler::build_with_config(session, config)?;
 
     handler.surplus(room_info).await
 }
+
+/// Query electricity of every bound room
+///
+/// Rooms are queried by [`bulk::BulkOptions::default`], each with its own result.
+pub async fn query_ele_all(session: &str) -> Result<Vec<BoundSurplus>, Error> {
+    query_ele_all_with_config(session, HandlerConfig::default()).await
+}
+
+/// [`query_ele_all`] with specific [`HandlerConfig`]
+pub async fn query_ele_all_with_config(
+    session: &str,
+    config: HandlerConfig,
+) -> Result<Vec<BoundSurplus>, Error> {
+    let handler = app::AppHandler::build_with_config(session, config)?;
+
+    let binds = handler.bindings().await?;
+    let rooms: Vec<app::electricity::RoomInfo> = binds.iter().map(Into::into).collect();
+    let results = handler
+        .surplus_many(&rooms, &bulk::BulkOptions::default())
+        .await;
+
+    Ok(binds.into_iter().zip(results).collect())
+}
@@ -0,0 +1,107 @@
+//! Tests of the blocking facade against the local emulation server
+#![cfg(feature = "blocking")]
+
+use yxy::blocking::bind::app::order::{OrderStatus, TrackOptions};
+use yxy::blocking::{AppHandler, CampusHandler, LoginHandler, RoomInfo};
+use yxy::error::Error;
+use yxy::{Endpoints, HandlerConfig, KilowattHours};
+use yxy_mock::MockServer;
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+/// Run the server on its own runtime, as blocking calls must not be made in an async context
+fn start() -> (tokio::runtime::Runtime, MockServer) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(MockServer::start()).unwrap();
+
+    (runtime, server)
+}
+
+fn config(server: &MockServer) -> HandlerConfig {
+    Endpoints::with_base(server.url()).into()
+}
+
+#[test]
+fn app() -> Result {
+    let (_runtime, server) = start();
+    let s = server.scenario();
+
+    let handler = AppHandler::build_by_uid_with_config(&s.uid, config(&server))?;
+    assert_eq!(handler.session(), s.session);
+    assert_eq!(handler.user_info()?.id, s.uid);
+
+    let room = RoomInfo::from(handler.binding_info()?);
+    assert_eq!(handler.bindings()?.len(), 1);
+    assert_eq!(handler.resolve_room("Building 10/1F/101")?, room);
+    assert_eq!(
+        handler.surplus(&room)?.soc,
+        KilowattHours::from_kwh(s.surplus.into())
+    );
+    assert!(!handler.user_recharge_records(1, None)?.is_empty());
+    assert!(!handler.room_recharge_records(1, &room)?.is_empty());
+    let results = handler.surplus_many(&[room.clone(), room.clone()], &Default::default());
+    assert!(results.iter().all(|r| r.is_ok()));
+
+    let lab = handler.resolve_room("Building 20/1F/101")?;
+    handler.bind_room(&lab)?;
+    let bindings = handler.bindings()?;
+    handler.rebind_room(&bindings[1], &room)?;
+    assert!(matches!(
+        handler.bind_room(&lab),
+        Err(Error::Api {
+            status_code: 203,
+            ..
+        })
+    ));
+    handler.unbind_room(&bindings[0])?;
+    assert_eq!(handler.bindings()?.len(), 1);
+
+    let order = handler.recharge_order(&room, 10, "50426", "照明用电", "token", &s.uid)?;
+    let options = TrackOptions {
+        interval: std::time::Duration::from_millis(10),
+        timeout: std::time::Duration::from_millis(50),
+    };
+    let outcome = handler.track_order(&order, &options)?;
+    assert_eq!(outcome.status, OrderStatus::Paid);
+    assert!(outcome.timed_out && outcome.credited.is_none());
+    assert_eq!(handler.order_record(&order)?, outcome.record);
+
+    let (session, _) = yxy::blocking::wrapper::app_auth_with_config(&s.uid, config(&server))?;
+    assert_eq!(session, s.session);
+
+    server.script(|s| s.no_bind = true);
+    assert!(matches!(handler.binding_info(), Err(Error::NoBind)));
+
+    Ok(())
+}
+
+#[test]
+fn campus() -> Result {
+    let (_runtime, server) = start();
+    let s = server.scenario();
+
+    let handler = LoginHandler::build_with_config(s.device_id.clone(), config(&server))?;
+    let info = handler.silent_login(&s.uid, None)?;
+    assert_eq!(info.token, s.token);
+
+    let handler = CampusHandler::build_with_config(
+        &s.device_id,
+        &s.uid,
+        &s.school_code,
+        Some(&info.token),
+        config(&server),
+    )?;
+    assert_eq!(handler.card_balance()?.to_string(), s.card_balance);
+    assert_eq!(handler.transaction_records(0, 2)?.items.len(), 2);
+    let all: Vec<_> = handler
+        .transaction_records_iter(2, None)
+        .collect::<std::result::Result<_, Error>>()?;
+    assert_eq!(all.len(), 3);
+    assert_eq!(handler.as_async().uid, s.uid);
+
+    let handler = CampusHandler::silent_login(&s.device_id, &s.uid, config(&server))?;
+    assert_eq!(handler.token(), s.token);
+    assert_eq!(handler.refresh()?, s.token);
+    assert!(!handler.needs_login());
+
+    Ok(())
+}
@@ -80,7 +80,6 @@ async fn app_auth() -> Result {
 #[ignore]
 #[tokio::test]
 async fn campus() {
-    use chrono::prelude::*;
     use yxy::bind::campus::*;
 
     let handler = Arc::new(
@@ -95,15 +94,10 @@ async fn campus() {
 
     let h1 = handler.clone();
     let task1 = tokio::spawn(async move {
-        let dt = Utc::now();
-        let time = dt.format("%Y%m%d").to_string();
-        println!("Local time: {}", &time);
-        let result = h1.consumption_records(&time).await;
-        match result {
-            Ok(v) => println!("{:#?}", v),
-            Err(yxy::error::Error::EmptyResp) => {}
-            Err(e) => panic!("{}", e),
-        }
+        let today = yxy::time::today();
+        println!("Today: {}", today);
+        let records = h1.consumption_records(today).await.unwrap();
+        println!("{:#?}", records);
     });
 
     let h1 = handler.clone();
@@ -0,0 +1,734 @@
+//! Tests against the local emulation server, no network or credentials required
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use futures_util::TryStreamExt;
+
+use yxy::bind::app::order::{OrderStatus, TrackOptions};
+use yxy::bind::app::AppHandler;
+use yxy::bind::bulk::BulkOptions;
+use yxy::bind::campus::{login::LoginHandler, CampusHandler};
+use yxy::error::Error;
+use yxy::{
+    AppSession, Endpoints, HandlerConfig, KilowattHours, RateLimiter, RetryPolicy, RoomInfo,
+};
+use yxy_mock::{message, MockServer, Scenario};
+type Result = std::result::Result<(), Box<dyn std::error::Error>>;
+
+fn config(server: &MockServer) -> HandlerConfig {
+    Endpoints::with_base(server.url()).into()
+}
+
+async fn app_handler(server: &MockServer) -> AppHandler {
+    AppHandler::build_by_uid_with_config(&server.scenario().uid, config(server))
+        .await
+        .unwrap()
+}
+
+fn campus_handler(server: &MockServer) -> CampusHandler {
+    let s = server.scenario();
+    CampusHandler::build_with_config(
+        &s.device_id,
+        &s.uid,
+        &s.school_code,
+        Some(&s.token),
+        config(server),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn app_auth() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+
+    assert_eq!(handler.session(), server.scenario().session);
+    assert_eq!(handler.user_info().await?.id, server.scenario().uid);
+
+    let result = AppHandler::build_by_uid_with_config("unknown", config(&server)).await;
+    assert!(matches!(result, Err(Error::EmptyResp)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn electricity() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+
+    let room = RoomInfo::from(handler.binding_info().await?);
+    let surplus = handler.surplus(&room).await?;
+    assert_eq!(surplus.room_code, room.room_code);
+    assert_eq!(
+        surplus.soc,
+        KilowattHours::from_kwh(server.scenario().surplus.into())
+    );
+
+    let md_type = &surplus.surplus_list[0].mdtype;
+    assert!(!handler.usage_records(&room, md_type).await?.is_empty());
+    assert!(!handler.user_recharge_records(1, None).await?.is_empty());
+    let page = handler.room_recharge_records(1, &room).await?;
+    assert_eq!((page.items.len(), page.next), (1, None));
+    // No rows is not an error
+    assert!(handler.room_recharge_records(2, &room).await?.is_empty());
+    let records: Vec<_> = handler
+        .room_recharge_records_stream(&room, None)
+        .try_collect()
+        .await?;
+    assert_eq!(records, page.items);
+
+    let tran_no = handler
+        .recharge(
+            &room,
+            10,
+            "50426",
+            "照明用电",
+            "token",
+            &server.scenario().uid,
+        )
+        .await?;
+    assert!(!tran_no.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn electricity_failures() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+
+    server.script(|s| s.no_bind = true);
+    let result = handler.binding_info().await;
+    assert!(matches!(result, Err(Error::NoBind)));
+    assert!(result.unwrap_err().is_not_found());
+
+    server.script(|s| s.session_expired = true);
+    let result = handler.binding_info().await;
+    assert!(matches!(result, Err(Error::Auth(_))));
+    assert!(result.unwrap_err().is_auth_expired());
+    assert!(matches!(handler.user_info().await, Err(Error::Auth(_))));
+    assert_eq!(server.hits("/app/electric/queryBind"), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn electricity_bindings() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+    assert_eq!(handler.bindings().await?.len(), 1);
+
+    server.script(|s| s.extra_bindings = vec!["20101".to_string()]);
+    let bindings = handler.bindings().await?;
+    let codes: Vec<_> = bindings.iter().map(|b| b.room_code.as_str()).collect();
+    assert_eq!(codes, ["20101", "10101"]);
+    assert_eq!(handler.binding_info().await?, bindings[1]);
+    assert!(bindings[0].matches_room("r20101"));
+    assert!(bindings[1].matches_room("Building 10 1F 101"));
+    assert!(!bindings[1].matches_room(""));
+
+    let all = yxy::wrapper::query_ele_all_with_config(handler.session(), config(&server)).await?;
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].1.as_ref().unwrap().room_code, "20101");
+
+    server.script(|s| s.no_bind = true);
+    assert!(handler.bindings().await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn room_directory() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+
+    assert_eq!(handler.areas().await?.len(), 2);
+    let buildings = handler.buildings("1").await?;
+    assert_eq!(buildings[0].name, "Building 10");
+    let floors = handler.floors("1", &buildings[0].code).await?;
+    let rooms = handler.rooms("1", "10", &floors[0].code).await?;
+    assert_eq!(rooms[0].code, "10101");
+
+    let bound = RoomInfo::from(handler.binding_info().await?);
+    assert_eq!(handler.resolve_room("Building 10/1F/101").await?, bound);
+    assert_eq!(
+        handler.resolve_room("main / building10 / 1f / 101").await?,
+        bound
+    );
+
+    let found = handler.find_rooms("Building/02").await?;
+    assert_eq!(found.len(), 6);
+    assert_eq!(found[0].to_string(), "Main/Building 10/1F/102");
+    let result = handler.resolve_room("Building/02").await;
+    assert!(matches!(result, Err(Error::BadInput(_))));
+    let result = handler.resolve_room("Building 10/301").await;
+    assert!(matches!(result, Err(Error::BadInput(_))));
+    assert!(matches!(
+        handler.find_rooms("101").await,
+        Err(Error::BadInput(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn electricity_bind_changes() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+
+    let lab = handler.resolve_room("Building 20/1F/101").await?;
+    handler.bind_room(&lab).await?;
+    // Already bound, not a locked binding
+    let result = handler.bind_room(&lab).await;
+    assert!(matches!(
+        result,
+        Err(Error::Api {
+            status_code: 203,
+            ..
+        })
+    ));
+    let bindings = handler.bindings().await?;
+    assert_eq!(bindings.len(), 2);
+    assert!(bindings.iter().all(|b| b.is_changeable()));
+
+    let dorm = handler.resolve_room("Building 10/2F/202").await?;
+    handler.rebind_room(&bindings[1], &dorm).await?;
+    assert_eq!(handler.binding_info().await?.room_code, "10202");
+
+    handler.unbind_room(&bindings[0]).await?;
+    let bindings = handler.bindings().await?;
+    assert_eq!(bindings.len(), 1);
+    handler.unbind_room(&bindings[0]).await?;
+    assert!(handler.bindings().await?.is_empty());
+    handler.bind_room(&lab).await?;
+    assert_eq!(handler.binding_info().await?.room_code, "20101");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn electricity_bind_locked() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+
+    let binding = handler.binding_info().await?;
+    let room = RoomInfo::from(&binding);
+    server.script(|s| s.bind_locked = true);
+    assert!(matches!(
+        handler.bind_room(&room).await,
+        Err(Error::BindNotAllowed(_))
+    ));
+    assert!(matches!(
+        handler.rebind_room(&binding, &room).await,
+        Err(Error::BindNotAllowed(_))
+    ));
+
+    // Refused before any request
+    let binding = handler.binding_info().await?;
+    assert!(!binding.is_changeable());
+    assert!(matches!(
+        handler.unbind_room(&binding).await,
+        Err(Error::BindNotAllowed(_))
+    ));
+    assert_eq!(server.hits("/app/electric/delBind"), 0);
+    assert_eq!(server.hits("/app/electric/updateBind"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recharge_order_tracking() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+    let options = TrackOptions {
+        interval: Duration::from_millis(20),
+        timeout: Duration::from_millis(100),
+    };
+
+    let room = RoomInfo::from(handler.binding_info().await?);
+    let order = handler
+        .recharge_order(
+            &room,
+            10,
+            "50426",
+            "照明用电",
+            "token",
+            &server.scenario().uid,
+        )
+        .await?;
+    assert_eq!(order.soc_before, KilowattHours::from_kwh(100.0));
+
+    server.script(|s| s.pay_status = "待支付".to_string());
+    let outcome = handler.track_order(&order, &options).await?;
+    assert_eq!(outcome.status, OrderStatus::Pending);
+    assert!(outcome.timed_out && !outcome.is_settled());
+    assert!(server.hits("/app/order/bussisdw/queryListData") > 1);
+
+    // Paid, but not credited yet
+    server.script(|s| s.pay_status = "支付成功".to_string());
+    let outcome = handler.track_order(&order, &options).await?;
+    assert_eq!(outcome.status, OrderStatus::Paid);
+    assert!(outcome.timed_out && outcome.credited.is_none());
+
+    server.script(|s| s.surplus = 110.0);
+    let outcome = handler.track_order(&order, &options).await?;
+    assert!(outcome.is_settled() && !outcome.timed_out);
+    assert_eq!(outcome.credited, Some(KilowattHours::from_kwh(10.0)));
+    assert!(outcome.credited_time.is_some());
+    assert_eq!(outcome.record.unwrap().order_no, order.tran_no);
+
+    server.script(|s| s.pay_status = "已退款".to_string());
+    let outcome = handler.track_order(&order, &options).await?;
+    assert_eq!(outcome.status, OrderStatus::Refunded);
+    assert!(outcome.is_settled() && outcome.surplus.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn electricity_bulk() -> Result {
+    let server = MockServer::start().await?;
+    let handler = app_handler(&server).await;
+    server.script(|s| s.missing_rooms = vec!["10102".to_string()]);
+
+    let bound = RoomInfo::from(handler.binding_info().await?);
+    let rooms: Vec<_> = ["10101", "10102", "10103"]
+        .into_iter()
+        .map(|code| RoomInfo {
+            room_code: code.to_string(),
+            ..bound.clone()
+        })
+        .collect();
+    let options = BulkOptions {
+        concurrency: 2,
+        interval: Duration::ZERO,
+    };
+
+    let results = handler.surplus_many(&rooms, &options).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().room_code, "10101");
+    assert!(matches!(
+        results[1],
+        Err(Error::Api {
+            status_code: 500,
+            ..
+        })
+    ));
+    assert_eq!(results[2].as_ref().unwrap().room_code, "10103");
+    assert_eq!(server.hits("/app/electric/queryISIMSRoomSurplus"), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn app_session() -> Result {
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+
+    let changes = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let hook = changes.clone();
+    let app = AppSession::with_config(&s.uid, Some("expired".to_string()), config(&server))?
+        .on_change(move |session| hook.lock().unwrap().push(session.to_string()));
+
+    // Concurrent calls of the expired session re-authorize only once
+    let tasks: Vec<_> = (0..10)
+        .map(|_| {
+            let app = app.clone();
+            tokio::spawn(async move { app.binding_info().await })
+        })
+        .collect();
+    for task in tasks {
+        task.await??;
+    }
+    assert_eq!(server.hits("/authoriz/getCodeV2"), 1);
+    assert_eq!(server.hits("/app/electric/queryBind"), 20);
+    assert_eq!(app.session().as_deref(), Some(s.session.as_str()));
+    assert_eq!(changes.lock().unwrap().as_slice(), [s.session.as_str()]);
+
+    // Authorized on the first call without session, the hook set on a clone is shared
+    let app = AppSession::with_config(&s.uid, None, config(&server))?;
+    let hook = changes.clone();
+    let _ = app
+        .clone()
+        .on_change(move |session| hook.lock().unwrap().push(session.to_string()));
+    assert_eq!(app.user_info().await?.id, s.uid);
+    assert_eq!(server.hits("/authoriz/getCodeV2"), 2);
+    assert_eq!(changes.lock().unwrap().len(), 2);
+    assert_eq!(app.find_rooms("Building 20/2F/201").await?.len(), 1);
+
+    // Retried only once
+    server.script(|s| s.session_expired = true);
+    assert!(matches!(app.binding_info().await, Err(Error::Auth(_))));
+    assert_eq!(server.hits("/authoriz/getCodeV2"), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry() -> Result {
+    let server = MockServer::start().await?;
+    let config = HandlerConfig {
+        retry: Some(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            ..Default::default()
+        }),
+        ..config(&server)
+    };
+    let handler = AppHandler::build_by_uid_with_config(&server.scenario().uid, config).await?;
+
+    server.script(|s| s.unavailable = 2);
+    handler.binding_info().await?;
+    assert_eq!(server.hits("/app/electric/queryBind"), 3);
+
+    server.script(|s| s.unavailable = 3);
+    let result = handler.binding_info().await;
+    assert!(matches!(result, Err(Error::Http { status: 503, .. })));
+    assert_eq!(server.hits("/app/electric/queryBind"), 6);
+
+    // Never retry recharge
+    let room = RoomInfo::from(handler.binding_info().await?);
+    server.script(|s| s.unavailable = 1);
+    let result = handler
+        .recharge(
+            &room,
+            10,
+            "50426",
+            "照明用电",
+            "token",
+            &server.scenario().uid,
+        )
+        .await;
+    assert!(result.unwrap_err().is_retryable());
+    assert_eq!(server.hits("/app/electric/recharge.htm"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limit() -> Result {
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+    let config = HandlerConfig {
+        limiter: Some(RateLimiter::default()),
+        ..config(&server)
+    };
+
+    let handler = LoginHandler::build_with_config(s.device_id.clone(), config.clone())?;
+    let token = handler.security_token().await?;
+    handler
+        .send_verification_code(&s.phone_num, &token.security_token, None)
+        .await?;
+
+    // Shared by another handler
+    let handler = LoginHandler::build_with_config("other".to_string(), config)?;
+    let result = handler
+        .send_verification_code(&s.phone_num, &token.security_token, None)
+        .await;
+    assert!(matches!(result, Err(Error::Throttled { .. })));
+    assert_eq!(server.hits("/compus/user/sendLoginVerificationCode"), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn login_by_code() -> Result {
+    let server = MockServer::start_with(Scenario {
+        security_level: 1,
+        ..Default::default()
+    })
+    .await?;
+    let s = server.scenario();
+
+    let handler = LoginHandler::build_with_config("new-device".to_string(), config(&server))?;
+
+    let token = handler.security_token().await?;
+    assert_eq!(token.level, 1);
+
+    let captcha = handler.captcha_image(&token.security_token).await?;
+    assert!(!captcha.is_empty());
+    assert!(matches!(
+        handler.captcha_image("bad").await,
+        Err(Error::BadInput(_))
+    ));
+
+    let user_exists = handler
+        .send_verification_code(&s.phone_num, &token.security_token, Some(&s.captcha))
+        .await?;
+    assert!(user_exists);
+
+    let result = handler.login_by_code(&s.phone_num, "000000").await;
+    assert!(matches!(result, Err(Error::BadLoginSecret)));
+
+    let info = handler
+        .login_by_code(&s.phone_num, &s.verification_code)
+        .await?;
+    assert_eq!(info.id, s.uid);
+    assert_eq!(server.scenario().device_id, "new-device");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_verification_code_failures() -> Result {
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+    let handler = LoginHandler::build_with_config(s.device_id.clone(), config(&server))?;
+    let token = handler.security_token().await?;
+
+    let result = handler
+        .send_verification_code("123", &token.security_token, None)
+        .await;
+    assert!(matches!(result, Err(Error::BadPhoneNumber)));
+
+    for message in [
+        message::FLOW_CONTROL,
+        message::TOO_FREQUENT,
+        message::TOO_MANY_TRIES,
+    ] {
+        server.script(|s| s.sms_error = Some(message.to_string()));
+        let result = handler
+            .send_verification_code(&s.phone_num, &token.security_token, None)
+            .await;
+        assert!(matches!(result, Err(Error::Limited)));
+        assert!(result.unwrap_err().is_rate_limited());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn login_by_password() -> Result {
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+
+    let handler = LoginHandler::build_with_config(s.device_id.clone(), config(&server))?;
+    let public_key = handler.public_key().await?;
+
+    let info = handler
+        .login_by_password(&s.phone_num, &s.password, &public_key)
+        .await?;
+    assert_eq!(info.token, s.token);
+
+    let result = handler
+        .login_by_password(&s.phone_num, "wrong", &public_key)
+        .await;
+    assert!(matches!(result, Err(Error::BadLoginSecret)));
+
+    let handler = LoginHandler::build_with_config("other".to_string(), config(&server))?;
+    let result = handler
+        .login_by_password(&s.phone_num, &s.password, &public_key)
+        .await;
+    assert!(matches!(result, Err(Error::AuthDeviceChanged)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn silent_login() -> Result {
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+
+    let handler = LoginHandler::build_with_config(s.device_id.clone(), config(&server))?;
+    assert_eq!(handler.silent_login(&s.uid, None).await?.token, s.token);
+
+    let result = handler.silent_login("unknown", None).await;
+    assert!(matches!(result, Err(Error::AuthUserNotFound)));
+
+    server.script(|s| s.device_changed = true);
+    let result = handler.silent_login(&s.uid, None).await;
+    assert!(matches!(result, Err(Error::AuthDeviceChanged)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn campus_user() -> Result {
+    let server = MockServer::start().await?;
+    let handler = campus_handler(&server);
+
+    assert_eq!(
+        handler.card_balance().await?.to_string(),
+        server.scenario().card_balance
+    );
+
+    let date = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+    let records = handler.consumption_records(date).await?;
+    assert_eq!(records[0].dealtime.date(), Some(date));
+    let days = date..=date.succ_opt().unwrap();
+    assert_eq!(
+        handler.consumption_records_between(days).await?.len(),
+        2 * records.len()
+    );
+
+    let page = handler.transaction_records(0, 2).await?;
+    assert_eq!(
+        (page.items.len(), page.total, page.next),
+        (2, Some(3), Some(2))
+    );
+    assert_eq!(handler.transaction_records(2, 2).await?.next, None);
+    assert!(handler.transaction_records(10, 2).await?.is_empty());
+
+    let all: Vec<_> = handler
+        .transaction_records_stream(2, None)
+        .try_collect()
+        .await?;
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[..2], page.items);
+    let bounded: Vec<_> = handler
+        .transaction_records_stream(1, Some(2))
+        .try_collect()
+        .await?;
+    assert_eq!(bounded.len(), 2);
+
+    server.script(|s| s.device_changed = true);
+    assert!(matches!(
+        handler.card_balance().await,
+        Err(Error::AuthDeviceChanged)
+    ));
+
+    server.script(|s| {
+        s.device_changed = false;
+        s.token_expired = true;
+    });
+    assert!(matches!(
+        handler.transaction_records(0, 2).await,
+        Err(Error::Auth(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn campus_auto_refresh() -> Result {
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+
+    let handler = CampusHandler::silent_login(&s.device_id, &s.uid, config(&server)).await?;
+    assert_eq!(handler.token(), s.token);
+    assert_eq!(handler.school_code, s.school_code);
+    assert_eq!(server.hits("/login/doLoginBySilent"), 1);
+
+    // Expired token is refreshed once for concurrent calls
+    let handler = CampusHandler::build_with_config(
+        &s.device_id,
+        &s.uid,
+        &s.school_code,
+        Some("stale"),
+        config(&server),
+    )?
+    .auto_refresh(true);
+    let (a, b) = tokio::join!(handler.card_balance(), handler.card_balance());
+    assert_eq!(a?.to_string(), s.card_balance);
+    assert_eq!(b?.to_string(), s.card_balance);
+    assert_eq!(handler.token(), s.token);
+    assert_eq!(server.hits("/login/doLoginBySilent"), 2);
+
+    // Changed device is terminal
+    server.script(|s| s.device_changed = true);
+    assert!(matches!(
+        handler.card_balance().await,
+        Err(Error::AuthDeviceChanged)
+    ));
+    assert!(handler.needs_login());
+
+    let hits = server.hits("/compus/user/getCardMoney");
+    server.script(|s| s.device_changed = false);
+    assert!(matches!(
+        handler.card_balance().await,
+        Err(Error::AuthDeviceChanged)
+    ));
+    assert_eq!(server.hits("/compus/user/getCardMoney"), hits);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn credential_store() -> Result {
+    use std::sync::Arc;
+    use yxy::store::{CredentialStore, Credentials, MemoryStore, Token};
+
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+    let store = Arc::new(MemoryStore::default());
+
+    // Authorized session is written back and reused
+    let app = AppSession::from_store(store.clone(), &s.uid, config(&server))?;
+    app.binding_info().await?;
+    let credentials = store.load(&s.uid)?.unwrap();
+    assert_eq!(credentials.app_session(), Some(s.session.as_str()));
+
+    let app = AppSession::from_store(store.clone(), &s.uid, config(&server))?;
+    assert_eq!(app.session(), Some(s.session.clone()));
+
+    // Campus handler requires the device ID
+    assert!(matches!(
+        CampusHandler::from_store(store.clone(), &s.uid, config(&server)),
+        Err(Error::BadInput(_))
+    ));
+
+    let login = LoginHandler::build_with_config(s.device_id.clone(), config(&server))?;
+    let info = login.silent_login(&s.uid, None).await?;
+    let credentials = Credentials::from(&info);
+    assert_eq!(credentials.device_id, Some(s.device_id.clone()));
+    assert_eq!(credentials.campus_token(), Some(s.token.as_str()));
+
+    // Stale token is refreshed and written back
+    store.update(&s.uid, &mut |c| {
+        c.device_id = Some(s.device_id.clone());
+        c.campus_token = Some(Token::new("stale"));
+    })?;
+    let handler = CampusHandler::from_store(store.clone(), &s.uid, config(&server))?;
+    assert_eq!(handler.card_balance().await?.to_string(), s.card_balance);
+
+    let credentials = store.load(&s.uid)?.unwrap();
+    assert_eq!(credentials.campus_token(), Some(s.token.as_str()));
+    assert_eq!(credentials.school_code, Some(s.school_code.clone()));
+    assert_eq!(credentials.app_session(), Some(s.session.as_str()));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cassette_record_replay() -> Result {
+    use yxy::cassette::{scrub::PLACEHOLDER, Cassette};
+
+    let path = std::env::temp_dir().join(format!("yxy-cassette-{}.json", std::process::id()));
+
+    let server = MockServer::start().await?;
+    let s = server.scenario();
+    let config = HandlerConfig {
+        cassette: Some(Cassette::record(&path)),
+        ..config(&server)
+    };
+    let handler = CampusHandler::build_with_config(
+        &s.device_id,
+        &s.uid,
+        &s.school_code,
+        Some(&s.token),
+        config,
+    )?;
+    let balance = handler.card_balance().await?;
+    let records = handler.transaction_records(0, 2).await?;
+
+    let content = std::fs::read_to_string(&path)?;
+    assert!(content.contains(PLACEHOLDER));
+    assert!(!content.contains(&s.token));
+    assert!(!content.contains(&s.uid));
+    drop(server);
+
+    // Replay without server
+    let config = HandlerConfig {
+        cassette: Some(Cassette::replay(&path)?),
+        ..Default::default()
+    };
+    let handler =
+        CampusHandler::build_with_config(&s.device_id, &s.uid, &s.school_code, None, config)?;
+    assert_eq!(handler.card_balance().await?, balance);
+    assert_eq!(handler.transaction_records(0, 2).await?, records);
+    assert!(handler.card_balance().await.is_err());
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
@@ -50,7 +50,7 @@ pub unsafe extern "C" fn query_ele_bind(handler: *const AppHandler) -> *mut Room
     check_null_return_null!(handler);
     let handler = &*handler;
 
-    match handler.query_electricity_binding() {
+    match handler.binding_info() {
         Ok(bind) => {
             let room = yxy::blocking::RoomInfo::from(bind);
             Box::into_raw(Box::new(RoomInfo::from(room)))
@@ -82,22 +82,22 @@ pub struct ElectricityInfo {
 
 extern_c_destructor!(ElectricityInfo);
 
-impl TryFrom<yxy::blocking::ElectricityInfo> for ElectricityInfo {
+impl TryFrom<yxy::blocking::SurplusInfo> for ElectricityInfo {
     type Error = ();
 
-    fn try_from(mut info: yxy::blocking::ElectricityInfo) -> Result<Self, Self::Error> {
+    fn try_from(mut info: yxy::blocking::SurplusInfo) -> Result<Self, Self::Error> {
         if info.surplus_list.is_empty() {
             return Err(());
         }
         let surplus = info.surplus_list.swap_remove(0);
 
         Ok(Self {
-            total_surplus: info.soc,
-            total_amount: info.total_soc_amount,
-            surplus: surplus.surplus,
-            surplus_amount: surplus.amount,
-            subsidy: surplus.subsidy,
-            subsidy_amount: surplus.subsidy_amount,
+            total_surplus: info.soc.kwh() as c_float,
+            total_amount: info.total_soc_amount.yuan() as c_float,
+            surplus: surplus.surplus.kwh() as c_float,
+            surplus_amount: surplus.amount.yuan() as c_float,
+            subsidy: surplus.subsidy.kwh() as c_float,
+            subsidy_amount: surplus.subsidy_amount.yuan() as c_float,
 
             area_id: CString::new(info.area_id).unwrap_or_default().into_raw(),
             building_code: CString::new(info.building_code)
@@ -123,7 +123,7 @@ pub unsafe extern "C" fn query_ele(handler: *const AppHandler) -> *mut Electrici
     let handler = &*handler;
 
     // Get RoomInfo by default binding
-    let bind = match handler.query_electricity_binding() {
+    let bind = match handler.binding_info() {
         Ok(bind) => bind,
         Err(e) => {
             eprintln!("{e}");
@@ -132,7 +132,7 @@ pub unsafe extern "C" fn query_ele(handler: *const AppHandler) -> *mut Electrici
     };
     let room = yxy::blocking::RoomInfo::from(bind);
 
-    match handler.query_electricity(&room) {
+    match handler.surplus(&room) {
         Ok(info) => match ElectricityInfo::try_from(info) {
             Ok(info) => Box::into_raw(Box::new(info)),
             Err(_) => std::ptr::null_mut(),
@@ -155,7 +155,7 @@ pub unsafe extern "C" fn query_ele_by_room_info(
     let handler = &*handler;
     let room = yxy::blocking::RoomInfo::from(&*info);
 
-    match handler.query_electricity(&room) {
+    match handler.surplus(&room) {
         Ok(info) => match ElectricityInfo::try_from(info) {
             Ok(info) => Box::into_raw(Box::new(info)),
             Err(_) => std::ptr::null_mut(),
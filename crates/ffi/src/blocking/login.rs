@@ -28,7 +28,7 @@ pub unsafe extern "C" fn build_login_handler(device_id: *mut c_char) -> *mut Log
     check_null_return_null!(device_id);
 
     let device_id = c_string_to_str(device_id);
-    let handler = match LoginHandler::build(device_id) {
+    let handler = match LoginHandler::build(device_id.to_string()) {
         Ok(handler) => Box::new(handler),
         Err(_) => return std::ptr::null_mut(),
     };
@@ -57,7 +57,7 @@ pub unsafe extern "C" fn get_security_token(handler: *const LoginHandler) -> *mu
     check_null_return_null!(handler);
 
     let handler = &*handler;
-    let token = match handler.get_security_token() {
+    let token = match handler.security_token() {
         Ok(token) => token,
         Err(e) => {
             eprintln!("{e}");
@@ -86,7 +86,7 @@ pub unsafe extern "C" fn get_captcha_image(
 
     let handler = &*handler;
     let token = c_string_to_str(security_token);
-    let image = match handler.get_captcha_image(token) {
+    let image = match handler.captcha_image(token) {
         Ok(image) => image,
         Err(e) => {
             eprintln!("{e}");
@@ -133,11 +133,7 @@ pub unsafe extern "C" fn send_verification_code(
         Ok(v) => !v as c_int,
         Err(e) => {
             eprintln!("{e}");
-            match e {
-                yxy::error::Error::BadPhoneNumber => -10, // Bad phone number
-                yxy::error::Error::Limited => -11,        // Too may requests, limited
-                _ => -1,                                  // Return -1 on other error
-            }
+            ErrorCode::from(&e) as c_int
         }
     }
 }
@@ -170,8 +166,7 @@ pub unsafe extern "C" fn do_login_by_code(
     check_null_return_null!(handler, phone_number, code);
 
     let handler = &*handler;
-    let info = match handler.do_login_by_code(c_string_to_str(phone_number), c_string_to_str(code))
-    {
+    let info = match handler.login_by_code(c_string_to_str(phone_number), c_string_to_str(code)) {
         Ok(info) => info,
         Err(e) => {
             eprintln!("{e}");
@@ -1,4 +1,5 @@
-//! # Warning: Not actively maintained
+//! Bindings of [`yxy::blocking`]
+//!
 //! This feature is not enabled by default.
 
 use crate::check_null_return;
@@ -35,6 +35,23 @@ pub enum ErrorCode {
     ECaptcha = -13,
 }
 
+impl From<&yxy::error::Error> for ErrorCode {
+    fn from(e: &yxy::error::Error) -> Self {
+        use yxy::error::Error;
+
+        match e {
+            e if e.is_auth_expired() => Self::EAuth,
+            e if e.is_not_found() => Self::ENoFound,
+            e if e.is_rate_limited() => Self::ELimited,
+            Error::AuthDeviceChanged => Self::EAuth,
+            Error::BadPhoneNumber => Self::EPhoneNum,
+            Error::BadLoginSecret => Self::EVeriCode,
+            Error::BadInput(_) => Self::EInvalid,
+            _ => Self::ERROR,
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! check_null_return_null {
     ($($arg:expr),+) => {
@@ -4,12 +4,12 @@ use axum::{extract::Query, http::StatusCode, Json};
 use yxy::error::Error;
 use yxy::{wrapper::*, AppHandler};
 
-use super::{error_result, success_result};
+use super::{config, error_result, success_result};
 use crate::handler::{HttpResult, ResultE};
 
 /// Build [`AppHandler`]
 fn build_handler(token: &str) -> ResultE<AppHandler> {
-    match AppHandler::build(token) {
+    match AppHandler::build_with_config(token, config()) {
         Ok(v) => Ok(v),
         Err(e) => Err(Json((StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e).into())),
     }
@@ -20,10 +20,9 @@ pub mod auth {
     use crate::model::app::auth;
 
     pub async fn by_uid(query: Query<auth::Request>) -> HttpResult<auth::Response> {
-        match app_auth(&query.uid).await {
+        match app_auth_with_config(&query.uid, config()).await {
             Ok(r) => success_result(auth::Response::from(r)),
-            Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 }
@@ -39,20 +38,28 @@ pub mod electricity {
         pub async fn by_user(
             Query(TokenRequest { token }): Query<TokenRequest>,
         ) -> HttpResult<Response> {
-            match query_ele(&token).await {
+            match query_ele_with_config(&token, config()).await {
                 Ok(v) => success_result(Response::from(v)),
-                Err(e @ Error::NoBind) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-                Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-                Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+                Err(e) => error_result(e),
             }
         }
 
-        pub async fn by_room(v: Query<RoomInfoRequest>) -> HttpResult<Response> {
-            let (token, room_info) = v.0.split();
-            match query_ele_by_room_info(&token, &room_info).await {
+        pub async fn by_room(
+            Query(RoomInfoRequest { token, room_info }): Query<RoomInfoRequest>,
+        ) -> HttpResult<Response> {
+            match query_ele_by_room_info_with_config(&token, &room_info, config()).await {
                 Ok(v) => success_result(Response::from(v)),
-                Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-                Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+                Err(e) => error_result(e),
+            }
+        }
+
+        /// Electricity of every binding
+        pub async fn by_binds(
+            Query(TokenRequest { token }): Query<TokenRequest>,
+        ) -> HttpResult<electricity::all::Response> {
+            match query_ele_all_with_config(&token, config()).await {
+                Ok(v) => success_result(v.into()),
+                Err(e) => error_result(e),
             }
         }
     }
@@ -64,11 +71,143 @@ pub mod electricity {
         pub async fn by_user(
             Query(bind::Request { token }): Query<bind::Request>,
         ) -> HttpResult<Response> {
-            match query_ele_bind(&token).await {
+            match query_ele_bind_with_config(&token, config()).await {
                 Ok(v) => success_result(Response::from(v)),
-                Err(e @ Error::NoBind) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-                Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-                Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn all(
+            Query(bind::Request { token }): Query<bind::Request>,
+        ) -> HttpResult<ListResponse> {
+            match query_ele_binds_with_config(&token, config()).await {
+                Ok(v) => success_result(ListResponse::from(v)),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn create(
+            Json(CreateRequest { token, room_info }): Json<CreateRequest>,
+        ) -> HttpResult<()> {
+            let handler = build_handler(&token)?;
+
+            match handler.bind_room(&room_info).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn change(
+            Json(ChangeRequest {
+                token,
+                id,
+                room_info,
+            }): Json<ChangeRequest>,
+        ) -> HttpResult<()> {
+            let handler = build_handler(&token)?;
+            let binding = match find_binding(&handler, &id).await {
+                Ok(v) => v,
+                Err(e) => return error_result(e),
+            };
+
+            match handler.rebind_room(&binding, &room_info).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn remove(
+            Json(RemoveRequest { token, id }): Json<RemoveRequest>,
+        ) -> HttpResult<()> {
+            let handler = build_handler(&token)?;
+            let binding = match find_binding(&handler, &id).await {
+                Ok(v) => v,
+                Err(e) => return error_result(e),
+            };
+
+            match handler.unbind_room(&binding).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        /// Binding of the ID, [`Error::NoBind`] if none
+        async fn find_binding(handler: &AppHandler, id: &str) -> Result<yxy::BindInfo, Error> {
+            handler
+                .bindings()
+                .await?
+                .into_iter()
+                .find(|b| b.id == id)
+                .ok_or(Error::NoBind)
+        }
+    }
+
+    pub mod directory {
+        use super::*;
+        use electricity::directory::*;
+
+        pub async fn areas(
+            Query(AreasRequest { token }): Query<AreasRequest>,
+        ) -> HttpResult<Vec<Area>> {
+            let handler = build_handler(&token)?;
+
+            match handler.areas().await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn buildings(
+            Query(BuildingsRequest { token, area_id }): Query<BuildingsRequest>,
+        ) -> HttpResult<Vec<Building>> {
+            let handler = build_handler(&token)?;
+
+            match handler.buildings(&area_id).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn floors(
+            Query(FloorsRequest {
+                token,
+                area_id,
+                building_code,
+            }): Query<FloorsRequest>,
+        ) -> HttpResult<Vec<Floor>> {
+            let handler = build_handler(&token)?;
+
+            match handler.floors(&area_id, &building_code).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        pub async fn rooms(
+            Query(RoomsRequest {
+                token,
+                area_id,
+                building_code,
+                floor_code,
+            }): Query<RoomsRequest>,
+        ) -> HttpResult<Vec<Room>> {
+            let handler = build_handler(&token)?;
+
+            match handler.rooms(&area_id, &building_code, &floor_code).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
+            }
+        }
+
+        /// Rooms matching a path of names, see [`yxy::bind::app::directory::RoomQuery`]
+        pub async fn search(
+            Query(SearchRequest { token, path }): Query<SearchRequest>,
+        ) -> HttpResult<Vec<RoomEntry>> {
+            let handler = build_handler(&token)?;
+
+            match handler.find_rooms(&path).await {
+                Ok(v) => success_result(v),
+                Err(e) => error_result(e),
             }
         }
     }
@@ -77,14 +216,18 @@ pub mod electricity {
         use super::*;
         use electricity::consumption::*;
 
-        pub async fn by_room(Query(v): Query<Request>) -> HttpResult<Response> {
-            let (token, md_type, room_info) = v.split();
+        pub async fn by_room(
+            Query(Request {
+                token,
+                room_info,
+                md_type,
+            }): Query<Request>,
+        ) -> HttpResult<Response> {
             let handler = build_handler(&token)?;
 
             match handler.usage_records(&room_info, &md_type).await {
                 Ok(v) => success_result(Response::from(v)),
-                Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-                Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+                Err(e) => error_result(e),
             }
         }
     }
@@ -100,10 +243,8 @@ pub mod electricity {
             let handler = build_handler(&token)?;
 
             match handler.room_recharge_records(page, &room_info).await {
-                Ok(v) => success_result(ByRoomResponse::from(v)),
-                Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-                Err(e @ Error::EmptyResp) => error_result(StatusCode::NO_CONTENT.as_u16(), e),
-                Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+                Ok(v) => success_result(ByRoomResponse::from(v.items)),
+                Err(e) => error_result(e),
             }
         }
 
@@ -111,15 +252,17 @@ pub mod electricity {
             Query(ByUserRequest { token, page, time }): Query<ByUserRequest>,
         ) -> HttpResult<ByUserResponse> {
             let handler = build_handler(&token)?;
+            let month = match time.as_deref().map(yxy::time::parse_date).transpose() {
+                Ok(v) => v,
+                Err(e) => return error_result(e),
+            };
 
-            match handler.user_recharge_records(page, time.as_deref()).await {
-                Ok(v) => match ByUserResponse::try_from(v) {
+            match handler.user_recharge_records(page, month).await {
+                Ok(v) => match ByUserResponse::try_from(v.items) {
                     Ok(v) => success_result(v),
-                    Err(()) => error_result(StatusCode::NO_CONTENT.as_u16(), Error::EmptyResp),
+                    Err(()) => error_result(Error::EmptyResp),
                 },
-                Err(e @ Error::Auth(_)) => error_result(StatusCode::UNAUTHORIZED.as_u16(), e),
-                Err(e @ Error::EmptyResp) => error_result(StatusCode::NO_CONTENT.as_u16(), e),
-                Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+                Err(e) => error_result(e),
             }
         }
     }
@@ -13,7 +13,7 @@ fn build_handler(
     school_code: &str,
     token: Option<&str>,
 ) -> ResultE<CampusHandler> {
-    match CampusHandler::build(device_id, uid, school_code, token) {
+    match CampusHandler::build_with_config(device_id, uid, school_code, token, config()) {
         Ok(v) => Ok(v),
         Err(e) => Err(Json((StatusCode::BAD_REQUEST.as_u16(), e).into())),
     }
@@ -30,7 +30,13 @@ impl TryFrom<BasicInfo> for CampusHandler {
             school_code,
         }: BasicInfo,
     ) -> ResultE<Self> {
-        match CampusHandler::build(&device_id, &uid, &school_code, token.as_deref()) {
+        match CampusHandler::build_with_config(
+            &device_id,
+            &uid,
+            &school_code,
+            token.as_deref(),
+            config(),
+        ) {
             Ok(v) => Ok(v),
             Err(e) => Err(Json((StatusCode::BAD_REQUEST.as_u16(), e).into())),
         }
@@ -45,7 +51,7 @@ pub mod login {
 
     /// Build [`LoginHandler`]
     fn build_handler(device_id: String) -> ResultE<LoginHandler> {
-        match LoginHandler::build(device_id) {
+        match LoginHandler::build_with_config(device_id, config()) {
             Ok(v) => Ok(v),
             Err(e) => Err(Json((StatusCode::BAD_REQUEST.as_u16(), e).into())),
         }
@@ -58,7 +64,7 @@ pub mod login {
 
         match handler.security_token().await {
             Ok(v) => success_result(v.into()),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -72,8 +78,7 @@ pub mod login {
 
         match handler.captcha_image(&security_token).await {
             Ok(v) => success_result(response::CaptchaImage { img: v }),
-            Err(e @ Error::BadInput(_)) => error_result(StatusCode::BAD_REQUEST.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -87,18 +92,14 @@ pub mod login {
     ) -> HttpResult<response::SendVerificationCode> {
         let handler = build_handler(device_id)?;
         if security_token.len() < 32 {
-            return error_result(
-                StatusCode::BAD_REQUEST.as_u16(),
-                Error::BadInput("security_token".to_string()),
-            );
+            return error_result(Error::BadInput("security_token".to_string()));
         }
         match handler
             .send_verification_code(&phone_num, &security_token, captcha.as_deref())
             .await
         {
             Ok(v) => success_result(response::SendVerificationCode { user_exists: v }),
-            Err(e @ Error::BadInput(_)) => error_result(StatusCode::BAD_REQUEST.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -113,8 +114,7 @@ pub mod login {
 
         match handler.login_by_code(&phone_num, &code).await {
             Ok(v) => success_result(v.into()),
-            Err(e @ Error::BadLoginSecret) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -129,9 +129,7 @@ pub mod login {
 
         match handler.silent_login(&uid, token.as_deref()).await {
             Ok(v) => success_result(v.into()),
-            Err(e @ Error::AuthUserNotFound) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-            Err(e @ Error::AuthDeviceChanged) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -142,7 +140,7 @@ pub mod login {
 
         match handler.public_key().await {
             Ok(v) => success_result(response::PublicKey { key: v }),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -161,9 +159,7 @@ pub mod login {
             .await
         {
             Ok(v) => success_result(v.into()),
-            Err(e @ Error::BadLoginSecret) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-            Err(e @ Error::AuthDeviceChanged) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 }
@@ -177,7 +173,7 @@ pub mod user {
 
         match handler.card_balance().await {
             Ok(v) => success_result(response::CardBalance { balance: v }),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -191,12 +187,14 @@ pub mod user {
         }): Query<request::ConsumptionRecords>,
     ) -> HttpResult<response::ConsumptionRecords> {
         let handler = build_handler(&device_id, &uid, &school_code, token.as_deref())?;
+        let date = match yxy::time::parse_date(&query_time) {
+            Ok(v) => v,
+            Err(e) => return error_result(e),
+        };
 
-        match handler.consumption_records(&query_time).await {
+        match handler.consumption_records(date).await {
             Ok(v) => success_result(v.into()),
-            Err(e @ Error::NoBind) => error_result(StatusCode::FORBIDDEN.as_u16(), e),
-            Err(e @ Error::EmptyResp) => error_result(StatusCode::NO_CONTENT.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Err(e) => error_result(e),
         }
     }
 
@@ -213,9 +211,8 @@ pub mod user {
         let handler = build_handler(&device_id, &uid, &school_code, token.as_deref())?;
 
         match handler.transaction_records(offset, limit).await {
-            Ok(v) => success_result(v.into()),
-            Err(e @ Error::EmptyResp) => error_result(StatusCode::NO_CONTENT.as_u16(), e),
-            Err(e) => error_result(StatusCode::INTERNAL_SERVER_ERROR.as_u16(), e),
+            Ok(v) => success_result(v),
+            Err(e) => error_result(e),
         }
     }
 }
@@ -1,4 +1,7 @@
-use axum::Json;
+use std::sync::OnceLock;
+
+use axum::{http::StatusCode, Json};
+use yxy::{error::Error, ClientConfig, HandlerConfig, RateLimiter};
 
 use crate::model::{ErrorResponse, SuccessResponse};
 
@@ -8,10 +11,51 @@ pub mod campus;
 type ResultE<T> = Result<T, Json<ErrorResponse>>;
 type HttpResult<T> = std::result::Result<Json<SuccessResponse<T>>, Json<ErrorResponse>>;
 
+static CONFIG: OnceLock<HandlerConfig> = OnceLock::new();
+
+/// Set HTTP client settings of every handler
+///
+/// Takes effect only if called before serving any request.
+pub fn set_client_config(client: ClientConfig) {
+    let _ = CONFIG.set(HandlerConfig {
+        client,
+        ..default_config()
+    });
+}
+
+/// The rate limiter is shared by the handlers of all users.
+fn default_config() -> HandlerConfig {
+    HandlerConfig {
+        limiter: Some(RateLimiter::default()),
+        ..Default::default()
+    }
+}
+
+/// Config of every handler
+fn config() -> HandlerConfig {
+    CONFIG.get_or_init(default_config).clone()
+}
+
 fn success_result<T>(data: T) -> HttpResult<T> {
     HttpResult::Ok(Json(SuccessResponse::new(data)))
 }
 
-fn error_result<T>(code: u16, error: yxy::error::Error) -> HttpResult<T> {
-    HttpResult::Err(Json((code, error).into()))
+fn error_result<T>(error: Error) -> HttpResult<T> {
+    HttpResult::Err(Json((status_code(&error).as_u16(), error).into()))
+}
+
+/// Response status of library error
+fn status_code(error: &Error) -> StatusCode {
+    match error {
+        e if e.is_auth_expired() => StatusCode::UNAUTHORIZED,
+        e if e.is_not_found() => StatusCode::FORBIDDEN,
+        e if e.is_rate_limited() => StatusCode::TOO_MANY_REQUESTS,
+        e if e.is_retryable() => StatusCode::BAD_GATEWAY,
+        Error::AuthDeviceChanged | Error::BadLoginSecret | Error::BindNotAllowed(_) => {
+            StatusCode::FORBIDDEN
+        }
+        Error::BadInput(_) | Error::BadPhoneNumber => StatusCode::BAD_REQUEST,
+        Error::EmptyResp => StatusCode::NO_CONTENT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
 }
@@ -1,7 +1,10 @@
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::Parser;
-use yxy_httpd::router;
+use yxy::ClientConfig;
+use yxy_httpd::{handler, router};
 
 #[cfg(target_env = "musl")]
 use tikv_jemallocator::Jemalloc;
@@ -19,6 +22,14 @@ async fn main() {
     // Init global logger
     tracing_subscriber::fmt::init();
 
+    match args.client_config() {
+        Ok(config) => handler::set_client_config(config),
+        Err(e) => {
+            tracing::error!("Invalid upstream client options: {}", e);
+            std::process::exit(1);
+        }
+    }
+
     let app = router::init();
 
     tracing::info!("Listening on: {}", addr);
@@ -34,4 +45,40 @@ struct Args {
     /// HTTPd binding address
     #[clap(short, long, default_value = "127.0.0.1:3000")]
     bind: SocketAddr,
+
+    /// Proxy of upstream requests, e.g. `http://proxy:3128` or `socks5://127.0.0.1:1080`
+    #[clap(long)]
+    proxy: Option<String>,
+
+    /// Extra trusted root certificate in PEM, can be repeated
+    #[clap(long)]
+    ca_cert: Vec<PathBuf>,
+
+    /// Local address of upstream requests
+    #[clap(long)]
+    local_address: Option<IpAddr>,
+
+    /// Timeout of upstream requests in seconds
+    #[clap(long, default_value_t = 30)]
+    timeout: u64,
+}
+
+impl Args {
+    fn client_config(&self) -> Result<ClientConfig, Box<dyn std::error::Error>> {
+        let mut config = ClientConfig {
+            timeout: Some(Duration::from_secs(self.timeout)),
+            local_address: self.local_address,
+            ..Default::default()
+        };
+
+        if let Some(ref proxy) = self.proxy {
+            config = config.with_proxy(proxy)?;
+        }
+        for path in &self.ca_cert {
+            let pem = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            config = config.with_root_certificate(&pem)?;
+        }
+
+        Ok(config)
+    }
 }
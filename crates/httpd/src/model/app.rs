@@ -56,31 +56,15 @@ pub mod electricity {
     use super::*;
 
     pub mod subsidy {
-        use yxy::{RoomInfo, SurplusInfo};
+        use yxy::{KilowattHours, Money, RoomInfo, SurplusInfo};
 
         use super::*;
 
         #[derive(Deserialize)]
         pub struct RoomInfoRequest {
             pub token: String,
-            pub area_id: String,
-            pub building_code: String,
-            pub floor_code: String,
-            pub room_code: String,
-        }
-
-        impl RoomInfoRequest {
-            pub fn split(self) -> (String, RoomInfo) {
-                (
-                    self.token,
-                    RoomInfo {
-                        area_id: self.area_id,
-                        building_code: self.building_code,
-                        floor_code: self.floor_code,
-                        room_code: self.room_code,
-                    },
-                )
-            }
+            #[serde(flatten)]
+            pub room_info: RoomInfo,
         }
 
         #[derive(Deserialize)]
@@ -99,14 +83,14 @@ pub mod electricity {
             /// Room name
             pub display_room_name: String,
             /// Total surplus
-            pub soc: f32,
+            pub soc: KilowattHours,
             /// Total surplus amount
-            pub soc_amount: f32,
+            pub soc_amount: Money,
 
-            pub surplus: Option<f32>,
-            pub surplus_amount: Option<f32>,
-            pub subsidy: Option<f32>,
-            pub subsidy_amount: Option<f32>,
+            pub surplus: Option<KilowattHours>,
+            pub surplus_amount: Option<Money>,
+            pub subsidy: Option<KilowattHours>,
+            pub subsidy_amount: Option<Money>,
 
             /// Type
             pub md_type: Option<String>,
@@ -157,8 +141,46 @@ pub mod electricity {
         }
     }
 
+    pub mod all {
+        use yxy::wrapper::BoundSurplus;
+
+        use super::*;
+
+        #[derive(Serialize)]
+        pub struct Response(Vec<Item>);
+
+        /// Electricity of a binding, or the error of the query
+        #[derive(Serialize)]
+        pub struct Item {
+            pub bind: bind::Response,
+            pub subsidy: Option<subsidy::Response>,
+            pub error: Option<String>,
+        }
+
+        impl From<BoundSurplus> for Item {
+            fn from((bind, result): BoundSurplus) -> Self {
+                let (subsidy, error) = match result {
+                    Ok(v) => (Some(v.into()), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+
+                Self {
+                    bind: bind.into(),
+                    subsidy,
+                    error,
+                }
+            }
+        }
+
+        impl From<Vec<BoundSurplus>> for Response {
+            fn from(v: Vec<BoundSurplus>) -> Self {
+                Self(v.into_iter().map(Item::from).collect())
+            }
+        }
+    }
+
     pub mod bind {
-        use yxy::BindInfo;
+        use yxy::{BindInfo, RoomInfo, Timestamp};
 
         use super::*;
 
@@ -168,6 +190,29 @@ pub mod electricity {
             pub token: String,
         }
 
+        #[derive(Deserialize)]
+        pub struct CreateRequest {
+            pub token: String,
+            #[serde(flatten)]
+            pub room_info: RoomInfo,
+        }
+
+        #[derive(Deserialize)]
+        pub struct ChangeRequest {
+            pub token: String,
+            /// ID of the binding to change
+            pub id: String,
+            #[serde(flatten)]
+            pub room_info: RoomInfo,
+        }
+
+        #[derive(Deserialize)]
+        pub struct RemoveRequest {
+            pub token: String,
+            /// ID of the binding to remove
+            pub id: String,
+        }
+
         #[derive(Serialize)]
         pub struct Response {
             pub id: String,
@@ -182,7 +227,8 @@ pub mod electricity {
             pub room_code: String,
             pub room_name: String,
             pub bind_type: String,
-            pub create_time: String,
+            pub create_time: Timestamp,
+            pub is_allow_change: u8,
         }
 
         impl From<BindInfo> for Response {
@@ -201,24 +247,73 @@ pub mod electricity {
                     room_name: v.room_name,
                     bind_type: v.bind_type_str,
                     create_time: v.create_time,
+                    is_allow_change: v.is_allow_change,
                 }
             }
         }
-    }
 
-    pub mod consumption {
-        use yxy::{RoomInfo, UsageRecord};
+        #[derive(Serialize)]
+        pub struct ListResponse(Vec<Response>);
 
+        impl From<Vec<BindInfo>> for ListResponse {
+            fn from(v: Vec<BindInfo>) -> Self {
+                Self(v.into_iter().map(Response::from).collect())
+            }
+        }
+    }
+
+    /// Room directory, to pick a room without a binding
+    pub mod directory {
         use super::*;
 
+        pub use yxy::bind::app::directory::{Area, Building, Floor, Room, RoomEntry};
+
         #[derive(Deserialize)]
-        pub struct Request {
+        pub struct AreasRequest {
             /// Session Token
             pub token: String,
+        }
+
+        #[derive(Deserialize)]
+        pub struct BuildingsRequest {
+            pub token: String,
+            pub area_id: String,
+        }
+
+        #[derive(Deserialize)]
+        pub struct FloorsRequest {
+            pub token: String,
+            pub area_id: String,
+            pub building_code: String,
+        }
+
+        #[derive(Deserialize)]
+        pub struct RoomsRequest {
+            pub token: String,
             pub area_id: String,
             pub building_code: String,
             pub floor_code: String,
-            pub room_code: String,
+        }
+
+        #[derive(Deserialize)]
+        pub struct SearchRequest {
+            pub token: String,
+            /// Path of names, like `Building 10/1F/101`
+            pub path: String,
+        }
+    }
+
+    pub mod consumption {
+        use yxy::{KilowattHours, RoomInfo, Timestamp, UsageRecord};
+
+        use super::*;
+
+        #[derive(Deserialize)]
+        pub struct Request {
+            /// Session Token
+            pub token: String,
+            #[serde(flatten)]
+            pub room_info: RoomInfo,
             pub md_type: String,
         }
 
@@ -228,23 +323,8 @@ pub mod electricity {
         #[derive(Serialize)]
         pub struct Record {
             pub room_dm: String,
-            pub datetime: String,
-            pub used: String,
-        }
-
-        impl Request {
-            pub fn split(self) -> (String, String, RoomInfo) {
-                (
-                    self.token,
-                    self.md_type,
-                    RoomInfo {
-                        area_id: self.area_id,
-                        building_code: self.building_code,
-                        floor_code: self.floor_code,
-                        room_code: self.room_code,
-                    },
-                )
-            }
+            pub datetime: Timestamp,
+            pub used: KilowattHours,
         }
 
         impl From<UsageRecord> for Record {
@@ -265,7 +345,7 @@ pub mod electricity {
     }
 
     pub mod recharge {
-        use yxy::{RechargeRecord, RoomInfo, UserRechargeRecord};
+        use yxy::{Money, RechargeRecord, RoomInfo, Timestamp, UserRechargeRecord};
 
         use super::*;
 
@@ -273,7 +353,7 @@ pub mod electricity {
         pub struct ByUserRequest {
             pub token: String,
             pub page: u32,
-            /// Transaction creation time
+            /// Month of the records, like "2022-01"
             pub time: Option<String>,
         }
 
@@ -309,8 +389,8 @@ pub mod electricity {
         pub struct ByUserResponse {
             records: Vec<UserRecord>,
             months: String,
-            total_transaction_money: String,
-            total_real_money: String,
+            total_transaction_money: Money,
+            total_real_money: Money,
             total_count: i64,
         }
 
@@ -318,17 +398,17 @@ pub mod electricity {
         pub struct UserRecord {
             pub id: String,
             pub order_no: String,
-            pub pay_money: f64,
-            pub total_money: f64,
+            pub pay_money: Money,
+            pub total_money: Money,
             pub pay_no: String,
             pub pay_type: String,
-            pub create_time: String,
+            pub create_time: Timestamp,
             pub pay_status_str: String,
             pub sub_type: String,
             pub prod_name: String,
-            pub pay_time: String,
+            pub pay_time: Timestamp,
             pub remark: String,
-            pub fee_money: f64,
+            pub fee_money: Money,
             pub week: String,
             pub day: String,
             pub month: String,
@@ -404,9 +484,9 @@ pub mod electricity {
         #[derive(Serialize)]
         pub struct RoomRecord {
             pub room_dm: String,
-            pub datetime: String,
+            pub datetime: Timestamp,
             pub using_type: String,
-            pub money: String,
+            pub money: Money,
             pub is_send: String,
         }
 
@@ -49,7 +49,7 @@ pub mod login {
     }
 
     pub mod response {
-        use yxy::SecurityTokenInfo;
+        use yxy::{SecurityTokenInfo, Timestamp};
 
         use super::*;
 
@@ -97,9 +97,9 @@ pub mod login {
             pub identity_no: Option<String>,
             pub user_class: Option<String>,
             pub real_name_status: i32,
-            pub regiser_time: Option<String>,
+            pub regiser_time: Option<Timestamp>,
             pub bind_card_status: i32,
-            pub last_login: String,
+            pub last_login: Timestamp,
             pub test_account: i32,
             pub is_new: Option<i8>,
             pub create_status: i32,
@@ -171,6 +171,7 @@ pub mod user {
             pub token: Option<String>,
             pub uid: String,
             pub school_code: String,
+            /// Date, like "20220101" or "2022-01-01"
             pub query_time: String,
         }
 
@@ -187,12 +188,13 @@ pub mod user {
 
     pub mod response {
         use yxy::bind::campus::user;
+        use yxy::{Money, Timestamp};
 
         use super::*;
 
         #[derive(Serialize)]
         pub struct CardBalance {
-            pub balance: String,
+            pub balance: Money,
         }
 
         #[derive(Serialize)]
@@ -200,10 +202,10 @@ pub mod user {
             #[serde(rename = "type")]
             pub row_type: String,
             pub fee_name: String,
-            pub time: String,
+            pub time: Timestamp,
             pub serial_no: String,
-            pub money: String,
-            pub deal_time: String,
+            pub money: Money,
+            pub deal_time: Timestamp,
             pub address: String,
         }
 
@@ -229,72 +231,7 @@ pub mod user {
             }
         }
 
-        #[derive(Serialize)]
-        pub struct TransactionRecords {
-            pub total: i64,
-            pub trade_details: Vec<TransactionDetail>,
-            pub trade_counts: Vec<TransactionCount>,
-        }
-        #[derive(Serialize)]
-        pub struct TransactionDetail {
-            pub tran_no: String,
-            pub create_time: String,
-            pub pay_time: Option<String>,
-            pub tran_money: i64,
-            pub prod_name: String,
-            pub tran_state: i64,
-            pub tran_state_name: String,
-            pub refund_state: i64,
-            pub refund_state_name: String,
-            pub pay_name: Option<String>,
-            pub week_name: String,
-            pub application_id: String,
-            pub real_money: i64,
-        }
-
-        #[derive(Serialize)]
-        pub struct TransactionCount {
-            pub count_month: String,
-            pub total_num: i64,
-            pub total_income_amount: i64,
-            pub total_expend_amount: i64,
-        }
-
-        impl From<user::TransactionRecords> for TransactionRecords {
-            fn from(v: user::TransactionRecords) -> Self {
-                Self {
-                    total: v.total,
-                    trade_details: v
-                        .trade_details
-                        .into_iter()
-                        .map(|x| TransactionDetail {
-                            tran_no: x.tran_no,
-                            create_time: x.create_time,
-                            pay_time: x.pay_time,
-                            tran_money: x.tran_money,
-                            prod_name: x.prod_name,
-                            tran_state: x.tran_state,
-                            tran_state_name: x.tran_state_name,
-                            refund_state: x.refund_state,
-                            refund_state_name: x.refund_state_name,
-                            pay_name: x.pay_name,
-                            week_name: x.week_name,
-                            application_id: x.application_id,
-                            real_money: x.real_money,
-                        })
-                        .collect(),
-                    trade_counts: v
-                        .trade_counts
-                        .into_iter()
-                        .map(|x| TransactionCount {
-                            count_month: x.count_month,
-                            total_num: x.total_num,
-                            total_income_amount: x.total_income_amount,
-                            total_expend_amount: x.total_expend_amount,
-                        })
-                        .collect(),
-                }
-            }
-        }
+        /// Served as it is
+        pub type TransactionRecords = yxy::Page<user::TransactionDetail>;
     }
 }
@@ -49,8 +49,27 @@ pub fn init() -> Router {
                 Router::new()
                     .route("/subsidy/by_user", get(app::electricity::subsidy::by_user))
                     .route("/subsidy/by_room", get(app::electricity::subsidy::by_room))
-                    .route("/bind", get(app::electricity::bind::by_user))
+                    .route(
+                        "/subsidy/by_binds",
+                        get(app::electricity::subsidy::by_binds),
+                    )
+                    .route(
+                        "/bind",
+                        get(app::electricity::bind::by_user).post(app::electricity::bind::create),
+                    )
+                    .route("/bind/change", post(app::electricity::bind::change))
+                    .route("/bind/remove", post(app::electricity::bind::remove))
+                    .route("/binds", get(app::electricity::bind::all))
                     .route("/consumption", get(app::electricity::consumption::by_room))
+                    .nest(
+                        "/directory",
+                        Router::new()
+                            .route("/areas", get(app::electricity::directory::areas))
+                            .route("/buildings", get(app::electricity::directory::buildings))
+                            .route("/floors", get(app::electricity::directory::floors))
+                            .route("/rooms", get(app::electricity::directory::rooms))
+                            .route("/search", get(app::electricity::directory::search)),
+                    )
                     .nest(
                         "/recharge",
                         Router::new()
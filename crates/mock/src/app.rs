@@ -0,0 +1,300 @@
+//! Application APIs
+
+use std::collections::HashMap;
+
+use axum::{extract::State, http::HeaderMap, Form, Json};
+use serde_json::{json, Value};
+
+use crate::{auth::session, fixture, scenario::message, AppState, Scenario};
+
+type Fields = Form<HashMap<String, String>>;
+
+/// Reject request without valid session
+fn check_session(s: &Scenario, headers: &HeaderMap) -> Result<(), Json<Value>> {
+    if s.session_expired || session(headers).as_deref() != Some(s.session.as_str()) {
+        return Err(fixture::fail(204, message::UNAUTHORIZED));
+    }
+
+    Ok(())
+}
+
+fn field<'a>(form: &'a HashMap<String, String>, key: &str) -> &'a str {
+    form.get(key).map(String::as_str).unwrap_or_default()
+}
+
+fn room(form: &HashMap<String, String>) -> [&str; 4] {
+    [
+        field(form, "areaId"),
+        field(form, "buildingCode"),
+        field(form, "floorCode"),
+        field(form, "roomCode"),
+    ]
+}
+
+/// `POST /center/common/token/get.htm`
+pub async fn submit_token(State(shared): State<AppState>, Form(form): Fields) -> Json<Value> {
+    let s = shared.scenario();
+
+    if field(&form, "ymId") != s.uid {
+        return fixture::fail(500, "用户不存在");
+    }
+
+    fixture::data(json!("0123456789abcdef0123456789abcdef"))
+}
+
+/// `POST /app/electric/queryBind`
+pub async fn query_bind(State(shared): State<AppState>, headers: HeaderMap) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+
+    if s.no_bind {
+        return fixture::rows(vec![]);
+    }
+
+    let mut rows: Vec<_> = s
+        .extra_bindings
+        .iter()
+        .enumerate()
+        .map(|(i, code)| fixture::bind_room(&s, &(i + 2).to_string(), code, &format!("R{}", code)))
+        .collect();
+    rows.push(fixture::bind_info(&s));
+
+    fixture::rows(rows)
+}
+
+/// `POST /app/electric/queryISIMSRoomSurplus`
+pub async fn query_surplus(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+
+    if s.missing_rooms
+        .iter()
+        .any(|r| r == field(&form, "roomCode"))
+    {
+        return fixture::fail(500, message::NO_ROOM);
+    }
+
+    fixture::data(fixture::surplus_info(&s, room(&form)))
+}
+
+/// `POST /app/electric/saveBind`
+pub async fn save_bind(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let mut s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+    if s.bind_locked {
+        return fixture::fail(203, message::BIND_LOCKED);
+    }
+
+    let code = field(&form, "roomCode").to_string();
+    if s.no_bind {
+        s.no_bind = false;
+        s.bound_room = code;
+        s.extra_bindings.clear();
+    } else if s.bound_room == code || s.extra_bindings.contains(&code) {
+        return fixture::fail(203, message::ALREADY_BOUND);
+    } else {
+        s.extra_bindings.push(code);
+    }
+
+    fixture::data(json!(null))
+}
+
+/// Index of the binding `id` in the extra bindings, `None` for the default one
+fn binding_index(s: &Scenario, id: &str) -> Result<Option<usize>, Json<Value>> {
+    match id.parse::<usize>() {
+        Ok(1) if !s.no_bind => Ok(None),
+        Ok(n) if (2..s.extra_bindings.len() + 2).contains(&n) => Ok(Some(n - 2)),
+        _ => Err(fixture::fail(500, message::NO_BINDING)),
+    }
+}
+
+/// `POST /app/electric/updateBind`
+pub async fn update_bind(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let mut s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+    if s.bind_locked {
+        return fixture::fail(203, message::BIND_LOCKED);
+    }
+
+    let code = field(&form, "roomCode").to_string();
+    match binding_index(&s, field(&form, "id")) {
+        Ok(None) => s.bound_room = code,
+        Ok(Some(i)) => s.extra_bindings[i] = code,
+        Err(e) => return e,
+    }
+
+    fixture::data(json!(null))
+}
+
+/// `POST /app/electric/delBind`
+pub async fn delete_bind(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let mut s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+    if s.bind_locked {
+        return fixture::fail(203, message::BIND_LOCKED);
+    }
+
+    match binding_index(&s, field(&form, "id")) {
+        // The last extra binding takes the place of the default one
+        Ok(None) => match s.extra_bindings.pop() {
+            Some(code) => s.bound_room = code,
+            None => s.no_bind = true,
+        },
+        Ok(Some(i)) => {
+            s.extra_bindings.remove(i);
+        }
+        Err(e) => return e,
+    }
+
+    fixture::data(json!(null))
+}
+
+/// `POST /app/electric/queryArea`
+pub async fn query_areas(State(shared): State<AppState>, headers: HeaderMap) -> Json<Value> {
+    if let Err(e) = check_session(&shared.scenario(), &headers) {
+        return e;
+    }
+
+    fixture::rows(fixture::areas())
+}
+
+/// `POST /app/electric/queryBuilding`
+pub async fn query_buildings(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    if let Err(e) = check_session(&shared.scenario(), &headers) {
+        return e;
+    }
+
+    fixture::rows(fixture::buildings(field(&form, "areaId")))
+}
+
+/// `POST /app/electric/queryFloor`
+pub async fn query_floors(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    if let Err(e) = check_session(&shared.scenario(), &headers) {
+        return e;
+    }
+
+    fixture::rows(fixture::floors(field(&form, "buildingCode")))
+}
+
+/// `POST /app/electric/queryRoom`
+pub async fn query_rooms(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    if let Err(e) = check_session(&shared.scenario(), &headers) {
+        return e;
+    }
+
+    fixture::rows(fixture::rooms(field(&form, "floorCode")))
+}
+
+/// `POST /app/electric/getISIMSRecords`
+pub async fn usage_records(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+
+    fixture::rows(fixture::usage_records(field(&form, "roomCode")))
+}
+
+/// `POST /app/order/bussisdw/queryListData`
+///
+/// Only the first page has records.
+pub async fn user_recharge_records(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+
+    match field(&form, "currentPage") {
+        "1" => fixture::rows(fixture::user_recharge_records(&s)),
+        _ => fixture::rows(vec![]),
+    }
+}
+
+/// `POST /app/electric/queryISIMSRoomBuyRecord`
+///
+/// Only the first page has records.
+pub async fn room_recharge_records(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+
+    match field(&form, "currentPage") {
+        "1" => fixture::rows(fixture::room_recharge_records(field(&form, "roomCode"))),
+        _ => fixture::rows(vec![]),
+    }
+}
+
+/// `POST /app/electric/recharge.htm`
+///
+/// Returns the cashier URL carrying `tran_no`
+pub async fn recharge(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_session(&s, &headers) {
+        return e;
+    }
+
+    match field(&form, "money").parse::<u32>() {
+        Ok(v) if v > 0 => {}
+        _ => return fixture::fail(500, "充值金额错误"),
+    }
+
+    fixture::data(json!(format!(
+        "{}/pay/unified/toCashier.shtml?tran_no=20220101000000000001",
+        shared.url
+    )))
+}
@@ -0,0 +1,75 @@
+//! Auth & application authorize APIs
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Form,
+};
+
+use crate::{fixture, scenario::message, AppState};
+
+/// Cookie name of application session
+pub const SESSION_KEY: &str = "shiroJID";
+
+/// Read application session from `Cookie` header
+pub fn session(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|v| v.trim().split_once('='))
+        .find(|(k, _)| *k == SESSION_KEY)
+        .map(|(_, v)| v.to_string())
+}
+
+/// `GET /authoriz/getCodeV2`
+///
+/// Redirect to `callbackUrl` with OAuth code, or without it if the user is unknown.
+pub async fn oauth_code(
+    State(shared): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let s = shared.scenario();
+
+    let callback = query.get("callbackUrl").cloned().unwrap_or_default();
+    let location = match query.get("unionid") {
+        Some(uid) if *uid == s.uid => format!("{}?ymCode={}&errCode=0", callback, s.oauth_code),
+        _ => format!("{}?errCode=1", callback),
+    };
+
+    (StatusCode::FOUND, [(header::LOCATION, location)]).into_response()
+}
+
+/// `POST /app/login/getUser4Authorize`
+///
+/// Authorize by `code`, or query user info of the session cookie.
+pub async fn authorize(
+    State(shared): State<AppState>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let s = shared.scenario();
+
+    if let Some(code) = form.get("code") {
+        if *code != s.oauth_code {
+            return fixture::fail(500, "授权失败").into_response();
+        }
+
+        let cookie = format!("{}={}; Path=/; HttpOnly", SESSION_KEY, s.session);
+        return (
+            [(header::SET_COOKIE, cookie)],
+            fixture::data(fixture::user_info(&s)),
+        )
+            .into_response();
+    }
+
+    if s.session_expired || session(&headers).as_deref() != Some(s.session.as_str()) {
+        return fixture::fail(204, message::UNAUTHORIZED).into_response();
+    }
+
+    fixture::data(fixture::user_info(&s)).into_response()
+}
@@ -0,0 +1,188 @@
+//! Campus APP APIs
+
+use std::collections::HashMap;
+
+use axum::{extract::State, Form, Json};
+use base64::{engine::general_purpose, Engine};
+use rsa::{pkcs1::DecodeRsaPrivateKey, Pkcs1v15Encrypt, RsaPrivateKey};
+use serde_json::{json, Value};
+
+use crate::fixture::{self, PRIVATE_KEY, PUBLIC_KEY, SECURITY_TOKEN};
+use crate::{scenario::message, AppState, Scenario};
+
+type Fields = Form<HashMap<String, String>>;
+
+fn field<'a>(form: &'a HashMap<String, String>, key: &str) -> &'a str {
+    form.get(key).map(String::as_str).unwrap_or_default()
+}
+
+/// Reject request of unknown user or changed device
+fn check_device(s: &Scenario, form: &HashMap<String, String>) -> Result<(), Json<Value>> {
+    if field(form, "ymId") != s.uid {
+        return Err(fixture::fail_biz(204, "10010", "用户不存在"));
+    }
+    if s.device_changed || field(form, "deviceId") != s.device_id {
+        return Err(fixture::fail_biz(204, "10011", message::DEVICE_CHANGED));
+    }
+
+    Ok(())
+}
+
+/// Reject request without valid identity
+fn check_identity(s: &Scenario, form: &HashMap<String, String>) -> Result<(), Json<Value>> {
+    check_device(s, form)?;
+
+    if s.token_expired || field(form, "token") != s.token {
+        return Err(fixture::fail(204, message::UNAUTHORIZED));
+    }
+
+    Ok(())
+}
+
+/// Decrypt password encrypted by [`PUBLIC_KEY`], returns MD5 of it
+fn decrypt_password(encrypted: &str) -> Option<String> {
+    let key = RsaPrivateKey::from_pkcs1_pem(PRIVATE_KEY).ok()?;
+    let data = general_purpose::STANDARD.decode(encrypted).ok()?;
+    let decrypted = key.decrypt(Pkcs1v15Encrypt, &data).ok()?;
+
+    String::from_utf8(decrypted).ok()
+}
+
+/// `POST /common/security/token`
+pub async fn security_token(State(shared): State<AppState>) -> Json<Value> {
+    let s = shared.scenario();
+
+    fixture::data(json!({
+        "level": s.security_level,
+        "securityToken": SECURITY_TOKEN,
+    }))
+}
+
+/// `POST /common/security/imageCaptcha`
+pub async fn captcha_image(Form(form): Fields) -> Json<Value> {
+    if field(&form, "securityToken") != SECURITY_TOKEN {
+        return fixture::fail(500, message::BAD_TOKEN);
+    }
+
+    fixture::data(json!("data:image/png;base64,iVBORw0KGgo="))
+}
+
+/// `POST /compus/user/sendLoginVerificationCode`
+pub async fn send_verification_code(
+    State(shared): State<AppState>,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+
+    if field(&form, "securityToken") != SECURITY_TOKEN
+        || field(&form, "appSecurityToken").is_empty()
+    {
+        return fixture::fail(500, message::BAD_TOKEN);
+    }
+
+    let phone_num = field(&form, "mobilePhone");
+    if phone_num.len() != 11 || !phone_num.chars().all(|c| c.is_ascii_digit()) {
+        return fixture::fail(203, message::BAD_PHONE_NUM);
+    }
+
+    if s.security_level != 0 && field(&form, "imageCaptchaValue") != s.captcha {
+        return fixture::fail(203, "图形验证码错误");
+    }
+
+    if let Some(ref message) = s.sms_error {
+        return fixture::fail(203, message);
+    }
+
+    fixture::data(json!({ "userExists": phone_num == s.phone_num }))
+}
+
+/// `POST /login/doLoginByVerificationCode`
+///
+/// Binds the user to the requesting device on success.
+pub async fn login_by_code(State(shared): State<AppState>, Form(form): Fields) -> Json<Value> {
+    let mut s = shared.scenario();
+
+    if field(&form, "mobilePhone") != s.phone_num
+        || field(&form, "verificationCode") != s.verification_code
+    {
+        return fixture::fail(203, message::WRONG_SECRET);
+    }
+
+    s.device_id = field(&form, "deviceId").to_string();
+    s.device_changed = false;
+
+    fixture::data(fixture::login_info(&s))
+}
+
+/// `POST /login/doLoginBySilent`
+pub async fn silent_login(State(shared): State<AppState>, Form(form): Fields) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_device(&s, &form) {
+        return e;
+    }
+
+    fixture::data(fixture::login_info(&s))
+}
+
+/// `POST /login/getPublicKey`
+pub async fn public_key() -> Json<Value> {
+    fixture::data(json!({ "publicKey": PUBLIC_KEY }))
+}
+
+/// `POST /login/doLoginByPwd`
+///
+/// Only works on the bound device.
+pub async fn login_by_password(State(shared): State<AppState>, Form(form): Fields) -> Json<Value> {
+    let s = shared.scenario();
+
+    let password = decrypt_password(field(&form, "password"));
+    let expected = format!("{:x}", md5::compute(&s.password));
+    if field(&form, "mobilePhone") != s.phone_num || password.as_ref() != Some(&expected) {
+        return fixture::fail(203, message::WRONG_SECRET);
+    }
+
+    if s.device_changed || field(&form, "deviceId") != s.device_id {
+        return fixture::fail(203, message::DEVICE_CHANGED);
+    }
+
+    fixture::data(fixture::login_info(&s))
+}
+
+/// `POST /compus/user/getCardMoney`
+pub async fn card_balance(State(shared): State<AppState>, Form(form): Fields) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_identity(&s, &form) {
+        return e;
+    }
+
+    fixture::data(json!(s.card_balance))
+}
+
+/// `POST /compus/user/queryNo`
+pub async fn consumption_records(
+    State(shared): State<AppState>,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_identity(&s, &form) {
+        return e;
+    }
+
+    fixture::rows(fixture::consumption_records())
+}
+
+/// `POST /routepay/route/order/queryTradePage`
+pub async fn transaction_records(
+    State(shared): State<AppState>,
+    Form(form): Fields,
+) -> Json<Value> {
+    let s = shared.scenario();
+    if let Err(e) = check_identity(&s, &form) {
+        return e;
+    }
+
+    let offset = field(&form, "offset").parse().unwrap_or_default();
+    let limit = field(&form, "limit").parse().unwrap_or(10);
+
+    fixture::data(fixture::transaction_records(offset, limit))
+}
@@ -0,0 +1,214 @@
+//! # yxy-mock
+//!
+//! In-process emulation server of the xiaofubao auth, application and campus APIs.
+//!
+//! Every API is served from the same host, point the bindings at it by
+//! `yxy::Endpoints::with_base(server.url())`.
+//!
+//! The accepted credentials and the scripted failures are described by [`Scenario`],
+//! which can be modified at any time by [`MockServer::script`].
+//!
+//! ```no_run
+//! # async fn run() -> std::io::Result<()> {
+//! use yxy_mock::{message, MockServer, Scenario};
+//!
+//! let server = MockServer::start_with(Scenario {
+//!     sms_error: Some(message::FLOW_CONTROL.to_string()),
+//!     ..Default::default()
+//! })
+//! .await?;
+//!
+//! println!("Serving on {}", server.url());
+//!
+//! // Later requests of the application APIs will be rejected by 204
+//! server.script(|s| s.session_expired = true);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+mod app;
+mod auth;
+mod campus;
+mod fixture;
+mod scenario;
+
+pub use scenario::{message, Scenario};
+
+/// State shared by the routes
+struct Shared {
+    /// Base URL of the server
+    url: String,
+    scenario: Mutex<Scenario>,
+    /// Request counts keyed by path
+    hits: Mutex<HashMap<String, usize>>,
+}
+
+impl Shared {
+    fn scenario(&self) -> MutexGuard<'_, Scenario> {
+        self.scenario.lock().unwrap()
+    }
+}
+
+type AppState = Arc<Shared>;
+
+/// Running emulation server
+///
+/// The server is shut down when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    url: String,
+    shared: AppState,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Start the server on a random local port with the default [`Scenario`]
+    pub async fn start() -> std::io::Result<Self> {
+        Self::start_with(Scenario::default()).await
+    }
+
+    /// Start the server on a random local port with specific [`Scenario`]
+    pub async fn start_with(scenario: Scenario) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let url = format!("http://{}", addr);
+
+        let shared = Arc::new(Shared {
+            url: url.clone(),
+            scenario: Mutex::new(scenario),
+            hits: Mutex::new(HashMap::new()),
+        });
+
+        let router = router(shared.clone());
+        let task = tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+
+        Ok(Self {
+            addr,
+            url,
+            shared,
+            task,
+        })
+    }
+
+    /// Local address of the server
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:12345`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Modify the running [`Scenario`]
+    pub fn script(&self, f: impl FnOnce(&mut Scenario)) {
+        f(&mut self.shared.scenario());
+    }
+
+    /// Snapshot of the running [`Scenario`]
+    pub fn scenario(&self) -> Scenario {
+        self.shared.scenario().clone()
+    }
+
+    /// Count of requests received by the API path, e.g. `/app/electric/queryBind`
+    pub fn hits(&self, path: &str) -> usize {
+        let hits = self.shared.hits.lock().unwrap();
+        hits.get(path).copied().unwrap_or_default()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn router(shared: AppState) -> Router {
+    Router::new()
+        .route("/authoriz/getCodeV2", get(auth::oauth_code))
+        .route("/app/login/getUser4Authorize", post(auth::authorize))
+        .route("/center/common/token/get.htm", post(app::submit_token))
+        .route("/app/electric/queryBind", post(app::query_bind))
+        .route(
+            "/app/electric/queryISIMSRoomSurplus",
+            post(app::query_surplus),
+        )
+        .route("/app/electric/saveBind", post(app::save_bind))
+        .route("/app/electric/updateBind", post(app::update_bind))
+        .route("/app/electric/delBind", post(app::delete_bind))
+        .route("/app/electric/queryArea", post(app::query_areas))
+        .route("/app/electric/queryBuilding", post(app::query_buildings))
+        .route("/app/electric/queryFloor", post(app::query_floors))
+        .route("/app/electric/queryRoom", post(app::query_rooms))
+        .route("/app/electric/getISIMSRecords", post(app::usage_records))
+        .route(
+            "/app/order/bussisdw/queryListData",
+            post(app::user_recharge_records),
+        )
+        .route(
+            "/app/electric/queryISIMSRoomBuyRecord",
+            post(app::room_recharge_records),
+        )
+        .route("/app/electric/recharge.htm", post(app::recharge))
+        .route("/common/security/token", post(campus::security_token))
+        .route("/common/security/imageCaptcha", post(campus::captcha_image))
+        .route(
+            "/compus/user/sendLoginVerificationCode",
+            post(campus::send_verification_code),
+        )
+        .route(
+            "/login/doLoginByVerificationCode",
+            post(campus::login_by_code),
+        )
+        .route("/login/doLoginBySilent", post(campus::silent_login))
+        .route("/login/getPublicKey", post(campus::public_key))
+        .route("/login/doLoginByPwd", post(campus::login_by_password))
+        .route("/compus/user/getCardMoney", post(campus::card_balance))
+        .route("/compus/user/queryNo", post(campus::consumption_records))
+        .route(
+            "/routepay/route/order/queryTradePage",
+            post(campus::transaction_records),
+        )
+        .layer(middleware::from_fn_with_state(shared.clone(), unavailable))
+        .layer(middleware::from_fn_with_state(shared.clone(), count_hits))
+        .with_state(shared)
+}
+
+async fn count_hits(State(shared): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    {
+        let mut hits = shared.hits.lock().unwrap();
+        *hits.entry(req.uri().path().to_string()).or_default() += 1;
+    }
+
+    next.run(req).await
+}
+
+/// Answer by 503 if scripted by [`Scenario::unavailable`]
+async fn unavailable(State(shared): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    {
+        let mut scenario = shared.scenario();
+        if scenario.unavailable > 0 {
+            scenario.unavailable -= 1;
+            return StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
+    }
+
+    next.run(req).await
+}
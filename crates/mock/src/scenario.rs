@@ -0,0 +1,104 @@
+//! Scriptable behaviors of the server
+
+/// Accepted account and the scripted failures
+///
+/// Requests carrying other credentials are rejected the way the real APIs do.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// UID of the only registered user
+    pub uid: String,
+    /// Device bound to the user
+    pub device_id: String,
+    pub phone_num: String,
+    pub password: String,
+    pub school_code: String,
+    /// Campus APP session token
+    pub token: String,
+    /// Application session, issued as `shiroJID` cookie
+    pub session: String,
+    /// OAuth code issued by `getCodeV2`
+    pub oauth_code: String,
+    /// Code sent by SMS
+    pub verification_code: String,
+    /// Security level, image captcha is required if not 0
+    pub security_level: u8,
+    pub captcha: String,
+
+    /// Reject application session with status code 204
+    pub session_expired: bool,
+    /// Reject campus token with status code 204
+    pub token_expired: bool,
+    /// Reject campus requests with biz code 10011
+    pub device_changed: bool,
+    /// Reject SMS request with the message and status code 203, see [`message`]
+    pub sms_error: Option<String>,
+    /// Return no electricity binding
+    pub no_bind: bool,
+    /// Room code of the default binding, its name is the last 3 digits
+    pub bound_room: String,
+    /// Reject binding changes with status code 203, and mark bindings as not changeable
+    pub bind_locked: bool,
+    /// Codes of rooms bound besides the default one, listed before it
+    ///
+    /// Room names are the codes prefixed by `R`.
+    pub extra_bindings: Vec<String>,
+    /// Answer the next requests by `503 Service Unavailable`, decreased on each of them
+    pub unavailable: u32,
+    /// Room codes of which surplus queries fail by status code 500
+    pub missing_rooms: Vec<String>,
+
+    /// Pay status of the recharge order, like `支付成功` or `待支付`
+    pub pay_status: String,
+
+    /// Electricity surplus of the bound room, in kW·h
+    pub surplus: f32,
+    /// Campus card balance
+    pub card_balance: String,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            uid: "0123456789abcdef0123456789abcdef".to_string(),
+            device_id: "yunmaf0a1f70b83774ecf94b2e94900b6cefb".to_string(),
+            phone_num: "18888888888".to_string(),
+            password: "123456".to_string(),
+            school_code: "2333".to_string(),
+            token: "fedcba9876543210fedcba9876543210".to_string(),
+            session: "00000000-1111-2222-3333-444444444444".to_string(),
+            oauth_code: "d15246f70c1d48658231153c75feb73f".to_string(),
+            verification_code: "123456".to_string(),
+            security_level: 0,
+            captcha: "abcd".to_string(),
+            session_expired: false,
+            token_expired: false,
+            device_changed: false,
+            sms_error: None,
+            no_bind: false,
+            bound_room: "10101".to_string(),
+            bind_locked: false,
+            extra_bindings: Vec::new(),
+            unavailable: 0,
+            missing_rooms: Vec::new(),
+            pay_status: "支付成功".to_string(),
+            surplus: 100.0,
+            card_balance: "20.01".to_string(),
+        }
+    }
+}
+
+/// Error messages of the real APIs
+pub mod message {
+    pub const WRONG_SECRET: &str = "您已输错1次，还剩4次机会";
+    pub const BAD_PHONE_NUM: &str = "请输入正确的手机号";
+    pub const TOO_FREQUENT: &str = "经过你的多次尝试，请稍后再试";
+    pub const TOO_MANY_TRIES: &str = "发送超限，请明天再来";
+    pub const FLOW_CONTROL: &str = "触发号码天级流控";
+    pub const DEVICE_CHANGED: &str = "设备已更换";
+    pub const BAD_TOKEN: &str = "token无效";
+    pub const NO_ROOM: &str = "房间不存在";
+    pub const BIND_LOCKED: &str = "当前绑定不允许修改";
+    pub const ALREADY_BOUND: &str = "该房间已绑定";
+    pub const NO_BINDING: &str = "绑定信息不存在";
+    pub const UNAUTHORIZED: &str = "登录已失效，请重新登录";
+}